@@ -0,0 +1,693 @@
+// src-tauri/src/backup.rs
+// 백업 가져오기: 파일 검증 → v8로 올림 → 지금 데이터와 합칠 계획(미리보기) → 확인받은 계획만 저장.
+// 학교/집 노트북을 오가며 쓰는 경우를 위해 "전부 바꾸기 / 최신 것 유지 / 사본으로 합치기" 세 가지를 둔다.
+
+use crate::db::SyncReport;
+use crate::engine::advisor::fnv1a64;
+use crate::engine::time::{parse_ts_in, DEFAULT_TZ};
+use crate::engine::RecordItem;
+use crate::schema::{self, AppState, ChangeKind, MigrationReport, StoredCase};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// 사본으로 합친 케이스 제목 뒤에 붙임(원래 케이스와 구별)
+const COPY_TITLE_SUFFIX: &str = " (가져온 사본)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeMode {
+  /// 지금 데이터를 모두 백업 내용으로 바꿈
+  Replace,
+  /// 합치되, 같은 id는 더 최근에 고친 것(updatedAt)을 남김. 수정 시각이 없거나 같으면 사본으로 더함.
+  MergeNewest,
+  /// 합치되, 같은 id인데 내용이 다르면 백업 쪽을 새 id 사본으로 더함(아무것도 덮어쓰지 않음)
+  MergeCopy,
+}
+
+impl MergeMode {
+  fn as_str(self) -> &'static str {
+    match self {
+      MergeMode::Replace => "replace",
+      MergeMode::MergeNewest => "mergeNewest",
+      MergeMode::MergeCopy => "mergeCopy",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictKind {
+  Record,
+  Case,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Resolution {
+  /// 백업 쪽으로 바꿈
+  TakeIncoming,
+  /// 이 기기 쪽을 남김
+  KeepLocal,
+  /// 백업 쪽을 새 id로 더함
+  Copied,
+}
+
+/// 같은 id인데 내용이 다른 기록/케이스 하나
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Conflict {
+  pub kind: ConflictKind,
+  pub id: String,
+  /// 사람이 알아볼 이름(기록은 요약 앞부분, 케이스는 제목)
+  pub local_label: String,
+  pub incoming_label: String,
+  pub resolution: Resolution,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub new_id: Option<String>,
+  pub reason: String,
+}
+
+/// 합친 뒤 기록/케이스가 어떻게 바뀌는지
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffCounts {
+  /// 이 기기에 없던 것
+  pub added: usize,
+  /// 백업 쪽으로 바뀌는 것
+  pub updated: usize,
+  /// 양쪽이 같아 그대로인 것
+  pub unchanged: usize,
+  /// 충돌했지만 이 기기 쪽을 남긴 것
+  pub kept_local: usize,
+  /// 새 id 사본으로 더한 것
+  pub copied: usize,
+  /// 사라지는 것(전부 바꾸기에서만)
+  pub removed: usize,
+}
+
+/// 가져오기 미리보기. state는 저장할 결과라 UI로 보내지 않는다.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPlan {
+  pub mode: MergeMode,
+  /// 지금 데이터 + 합친 결과의 지문. 커밋할 때 그대로 돌려줘야 하고, 그사이 데이터가 바뀌면 거부한다.
+  pub plan_id: String,
+  pub incoming_records: usize,
+  pub incoming_cases: usize,
+  /// 백업 파일을 v8로 올린 보고서
+  pub migration: MigrationReport,
+  pub records: DiffCounts,
+  pub cases: DiffCounts,
+  pub conflicts: Vec<Conflict>,
+  /// 버린 필드, 백업에 없는 기록을 가리키던 연결 등
+  pub warnings: Vec<String>,
+  #[serde(skip)]
+  pub state: AppState,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+  #[serde(flatten)]
+  pub plan: ImportPlan,
+  /// false면 미리보기만 한 것
+  pub applied: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub sync: Option<SyncReport>,
+}
+
+/* -------------------- validate -------------------- */
+
+/// 백업 파일 모양 확인: 객체(또는 {v, exportedAt, state} 팩)이고 records(배열)/cases(객체) 중 하나는 있어야 한다
+pub fn validate(value: &Value) -> Result<(), String> {
+  let o = value.as_object().ok_or("백업 파일이 아니에요: 최상위가 객체가 아님")?;
+  let state = match o.get("state") {
+    Some(Value::Object(s)) => s,
+    Some(_) => return Err("백업 파일이 아니에요: state가 객체가 아님".into()),
+    None => o,
+  };
+  match (state.get("records"), state.get("cases")) {
+    (None, None) => Err("백업 파일이 아니에요: records/cases가 없음".into()),
+    (Some(r), _) if !r.is_array() => Err("백업 파일이 잘못됐어요: records가 배열이 아님".into()),
+    (_, Some(c)) if !c.is_object() => Err("백업 파일이 잘못됐어요: cases가 객체가 아님".into()),
+    _ => Ok(()),
+  }
+}
+
+/// 올리면서 버린 값만 경고로(기본값 채움은 migration 보고서에 그대로 있다)
+fn migration_warnings(report: &MigrationReport) -> Vec<String> {
+  report
+    .steps
+    .iter()
+    .flat_map(|s| s.changes.iter().map(move |c| (s, c)))
+    .filter(|(_, c)| c.kind == ChangeKind::Dropped)
+    .map(|(s, c)| format!("v{}→v{}: {} {}개 버림 ({})", s.from, s.to, c.path, c.count, c.note))
+    .collect()
+}
+
+/// 백업 안에 없는 기록을 가리키는 케이스 연결은 버린다(이 기기의 같은 id 기록에 잘못 붙지 않게)
+fn drop_dangling_links(state: &mut AppState, warnings: &mut Vec<String>) {
+  let ids: HashSet<&str> = state.records.iter().map(|r| r.id.as_str()).collect();
+  for c in state.cases.values_mut() {
+    let before = c.case_item.record_ids.len();
+    c.case_item.record_ids.retain(|rid| ids.contains(rid.as_str()));
+    let dropped = before - c.case_item.record_ids.len();
+    if dropped > 0 {
+      warnings.push(format!("케이스 {}: 백업에 없는 기록 {dropped}개 연결을 버림", c.case_item.id));
+    }
+  }
+}
+
+/* -------------------- plan -------------------- */
+
+fn empty_state() -> AppState {
+  AppState {
+    v: schema::CURRENT_VERSION,
+    tab: "records".into(),
+    selected_case_id: None,
+    records: Vec::new(),
+    cases: BTreeMap::new(),
+  }
+}
+
+/// 백업 JSON과 지금 상태(없으면 빈 상태)로 합칠 계획을 세운다. 저장은 하지 않는다.
+pub fn plan(json: &str, current: Option<AppState>, mode: MergeMode) -> Result<ImportPlan, String> {
+  let value: Value = serde_json::from_str(json).map_err(|e| format!("백업 파일을 읽을 수 없어요(JSON 아님): {e}"))?;
+  validate(&value)?;
+  let up = schema::upgrade(value)?;
+  let mut incoming = up.state;
+  let mut warnings = migration_warnings(&up.report);
+  drop_dangling_links(&mut incoming, &mut warnings);
+
+  let local = current.unwrap_or_else(empty_state);
+  let incoming_records = incoming.records.len();
+  let incoming_cases = incoming.cases.len();
+  let mut conflicts = Vec::new();
+
+  let (records, renamed, record_diff) = merge_records(&local.records, incoming.records, mode, &mut conflicts);
+  let mut incoming_case_list: Vec<StoredCase> = incoming.cases.into_values().collect();
+  if !renamed.is_empty() {
+    incoming_case_list.iter_mut().for_each(|c| remap_case(c, &renamed));
+  }
+  let (cases, case_diff) = merge_cases(&local.cases, incoming_case_list, mode, &mut conflicts);
+
+  let selected = match mode {
+    MergeMode::Replace => incoming.selected_case_id,
+    _ => local.selected_case_id.clone(),
+  }
+  .filter(|id| cases.contains_key(id));
+  let state = AppState {
+    v: schema::CURRENT_VERSION,
+    tab: local.tab.clone(),
+    selected_case_id: selected,
+    records,
+    cases,
+  };
+
+  Ok(ImportPlan {
+    mode,
+    plan_id: plan_id(&local, &state, mode)?,
+    incoming_records,
+    incoming_cases,
+    migration: up.report,
+    records: record_diff,
+    cases: case_diff,
+    conflicts,
+    warnings,
+    state,
+  })
+}
+
+/// 미리보기 때 받은 planId가 지금 다시 세운 계획과 같을 때만 저장한다(그사이 DB나 파일이 바뀌면 거부)
+pub fn check_confirm(plan: &ImportPlan, confirm: &str) -> Result<(), String> {
+  if confirm != plan.plan_id {
+    return Err("미리보기 이후 데이터가 바뀌었어요. 다시 미리보기를 확인해 주세요.".into());
+  }
+  Ok(())
+}
+
+fn plan_id(local: &AppState, merged: &AppState, mode: MergeMode) -> Result<String, String> {
+  let enc = |s: &AppState| serde_json::to_string(s).map_err(|e| format!("encode failed: {e}"));
+  let (a, b) = (enc(local)?, enc(merged)?);
+  Ok(format!("{:016x}", fnv1a64(&[mode.as_str(), &a, &b])))
+}
+
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+  match (serde_json::to_value(a), serde_json::to_value(b)) {
+    (Ok(x), Ok(y)) => x == y,
+    _ => false,
+  }
+}
+
+fn ts_of(s: &str) -> Option<DateTime<Utc>> {
+  parse_ts_in(s, DEFAULT_TZ).ok()
+}
+
+/// 양쪽 수정 시각(updatedAt)이 모두 있고 다를 때만 (백업 쪽, 이 기기 쪽). 그 밖에는 어느 쪽이 최신인지 모른다.
+fn edit_times(incoming: &str, local: &str) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+  match (ts_of(incoming), ts_of(local)) {
+    (Some(a), Some(b)) if a != b => Some((a, b)),
+    _ => None,
+  }
+}
+
+fn copy_reason(mode: MergeMode) -> &'static str {
+  match mode {
+    MergeMode::MergeNewest => "수정 시각이 없거나 같아 어느 쪽이 최신인지 몰라 새 id 사본으로 더함",
+    _ => "내용이 달라 새 id 사본으로 더함",
+  }
+}
+
+fn record_label(r: &RecordItem) -> String {
+  let s: String = r.summary.chars().take(30).collect();
+  if s.chars().count() < r.summary.chars().count() {
+    format!("{s}…")
+  } else {
+    s
+  }
+}
+
+/// 기존 id + 내용으로 만든 사본 id(같은 백업을 다시 가져오면 같은 id)
+fn copy_base_id(prefix: &str, id: &str, body: &str) -> String {
+  format!("{prefix}_{:016x}", fnv1a64(&["import", id, body]))
+}
+
+/// 그래도 겹치면 뒤에 번호
+fn unique_id(base: String, taken: &HashSet<String>) -> String {
+  let mut out = base.clone();
+  let mut n = 2;
+  while taken.contains(&out) {
+    out = format!("{base}_{n}");
+    n += 1;
+  }
+  out
+}
+
+/// 기록 합치기. 사본으로 합치며 바뀐 id는 (옛 id → 새 id)로 돌려준다.
+fn merge_records(
+  local: &[RecordItem],
+  incoming: Vec<RecordItem>,
+  mode: MergeMode,
+  conflicts: &mut Vec<Conflict>,
+) -> (Vec<RecordItem>, HashMap<String, String>, DiffCounts) {
+  let local_by_id: HashMap<&str, &RecordItem> = local.iter().map(|r| (r.id.as_str(), r)).collect();
+  let incoming_ids: HashSet<String> = incoming.iter().map(|r| r.id.clone()).collect();
+  let mut taken: HashSet<String> = local.iter().map(|r| r.id.clone()).chain(incoming_ids.iter().cloned()).collect();
+  let mut out: Vec<RecordItem> = match mode {
+    MergeMode::Replace => Vec::new(),
+    _ => local.to_vec(),
+  };
+  let mut pos: HashMap<String, usize> = out.iter().enumerate().map(|(i, r)| (r.id.clone(), i)).collect();
+  let mut renamed = HashMap::new();
+  let mut d = DiffCounts::default();
+
+  for mut r in incoming {
+    let Some(l) = local_by_id.get(r.id.as_str()).copied() else {
+      d.added += 1;
+      pos.insert(r.id.clone(), out.len());
+      out.push(r);
+      continue;
+    };
+    if same(l, &r) {
+      d.unchanged += 1;
+      if mode == MergeMode::Replace {
+        out.push(r);
+      }
+      continue;
+    }
+    let mut c = Conflict {
+      kind: ConflictKind::Record,
+      id: r.id.clone(),
+      local_label: record_label(l),
+      incoming_label: record_label(&r),
+      resolution: Resolution::TakeIncoming,
+      new_id: None,
+      reason: String::new(),
+    };
+    let newest = match mode {
+      MergeMode::MergeNewest => edit_times(&r.updated_at, &l.updated_at),
+      _ => None,
+    };
+    match (mode, newest) {
+      (MergeMode::Replace, _) => {
+        d.updated += 1;
+        c.reason = "전부 바꾸기: 백업 쪽으로".into();
+        out.push(r);
+      }
+      (MergeMode::MergeNewest, Some((a, b))) => {
+        if a > b {
+          d.updated += 1;
+          c.reason = format!("백업 쪽을 더 최근에 고침({} > {})", a.to_rfc3339(), b.to_rfc3339());
+          if let Some(&i) = pos.get(&r.id) {
+            out[i] = r;
+          }
+        } else {
+          d.kept_local += 1;
+          c.resolution = Resolution::KeepLocal;
+          c.reason = format!("이 기기 쪽을 더 최근에 고침({} > {})", b.to_rfc3339(), a.to_rfc3339());
+        }
+      }
+      // 최신 것 유지인데 수정 시각으로 가릴 수 없으면 덮어쓰지 않고 사본으로
+      _ => {
+        let body = serde_json::to_string(&r).unwrap_or_default();
+        let base = copy_base_id("REC", &r.id, &body);
+        let old_id = std::mem::replace(&mut r.id, base.clone());
+        // 같은 백업을 전에 사본으로 가져온 적 있으면 또 만들지 않는다
+        if local_by_id.get(base.as_str()).is_some_and(|x| same(*x, &r)) {
+          d.unchanged += 1;
+          renamed.insert(old_id, base);
+          continue;
+        }
+        let new_id = unique_id(base, &taken);
+        taken.insert(new_id.clone());
+        renamed.insert(old_id, new_id.clone());
+        d.copied += 1;
+        c.resolution = Resolution::Copied;
+        c.new_id = Some(new_id.clone());
+        c.reason = copy_reason(mode).into();
+        r.id = new_id;
+        out.push(r);
+      }
+    }
+    conflicts.push(c);
+  }
+
+  if mode == MergeMode::Replace {
+    d.removed = local.iter().filter(|r| !incoming_ids.contains(&r.id)).count();
+  }
+  (out, renamed, d)
+}
+
+/// 사본으로 바뀐 기록 id를 케이스 안 참조(연결, 권고 근거, 점수표)에도 반영
+fn remap_case(c: &mut StoredCase, renamed: &HashMap<String, String>) {
+  let map = |id: &mut String| {
+    if let Some(n) = renamed.get(id.as_str()) {
+      *id = n.clone();
+    }
+  };
+  c.case_item.record_ids.iter_mut().for_each(map);
+  for a in &mut c.case_item.advisors {
+    if let Some(ex) = a.explain.as_mut() {
+      ex.matched_record_ids.iter_mut().for_each(map);
+      if let Some(id) = ex.anchor_record_id.as_mut() {
+        map(id);
+      }
+    }
+  }
+  for key in ["scoreByRecordId", "componentsByRecordId"] {
+    if let Some(Value::Object(m)) = c.extra.get_mut(key) {
+      *m = std::mem::take(m)
+        .into_iter()
+        .map(|(k, v)| (renamed.get(&k).cloned().unwrap_or(k), v))
+        .collect();
+    }
+  }
+}
+
+fn merge_cases(
+  local: &BTreeMap<String, StoredCase>,
+  incoming: Vec<StoredCase>,
+  mode: MergeMode,
+  conflicts: &mut Vec<Conflict>,
+) -> (BTreeMap<String, StoredCase>, DiffCounts) {
+  let incoming_ids: HashSet<String> = incoming.iter().map(|c| c.case_item.id.clone()).collect();
+  let mut taken: HashSet<String> = local.keys().cloned().chain(incoming_ids.iter().cloned()).collect();
+  let mut out: BTreeMap<String, StoredCase> = match mode {
+    MergeMode::Replace => BTreeMap::new(),
+    _ => local.clone(),
+  };
+  let mut d = DiffCounts::default();
+
+  for mut c in incoming {
+    let id = c.case_item.id.clone();
+    let Some(l) = local.get(&id) else {
+      d.added += 1;
+      out.insert(id, c);
+      continue;
+    };
+    if same(l, &c) {
+      d.unchanged += 1;
+      if mode == MergeMode::Replace {
+        out.insert(id, c);
+      }
+      continue;
+    }
+    let mut cf = Conflict {
+      kind: ConflictKind::Case,
+      id: id.clone(),
+      local_label: l.case_item.title.clone(),
+      incoming_label: c.case_item.title.clone(),
+      resolution: Resolution::TakeIncoming,
+      new_id: None,
+      reason: String::new(),
+    };
+    let newest = match mode {
+      MergeMode::MergeNewest => edit_times(&c.updated_at, &l.updated_at),
+      _ => None,
+    };
+    match (mode, newest) {
+      (MergeMode::Replace, _) => {
+        d.updated += 1;
+        cf.reason = "전부 바꾸기: 백업 쪽으로".into();
+        out.insert(id, c);
+      }
+      (MergeMode::MergeNewest, Some((a, b))) => {
+        if a > b {
+          d.updated += 1;
+          cf.reason = format!("백업 쪽을 더 최근에 고침({} > {})", a.to_rfc3339(), b.to_rfc3339());
+          out.insert(id, c);
+        } else {
+          d.kept_local += 1;
+          cf.resolution = Resolution::KeepLocal;
+          cf.reason = format!("이 기기 쪽을 더 최근에 고침({} > {})", b.to_rfc3339(), a.to_rfc3339());
+        }
+      }
+      _ => {
+        let body = serde_json::to_string(&c).unwrap_or_default();
+        let base = copy_base_id("CASE", &id, &body);
+        c.case_item.id = base.clone();
+        c.case_item.title.push_str(COPY_TITLE_SUFFIX);
+        if local.get(&base).is_some_and(|x| same(x, &c)) {
+          d.unchanged += 1;
+          continue;
+        }
+        let new_id = unique_id(base, &taken);
+        taken.insert(new_id.clone());
+        d.copied += 1;
+        cf.resolution = Resolution::Copied;
+        cf.new_id = Some(new_id.clone());
+        cf.reason = copy_reason(mode).into();
+        c.case_item.id = new_id.clone();
+        out.insert(new_id, c);
+      }
+    }
+    conflicts.push(cf);
+  }
+
+  if mode == MergeMode::Replace {
+    d.removed = local.keys().filter(|id| !incoming_ids.contains(*id)).count();
+  }
+  (out, d)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::test_util::{case_json, record_json, student};
+  use serde_json::json;
+
+  fn rec(id: &str, ts: &str, summary: &str) -> Value {
+    record_json(id, summary, json!({"ts": ts}))
+  }
+
+  fn case(id: &str, record_ids: &[&str]) -> Value {
+    let scores: serde_json::Map<String, Value> = record_ids.iter().map(|r| (r.to_string(), json!(0.9))).collect();
+    case_json(id, json!({
+      "title": "사건", "actors": [student("김철수")], "query": "폭언", "status": "진행중",
+      "createdAt": "2025-03-01T00:00:00Z", "steps": [], "recordIds": record_ids, "scoreByRecordId": scores,
+    }))
+  }
+
+  fn edited(mut v: Value, at: &str) -> Value {
+    v["updatedAt"] = json!(at);
+    v
+  }
+
+  fn state(records: Vec<Value>, cases: Vec<Value>) -> Value {
+    let cases: serde_json::Map<String, Value> = cases.into_iter().map(|c| (c["id"].as_str().unwrap().to_string(), c)).collect();
+    json!({"v": 8, "tab": "records", "selectedCaseId": null, "records": records, "cases": cases})
+  }
+
+  fn upgraded(v: Value) -> AppState {
+    schema::upgrade(v).unwrap().state
+  }
+
+  fn local() -> AppState {
+    upgraded(state(
+      vec![
+        edited(rec("r1", "2025-03-01T09:00:00Z", "수업 중 폭언"), "2025-03-01T10:00:00Z"),
+        edited(rec("r2", "2025-03-02T09:00:00Z", "학부모 민원"), "2025-03-02T10:00:00Z"),
+      ],
+      vec![case("c1", &["r1"])],
+    ))
+  }
+
+  /// r1은 사건 시각을 앞당겨 고친 것(더 최근에 고침), r2는 예전에 고친 본, r3은 새 기록
+  fn incoming() -> String {
+    state(
+      vec![
+        edited(rec("r1", "2025-02-28T09:00:00Z", "수업 중 폭언(집에서 고침)"), "2025-03-05T10:00:00Z"),
+        edited(rec("r2", "2025-03-02T09:00:00Z", "학부모 민원(예전 본)"), "2025-02-01T10:00:00Z"),
+        rec("r3", "2025-03-06T09:00:00Z", "복도에서 다툼"),
+      ],
+      vec![case("c1", &["r1"])],
+    )
+    .to_string()
+  }
+
+  fn ids(s: &AppState) -> Vec<&str> {
+    let mut v: Vec<&str> = s.records.iter().map(|r| r.id.as_str()).collect();
+    v.sort();
+    v
+  }
+
+  #[test]
+  fn replace_takes_backup_as_is() {
+    let only_r3 = state(vec![rec("r3", "2025-03-06T09:00:00Z", "복도에서 다툼")], vec![]).to_string();
+    let p = plan(&only_r3, Some(local()), MergeMode::Replace).unwrap();
+    assert_eq!(ids(&p.state), vec!["r3"]);
+    assert!(p.state.cases.is_empty());
+    assert_eq!((p.records.added, p.records.removed), (1, 2));
+    assert_eq!(p.cases.removed, 1);
+
+    let p = plan(&incoming(), Some(local()), MergeMode::Replace).unwrap();
+    assert_eq!((p.records.updated, p.records.added, p.records.removed), (2, 1, 0));
+    assert!(p.conflicts.iter().all(|c| c.resolution == Resolution::TakeIncoming));
+  }
+
+  #[test]
+  fn merge_newest_keeps_the_later_side() {
+    let p = plan(&incoming(), Some(local()), MergeMode::MergeNewest).unwrap();
+    assert_eq!(ids(&p.state), vec!["r1", "r2", "r3"]);
+    let summary = |id: &str| p.state.records.iter().find(|r| r.id == id).unwrap().summary.clone();
+    assert_eq!(summary("r1"), "수업 중 폭언(집에서 고침)");
+    assert_eq!(summary("r2"), "학부모 민원");
+    assert_eq!((p.records.updated, p.records.kept_local, p.records.added), (1, 1, 1));
+    let r2 = p.conflicts.iter().find(|c| c.id == "r2").unwrap();
+    assert_eq!(r2.resolution, Resolution::KeepLocal);
+    // 케이스는 같아서 그대로
+    assert_eq!(p.cases.unchanged, 1);
+  }
+
+  #[test]
+  fn merge_newest_copies_when_edit_times_cannot_decide() {
+    let local = upgraded(state(
+      vec![
+        rec("r1", "2025-03-01T09:00:00Z", "수업 중 폭언"),
+        edited(rec("r2", "2025-03-02T09:00:00Z", "학부모 민원"), "2025-03-02T10:00:00Z"),
+      ],
+      vec![case("c1", &["r1"])],
+    ));
+    // r1은 양쪽 다 수정 시각이 없고, r2는 수정 시각이 같다(사건 시각만 더 늦음)
+    let incoming = state(
+      vec![
+        rec("r1", "2025-03-05T09:00:00Z", "수업 중 폭언(집에서 고침)"),
+        edited(rec("r2", "2025-03-09T09:00:00Z", "학부모 민원(다른 본)"), "2025-03-02T10:00:00Z"),
+      ],
+      vec![case("c1", &["r1"])],
+    )
+    .to_string();
+    let p = plan(&incoming, Some(local), MergeMode::MergeNewest).unwrap();
+    assert_eq!((p.records.copied, p.records.updated, p.records.kept_local), (2, 0, 0));
+    assert_eq!(p.state.records.len(), 4);
+    let r1 = p.conflicts.iter().find(|c| c.id == "r1").unwrap();
+    assert_eq!(r1.resolution, Resolution::Copied);
+    // 원래 기록은 그대로 남고, 백업 쪽 케이스는 사본 기록을 가리킨다
+    let summary = |id: &str| p.state.records.iter().find(|r| r.id == id).unwrap().summary.clone();
+    assert_eq!(summary("r1"), "수업 중 폭언");
+    let r1_copy = r1.new_id.clone().unwrap();
+    assert_eq!(summary(&r1_copy), "수업 중 폭언(집에서 고침)");
+    assert_eq!(p.state.cases["c1"].case_item.record_ids, vec!["r1"]);
+    assert_eq!(p.cases.copied, 1);
+  }
+
+  #[test]
+  fn merge_newest_compares_case_edit_times() {
+    let mut newer = case("c1", &["r1"]);
+    newer["title"] = json!("사건(제목 고침)");
+    let incoming = state(
+      vec![edited(rec("r1", "2025-03-01T09:00:00Z", "수업 중 폭언"), "2025-03-01T10:00:00Z")],
+      vec![edited(newer, "2025-03-07T10:00:00Z")],
+    )
+    .to_string();
+    let mut local = local();
+    local.cases.get_mut("c1").unwrap().updated_at = "2025-03-03T10:00:00Z".into();
+    let p = plan(&incoming, Some(local.clone()), MergeMode::MergeNewest).unwrap();
+    assert_eq!(p.cases.updated, 1);
+    assert_eq!(p.state.cases["c1"].case_item.title, "사건(제목 고침)");
+
+    // 이 기기 쪽을 더 최근에 고쳤으면 그대로
+    local.cases.get_mut("c1").unwrap().updated_at = "2025-03-08T10:00:00Z".into();
+    let p = plan(&incoming, Some(local), MergeMode::MergeNewest).unwrap();
+    assert_eq!(p.cases.kept_local, 1);
+    assert_eq!(p.state.cases["c1"].case_item.title, "사건");
+  }
+
+  #[test]
+  fn merge_copy_adds_copies_and_remaps_case_references() {
+    let p = plan(&incoming(), Some(local()), MergeMode::MergeCopy).unwrap();
+    assert_eq!((p.records.copied, p.records.added), (2, 1));
+    assert_eq!(p.state.records.len(), 5);
+    let r1_copy = p.conflicts.iter().find(|c| c.id == "r1").unwrap().new_id.clone().unwrap();
+    assert!(r1_copy.starts_with("REC_"));
+
+    // 원래 케이스는 그대로 r1, 백업 쪽 케이스는 사본 기록을 가리키는 사본이 된다
+    assert_eq!(p.state.cases["c1"].case_item.record_ids, vec!["r1"]);
+    assert_eq!(p.cases.copied, 1);
+    let copy = p.state.cases.values().find(|c| c.case_item.id != "c1").unwrap();
+    assert!(copy.case_item.title.ends_with(COPY_TITLE_SUFFIX));
+    assert_eq!(copy.case_item.record_ids, vec![r1_copy.clone()]);
+    let scores = copy.extra["scoreByRecordId"].as_object().unwrap();
+    assert!(scores.contains_key(&r1_copy) && !scores.contains_key("r1"));
+  }
+
+  #[test]
+  fn reimporting_the_same_backup_is_idempotent() {
+    for mode in [MergeMode::MergeNewest, MergeMode::MergeCopy] {
+      let first = plan(&incoming(), Some(local()), mode).unwrap();
+      let again = plan(&incoming(), Some(first.state.clone()), mode).unwrap();
+      assert_eq!((again.records.added, again.records.copied, again.records.updated), (0, 0, 0), "{mode:?}");
+      assert_eq!((again.cases.added, again.cases.copied, again.cases.updated), (0, 0, 0), "{mode:?}");
+      assert_eq!(ids(&again.state), ids(&first.state), "{mode:?}");
+      assert_eq!(again.state.cases.len(), first.state.cases.len(), "{mode:?}");
+    }
+  }
+
+  #[test]
+  fn changed_data_invalidates_the_plan_id() {
+    let preview = plan(&incoming(), Some(local()), MergeMode::MergeNewest).unwrap();
+    let same_again = plan(&incoming(), Some(local()), MergeMode::MergeNewest).unwrap();
+    assert!(check_confirm(&same_again, &preview.plan_id).is_ok());
+
+    // 미리보기 뒤 이 기기에서 기록을 하나 더 씀
+    let mut changed = local();
+    changed.records.push(upgraded(state(vec![rec("r9", "2025-03-07T09:00:00Z", "새 메모")], vec![])).records.remove(0));
+    let now = plan(&incoming(), Some(changed), MergeMode::MergeNewest).unwrap();
+    assert!(check_confirm(&now, &preview.plan_id).is_err());
+
+    // 같은 데이터라도 방식이 다르면 다른 계획
+    let other_mode = plan(&incoming(), Some(local()), MergeMode::MergeCopy).unwrap();
+    assert!(check_confirm(&other_mode, &preview.plan_id).is_err());
+  }
+
+  #[test]
+  fn rejects_files_that_are_not_backups() {
+    assert!(plan("[]", None, MergeMode::Replace).is_err());
+    assert!(plan(r#"{"foo": 1}"#, None, MergeMode::Replace).is_err());
+    assert!(plan(r#"{"records": {}}"#, None, MergeMode::Replace).is_err());
+    assert!(plan(r#"{"state": [], "v": 8}"#, None, MergeMode::Replace).is_err());
+  }
+}
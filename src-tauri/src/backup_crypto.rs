@@ -0,0 +1,281 @@
+// src-tauri/src/backup_crypto.rs
+// 암호화 백업 컨테이너: 비밀번호 → Argon2id 키(32바이트) → XChaCha20-Poly1305.
+// 머리(형식/버전/KDF 설정/nonce)는 평문 JSON이고 AEAD 연관 데이터로 묶여 있어 한 글자라도 바꾸면 풀리지 않는다.
+// contentHash(암호문 SHA-256)는 키를 만들기 전에 확인해 "파일 손상"과 "비밀번호 틀림"을 구별하는 데 쓴다.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as B64;
+use base64::Engine as _;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+/// 암호화 백업인지 알아보는 표식(프론트도 같은 값으로 확인)
+pub const FORMAT: &str = "roosycozy-encrypted-backup";
+pub const CONTAINER_VERSION: u32 = 1;
+
+const KDF_ALG: &str = "argon2id";
+const CIPHER_ALG: &str = "xchacha20poly1305";
+const MIN_PASSPHRASE_CHARS: usize = 8;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+
+/// 새로 만들 때 KDF 설정: 64 MiB, 3회, 1레인(보통 노트북에서 1초 안쪽)
+const KDF_M_KIB: u32 = 64 * 1024;
+const KDF_T: u32 = 3;
+const KDF_P: u32 = 1;
+
+/// 파일에 적힌 값이 이보다 크면 거부(조작된 파일로 메모리/시간을 다 쓰지 않게)
+const KDF_MAX_M_KIB: u32 = 1024 * 1024;
+const KDF_MAX_T: u32 = 16;
+const KDF_MAX_P: u32 = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KdfParams {
+  pub alg: String,
+  /// Argon2 버전(0x13 = 19)
+  pub version: u32,
+  pub m_kib: u32,
+  pub t: u32,
+  pub p: u32,
+  /// base64
+  pub salt: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CipherParams {
+  pub alg: String,
+  /// base64
+  pub nonce: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Container {
+  pub format: String,
+  pub version: u32,
+  pub kdf: KdfParams,
+  pub cipher: CipherParams,
+  /// "sha256:" + 암호문 SHA-256(hex)
+  pub content_hash: String,
+  /// base64
+  pub ciphertext: String,
+}
+
+/// AEAD 연관 데이터: contentHash/ciphertext를 뺀 머리 전체
+#[derive(Serialize)]
+struct Aad<'a> {
+  format: &'a str,
+  version: u32,
+  kdf: &'a KdfParams,
+  cipher: &'a CipherParams,
+}
+
+impl Container {
+  fn aad(&self) -> Result<Vec<u8>, String> {
+    serde_json::to_vec(&Aad {
+      format: &self.format,
+      version: self.version,
+      kdf: &self.kdf,
+      cipher: &self.cipher,
+    })
+    .map_err(|e| format!("encode failed: {e}"))
+  }
+}
+
+fn content_hash(ciphertext: &[u8]) -> String {
+  let digest = Sha256::digest(ciphertext);
+  let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+  format!("sha256:{hex}")
+}
+
+fn b64_decode(field: &str, s: &str) -> Result<Vec<u8>, String> {
+  B64.decode(s.trim()).map_err(|e| format!("암호화 백업이 잘못됐어요: {field} base64 오류 ({e})"))
+}
+
+fn derive_key(passphrase: &str, kdf: &KdfParams, salt: &[u8]) -> Result<Zeroizing<[u8; KEY_LEN]>, String> {
+  let params = Params::new(kdf.m_kib, kdf.t, kdf.p, Some(KEY_LEN)).map_err(|e| format!("KDF 설정 오류: {e}"))?;
+  let argon = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+  let mut key = Zeroizing::new([0u8; KEY_LEN]);
+  argon
+    .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+    .map_err(|e| format!("키 만들기 실패: {e}"))?;
+  Ok(key)
+}
+
+/// 백업 JSON 텍스트가 암호화 컨테이너인지(형식 표식만 본다)
+pub fn is_encrypted(text: &str) -> bool {
+  #[derive(Deserialize)]
+  struct Probe {
+    format: Option<String>,
+  }
+  serde_json::from_str::<Probe>(text).is_ok_and(|p| p.format.as_deref() == Some(FORMAT))
+}
+
+/// 평문 백업 JSON → 암호화 컨테이너 JSON
+pub fn encrypt(plaintext: &str, passphrase: &str) -> Result<String, String> {
+  encrypt_with(plaintext, passphrase, KDF_M_KIB, KDF_T)
+}
+
+/// KDF 비용을 정해 암호화(테스트는 작은 값으로 빠르게 돈다; 풀 때는 파일에 적힌 값을 쓴다)
+fn encrypt_with(plaintext: &str, passphrase: &str, m_kib: u32, t: u32) -> Result<String, String> {
+  if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+    return Err(format!("비밀번호는 {MIN_PASSPHRASE_CHARS}자 이상이어야 해요."));
+  }
+  let mut salt = [0u8; SALT_LEN];
+  let mut nonce = [0u8; NONCE_LEN];
+  OsRng.fill_bytes(&mut salt);
+  OsRng.fill_bytes(&mut nonce);
+
+  let mut c = Container {
+    format: FORMAT.into(),
+    version: CONTAINER_VERSION,
+    kdf: KdfParams {
+      alg: KDF_ALG.into(),
+      version: Version::V0x13 as u32,
+      m_kib,
+      t,
+      p: KDF_P,
+      salt: B64.encode(salt),
+    },
+    cipher: CipherParams {
+      alg: CIPHER_ALG.into(),
+      nonce: B64.encode(nonce),
+    },
+    content_hash: String::new(),
+    ciphertext: String::new(),
+  };
+
+  let key = derive_key(passphrase, &c.kdf, &salt)?;
+  let aead = XChaCha20Poly1305::new(key.as_ref().into());
+  let aad = c.aad()?;
+  let ct = aead
+    .encrypt(XNonce::from_slice(&nonce), Payload { msg: plaintext.as_bytes(), aad: &aad })
+    .map_err(|_| "암호화 실패".to_string())?;
+
+  c.content_hash = content_hash(&ct);
+  c.ciphertext = B64.encode(&ct);
+  serde_json::to_string_pretty(&c).map_err(|e| format!("encode failed: {e}"))
+}
+
+/// 암호화 컨테이너 JSON → 평문 백업 JSON
+pub fn decrypt(text: &str, passphrase: &str) -> Result<String, String> {
+  let c: Container = serde_json::from_str(text).map_err(|e| format!("암호화 백업을 읽을 수 없어요: {e}"))?;
+  if c.format != FORMAT {
+    return Err("암호화 백업이 아니에요".into());
+  }
+  if c.version > CONTAINER_VERSION {
+    return Err(format!(
+      "암호화 백업 v{}은 이 앱(v{CONTAINER_VERSION})보다 새 버전이에요 — 앱을 업데이트한 뒤 열어 주세요",
+      c.version
+    ));
+  }
+  if c.kdf.alg != KDF_ALG || c.kdf.version != Version::V0x13 as u32 || c.cipher.alg != CIPHER_ALG {
+    return Err(format!("지원하지 않는 암호 방식: {}/{}", c.kdf.alg, c.cipher.alg));
+  }
+  if c.kdf.m_kib > KDF_MAX_M_KIB || c.kdf.t > KDF_MAX_T || c.kdf.p > KDF_MAX_P {
+    return Err("암호화 백업의 KDF 설정이 너무 커요(손상되었거나 조작된 파일)".into());
+  }
+
+  let salt = b64_decode("kdf.salt", &c.kdf.salt)?;
+  let nonce = b64_decode("cipher.nonce", &c.cipher.nonce)?;
+  let ct = b64_decode("ciphertext", &c.ciphertext)?;
+  if nonce.len() != NONCE_LEN {
+    return Err("암호화 백업이 잘못됐어요: nonce 길이".into());
+  }
+  if content_hash(&ct) != c.content_hash.trim().to_ascii_lowercase() {
+    return Err("백업 파일이 손상됐어요(내용 해시 불일치)".into());
+  }
+
+  let key = derive_key(passphrase, &c.kdf, &salt)?;
+  let aead = XChaCha20Poly1305::new(key.as_ref().into());
+  let aad = c.aad()?;
+  let pt = Zeroizing::new(
+    aead
+      .decrypt(XNonce::from_slice(&nonce), Payload { msg: &ct, aad: &aad })
+      .map_err(|_| "비밀번호가 틀렸거나 파일이 바뀌었어요".to_string())?,
+  );
+  String::from_utf8(pt.to_vec()).map_err(|_| "풀어낸 내용이 UTF-8이 아니에요".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::Value;
+
+  const PASS: &str = "correct horse battery";
+  const PLAIN: &str = r#"{"v":8,"records":[{"id":"r1","summary":"수업 중 폭언"}],"cases":{}}"#;
+
+  /// 64 MiB Argon2는 디버그 빌드에서 느려 작은 비용으로 만든다
+  fn sealed() -> String {
+    encrypt_with(PLAIN, PASS, 64, 1).unwrap()
+  }
+
+  fn edit(text: &str, f: impl FnOnce(&mut Value)) -> String {
+    let mut v: Value = serde_json::from_str(text).unwrap();
+    f(&mut v);
+    v.to_string()
+  }
+
+  #[test]
+  fn round_trip() {
+    let text = sealed();
+    assert!(is_encrypted(&text));
+    assert!(!is_encrypted(PLAIN));
+    assert!(!text.contains("폭언"));
+    assert_eq!(decrypt(&text, PASS).unwrap(), PLAIN);
+  }
+
+  #[test]
+  fn default_params_round_trip() {
+    let text = encrypt(PLAIN, PASS).unwrap();
+    let c: Container = serde_json::from_str(&text).unwrap();
+    assert_eq!((c.kdf.m_kib, c.kdf.t, c.kdf.p), (KDF_M_KIB, KDF_T, KDF_P));
+    assert_eq!(decrypt(&text, PASS).unwrap(), PLAIN);
+  }
+
+  #[test]
+  fn short_passphrase_is_rejected() {
+    assert!(encrypt_with(PLAIN, "short", 64, 1).is_err());
+  }
+
+  #[test]
+  fn wrong_passphrase_fails() {
+    let e = decrypt(&sealed(), "wrong passphrase").unwrap_err();
+    assert!(e.contains("비밀번호가 틀렸거나"), "{e}");
+  }
+
+  #[test]
+  fn tampered_header_fails_authentication() {
+    // 머리는 AAD라 salt 외의 값(여기서는 반복 횟수)을 바꿔도 풀리지 않는다
+    let text = edit(&sealed(), |v| v["kdf"]["t"] = 2.into());
+    assert!(decrypt(&text, PASS).is_err());
+    let text = edit(&sealed(), |v| v["format"] = "something-else".into());
+    assert!(decrypt(&text, PASS).is_err());
+  }
+
+  #[test]
+  fn corrupted_ciphertext_is_reported_as_damage() {
+    let text = edit(&sealed(), |v| {
+      let mut ct = B64.decode(v["ciphertext"].as_str().unwrap()).unwrap();
+      ct[0] ^= 1;
+      v["ciphertext"] = B64.encode(ct).into();
+    });
+    let e = decrypt(&text, PASS).unwrap_err();
+    assert!(e.contains("손상"), "{e}");
+  }
+
+  #[test]
+  fn newer_container_and_oversized_kdf_are_refused() {
+    let text = edit(&sealed(), |v| v["version"] = (CONTAINER_VERSION + 1).into());
+    assert!(decrypt(&text, PASS).unwrap_err().contains("새 버전"));
+    let text = edit(&sealed(), |v| v["kdf"]["mKib"] = (KDF_MAX_M_KIB + 1).into());
+    assert!(decrypt(&text, PASS).unwrap_err().contains("너무 커요"));
+  }
+}
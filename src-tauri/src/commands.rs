@@ -1,25 +1,477 @@
 // src-tauri/src/commands.rs
+use crate::backup;
+use crate::backup_crypto;
+use crate::db;
 use crate::engine;
-use engine::{AdvisorItem, CaseItem, RankOpts, RankedHit, RecordItem};
+use crate::schema;
+use crate::storage;
+use engine::advisor::AdvisorRule;
+use engine::advisor_custom::{self, AdvisorOverlay};
+use engine::store::RecordStore;
+use engine::synonym::{self, LoadedSynonyms, SynonymTable};
+use engine::{AdvisorItem, CaseItem, RankOpts, RankedHit, RecordItem, StepItem};
 
 use serde::Deserialize;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tauri::Manager;
+use zeroize::Zeroizing;
 
 // genpdf의 .styled()/.padded()/.framed() 등을 쓰려면 Element 트레이트가 스코프에 있어야 함
 use genpdf::Element;
 
 #[tauri::command]
 pub fn engine_rank(
+  app: tauri::AppHandle,
+  synonym_state: tauri::State<'_, SynonymState>,
   records: Vec<RecordItem>,
   case_item: CaseItem,
   opts: Option<RankOpts>,
 ) -> Result<Vec<RankedHit>, String> {
-  Ok(engine::rank_records_for_case(&records, &case_item, opts))
+  let synonyms = current_synonyms(&app, &synonym_state)?;
+  let ctx = engine::RankContext {
+    synonyms: Some(&synonyms),
+    ..Default::default()
+  };
+  engine::rank_records_with(&records, &case_item, opts, ctx)
+}
+
+#[tauri::command]
+pub fn engine_advise(
+  app: tauri::AppHandle,
+  records: Vec<RecordItem>,
+  case_item: CaseItem,
+) -> Result<engine::advisor::AdviseResult, String> {
+  let dir = app.path().app_data_dir().ok();
+  let loaded = engine::advisor::load_pack(dir.as_deref())?;
+  Ok(engine::advisor::AdviseResult {
+    advisors: engine::generate_advisors_for_case(&case_item, &records, &loaded.pack),
+    warnings: loaded.warnings,
+  })
+}
+
+/// caseItem.advisors의 state(done/dismissed)를 유지하면서 재생성
+#[tauri::command]
+pub fn engine_regenerate_advisors(
+  app: tauri::AppHandle,
+  records: Vec<RecordItem>,
+  case_item: CaseItem,
+) -> Result<engine::advisor::AdviseResult, String> {
+  let dir = app.path().app_data_dir().ok();
+  let loaded = engine::advisor::load_pack(dir.as_deref())?;
+  Ok(engine::advisor::AdviseResult {
+    advisors: engine::regenerate_advisors_for_case(&case_item, &records, &loaded.pack),
+    warnings: loaded.warnings,
+  })
+}
+
+/* -------------------- record store (managed state) -------------------- */
+
+/// main.rs에서 `.manage(RecordStoreState::default())`로 등록
+#[derive(Default)]
+pub struct RecordStoreState(pub Mutex<RecordStore>);
+
+fn lock_store(state: &RecordStoreState) -> Result<MutexGuard<'_, RecordStore>, String> {
+  state.0.lock().map_err(|_| "record store lock poisoned".to_string())
+}
+
+/// 전체 교체. 저장된 기록 수를 돌려준다.
+#[tauri::command]
+pub fn record_store_sync(state: tauri::State<'_, RecordStoreState>, records: Vec<RecordItem>) -> Result<usize, String> {
+  let mut store = lock_store(&state)?;
+  store.replace_all(records);
+  Ok(store.len())
+}
+
+#[tauri::command]
+pub fn record_store_upsert(state: tauri::State<'_, RecordStoreState>, records: Vec<RecordItem>) -> Result<usize, String> {
+  let mut store = lock_store(&state)?;
+  store.upsert(records);
+  Ok(store.len())
+}
+
+/// 실제로 지운 개수를 돌려준다
+#[tauri::command]
+pub fn record_store_delete(state: tauri::State<'_, RecordStoreState>, ids: Vec<String>) -> Result<usize, String> {
+  Ok(lock_store(&state)?.delete(&ids))
+}
+
+/// engine_rank와 같은 결과를 저장된 기록/색인으로 계산(기록 배열을 넘기지 않음)
+#[tauri::command]
+pub fn engine_rank_indexed(
+  app: tauri::AppHandle,
+  state: tauri::State<'_, RecordStoreState>,
+  synonym_state: tauri::State<'_, SynonymState>,
+  case_item: CaseItem,
+  opts: Option<RankOpts>,
+) -> Result<Vec<RankedHit>, String> {
+  let synonyms = current_synonyms(&app, &synonym_state)?;
+  lock_store(&state)?.rank(&case_item, opts, Some(&synonyms))
+}
+
+/// 케이스 쿼리 검사(UI 하이라이트용). 오류의 start/end는 문자 위치.
+#[tauri::command]
+pub fn engine_parse_query(query: String) -> Result<engine::query::QueryNode, engine::query::QueryError> {
+  engine::query::parse_query(&query)
+}
+
+/// 당사자/관련자 동시 등장 네트워크(JSON + GraphViz DOT)
+#[tauri::command]
+pub fn engine_actor_network(
+  records: Vec<RecordItem>,
+  opts: Option<engine::network::NetworkOpts>,
+) -> Result<engine::network::ActorNetwork, String> {
+  engine::network::build_actor_network(&records, &opts.unwrap_or_default())
+}
+
+/// 기록 묶음에서 새 케이스 초안 제안(이미 있는 케이스가 대부분 가진 묶음은 뺀다)
+#[tauri::command]
+pub fn engine_suggest_cases(
+  records: Vec<RecordItem>,
+  cases: Option<Vec<CaseItem>>,
+  opts: Option<engine::suggest::SuggestOpts>,
+) -> Result<Vec<engine::suggest::CaseSuggestion>, String> {
+  engine::suggest::suggest_cases(&records, &cases.unwrap_or_default(), &opts.unwrap_or_default())
+}
+
+/* -------------------- synonyms / aliases (AppDataDir) -------------------- */
+
+/// main.rs에서 `.manage(SynonymState::default())`로 등록. 첫 랭킹 때 한 번 읽고, 저장/초기화 때 바꾼다.
+#[derive(Default)]
+pub struct SynonymState(pub Mutex<Option<LoadedSynonyms>>);
+
+/// AppDataDir을 못 찾거나 파일이 깨졌으면 기본 표로 랭킹한다(경고는 synonyms_get으로)
+fn loaded_synonyms(app: &tauri::AppHandle, state: &SynonymState) -> Result<LoadedSynonyms, String> {
+  let mut guard = state.0.lock().map_err(|_| "synonyms lock poisoned".to_string())?;
+  let loaded = guard.get_or_insert_with(|| match app_data_dir(app) {
+    Ok(dir) => synonym::load_synonyms_or_default(&dir),
+    Err(e) => LoadedSynonyms {
+      table: SynonymTable::default(),
+      warnings: vec![e],
+    },
+  });
+  Ok(loaded.clone())
+}
+
+fn current_synonyms(app: &tauri::AppHandle, state: &SynonymState) -> Result<SynonymTable, String> {
+  Ok(loaded_synonyms(app, state)?.table)
+}
+
+fn set_synonyms(state: &SynonymState, table: SynonymTable) -> Result<(), String> {
+  *state.0.lock().map_err(|_| "synonyms lock poisoned".to_string())? = Some(LoadedSynonyms {
+    table,
+    warnings: Vec::new(),
+  });
+  Ok(())
+}
+
+/// 지금 랭킹에 쓰는 표. 파일을 못 읽어 기본 표를 쓰는 중이면 warnings가 붙는다.
+#[tauri::command]
+pub fn synonyms_get(app: tauri::AppHandle, state: tauri::State<'_, SynonymState>) -> Result<LoadedSynonyms, String> {
+  loaded_synonyms(&app, &state)
+}
+
+/// 표 전체를 검증 후 저장(다음 랭킹부터 바로 쓴다)
+#[tauri::command]
+pub fn synonyms_save(
+  app: tauri::AppHandle,
+  state: tauri::State<'_, SynonymState>,
+  table: SynonymTable,
+) -> Result<SynonymTable, String> {
+  synonym::save_synonyms(&app_data_dir(&app)?, &table)?;
+  set_synonyms(&state, table.clone())?;
+  Ok(table)
+}
+
+/// 기본 표로 되돌림(파일 삭제)
+#[tauri::command]
+pub fn synonyms_reset(app: tauri::AppHandle, state: tauri::State<'_, SynonymState>) -> Result<SynonymTable, String> {
+  let path = app_data_dir(&app)?.join(synonym::SYNONYM_FILE);
+  if path.exists() {
+    std::fs::remove_file(&path).map_err(|e| format!("synonyms reset failed: {e}"))?;
+  }
+  set_synonyms(&state, SynonymTable::default())?;
+  Ok(SynonymTable::default())
+}
+
+/* -------------------- app state (AppDataDir/roosycozy.db) -------------------- */
+
+/// main.rs에서 `.manage(DbState::default())`로 등록. 첫 사용 때 연다.
+#[derive(Default)]
+pub struct DbState {
+  conn: Mutex<Option<rusqlite::Connection>>,
+  /// 열 때 손상돼 스냅샷에서 되살렸으면 그 내역(load_state가 한 번 알린다)
+  recovery: Mutex<Option<db::Recovery>>,
+}
+
+fn db_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+  Ok(app_data_dir(app)?.join(db::DB_FILE))
+}
+
+fn with_db<T>(
+  app: &tauri::AppHandle,
+  state: &DbState,
+  f: impl FnOnce(&mut rusqlite::Connection) -> Result<T, String>,
+) -> Result<T, String> {
+  let mut guard = state.conn.lock().map_err(|_| "db lock poisoned".to_string())?;
+  let conn = match &mut *guard {
+    Some(c) => c,
+    slot @ None => {
+      let (conn, recovery) = db::open_with_recovery(&db_path(app)?)?;
+      if recovery.is_some() {
+        *state.recovery.lock().map_err(|_| "db lock poisoned".to_string())? = recovery;
+      }
+      slot.insert(conn)
+    }
+  };
+  f(conn)
+}
+
+/// 스냅샷 실패는 저장 실패가 아니다(경고로 돌려준다)
+fn snapshot_db(conn: &rusqlite::Connection, path: &Path, min_interval: std::time::Duration) -> Option<String> {
+  db::snapshot(conn, path, min_interval).err()
+}
+
+/// DB가 비어 있으면(첫 실행) 예전 JSON 저장 파일(roosycozy_state_v1.json, 세대 복구 포함)을 v8로 올려 한 번 옮겨 온다
+#[tauri::command]
+pub fn load_state(app: tauri::AppHandle, db_state: tauri::State<'_, DbState>) -> Result<storage::LoadedState, String> {
+  let dir = app_data_dir(&app)?;
+  with_db(&app, &db_state, |conn| {
+    if db::has_state(conn)? {
+      let recovery = db_state.recovery.lock().map_err(|_| "db lock poisoned".to_string())?.take();
+      return Ok(storage::LoadedState {
+        state: db::load_app_state(conn)?,
+        source: recovery.as_ref().map_or_else(|| "sqlite".into(), |r| r.source.clone()),
+        recovered: recovery.is_some(),
+        errors: recovery.map(|r| r.errors).unwrap_or_default(),
+        migration: None,
+      });
+    }
+    let mut legacy = storage::load_state(&dir);
+    if let Some(v) = legacy.state.take() {
+      let up = schema::upgrade(v)?;
+      let report = db::save_app_state(conn, &up.state)?;
+      legacy
+        .errors
+        .extend(report.dropped_links.iter().map(|l| format!("dropped case link: {l}")));
+      legacy.state = db::load_app_state(conn)?;
+      legacy.migration = (!up.report.is_noop()).then_some(up.report);
+    }
+    Ok(legacy)
+  })
+}
+
+/// 전체 AppState를 받지만 바뀐 행만 쓴다(localStorage에서 옮겨 올 때, 비교할 지난 저장이 없을 때). 예전 버전 모양이면 v8로 올려서 저장.
+/// 저장 뒤 스냅샷(roosycozy.db.1~)을 SNAPSHOT_INTERVAL마다 한 세대씩 돌린다.
+#[tauri::command]
+pub fn save_state(
+  app: tauri::AppHandle,
+  db_state: tauri::State<'_, DbState>,
+  state: serde_json::Value,
+) -> Result<db::SyncReport, String> {
+  let up = schema::upgrade(state)?;
+  let path = db_path(&app)?;
+  with_db(&app, &db_state, |conn| {
+    let mut report = db::save_app_state(conn, &up.state)?;
+    report.warnings.extend(snapshot_db(conn, &path, db::SNAPSHOT_INTERVAL));
+    Ok(report)
+  })
+}
+
+/// 보통 저장: 바뀐 기록/케이스만 받아 쓴다(전체 AppState는 첫 저장/복구 때만 save_state로)
+#[tauri::command]
+pub fn save_changes(
+  app: tauri::AppHandle,
+  db_state: tauri::State<'_, DbState>,
+  changes: db::StateChanges,
+) -> Result<db::SyncReport, String> {
+  let path = db_path(&app)?;
+  with_db(&app, &db_state, |conn| {
+    let mut report = db::apply_changes(conn, &changes)?;
+    report.warnings.extend(snapshot_db(conn, &path, db::SNAPSHOT_INTERVAL));
+    Ok(report)
+  })
+}
+
+/// 지우기 전에 스냅샷을 한 세대 남긴다. 스냅샷을 못 떴으면 경고로 돌려준다.
+#[tauri::command]
+pub fn clear_state(app: tauri::AppHandle, db_state: tauri::State<'_, DbState>) -> Result<Vec<String>, String> {
+  let path = db_path(&app)?;
+  with_db(&app, &db_state, |conn| {
+    let warnings: Vec<String> = snapshot_db(conn, &path, std::time::Duration::ZERO).into_iter().collect();
+    db::clear_app_state(conn)?;
+    Ok(warnings)
+  })
+}
+
+/* -------------------- db CRUD -------------------- */
+
+#[tauri::command]
+pub fn db_records_list(app: tauri::AppHandle, db_state: tauri::State<'_, DbState>) -> Result<Vec<RecordItem>, String> {
+  with_db(&app, &db_state, |conn| db::list_records(conn))
+}
+
+#[tauri::command]
+pub fn db_record_get(app: tauri::AppHandle, db_state: tauri::State<'_, DbState>, id: String) -> Result<Option<RecordItem>, String> {
+  with_db(&app, &db_state, |conn| db::get_record(conn, &id))
+}
+
+#[tauri::command]
+pub fn db_record_upsert(app: tauri::AppHandle, db_state: tauri::State<'_, DbState>, records: Vec<RecordItem>) -> Result<usize, String> {
+  with_db(&app, &db_state, |conn| db::upsert_records(conn, &records))
+}
+
+/// 케이스에 포함된 기록이 하나라도 있으면 아무것도 지우지 않고 오류
+#[tauri::command]
+pub fn db_record_delete(app: tauri::AppHandle, db_state: tauri::State<'_, DbState>, ids: Vec<String>) -> Result<usize, String> {
+  with_db(&app, &db_state, |conn| db::delete_records(conn, &ids))
+}
+
+#[tauri::command]
+pub fn db_cases_list(app: tauri::AppHandle, db_state: tauri::State<'_, DbState>) -> Result<Vec<schema::StoredCase>, String> {
+  with_db(&app, &db_state, |conn| db::list_cases(conn))
 }
 
 #[tauri::command]
-pub fn engine_advise(records: Vec<RecordItem>, case_item: CaseItem) -> Result<Vec<AdvisorItem>, String> {
-  Ok(engine::generate_advisors_for_case(&case_item, &records))
+pub fn db_case_get(app: tauri::AppHandle, db_state: tauri::State<'_, DbState>, id: String) -> Result<Option<schema::StoredCase>, String> {
+  with_db(&app, &db_state, |conn| db::get_case(conn, &id))
+}
+
+#[tauri::command]
+pub fn db_case_upsert(app: tauri::AppHandle, db_state: tauri::State<'_, DbState>, case_item: schema::StoredCase) -> Result<(), String> {
+  with_db(&app, &db_state, |conn| db::upsert_case(conn, &case_item))
+}
+
+#[tauri::command]
+pub fn db_case_delete(app: tauri::AppHandle, db_state: tauri::State<'_, DbState>, id: String) -> Result<bool, String> {
+  with_db(&app, &db_state, |conn| db::delete_case(conn, &id))
+}
+
+#[tauri::command]
+pub fn db_case_add_records(
+  app: tauri::AppHandle,
+  db_state: tauri::State<'_, DbState>,
+  case_id: String,
+  record_ids: Vec<String>,
+) -> Result<usize, String> {
+  with_db(&app, &db_state, |conn| db::add_case_records(conn, &case_id, &record_ids))
+}
+
+#[tauri::command]
+pub fn db_case_remove_records(
+  app: tauri::AppHandle,
+  db_state: tauri::State<'_, DbState>,
+  case_id: String,
+  record_ids: Vec<String>,
+) -> Result<usize, String> {
+  with_db(&app, &db_state, |conn| db::remove_case_records(conn, &case_id, &record_ids))
+}
+
+#[tauri::command]
+pub fn db_step_upsert(app: tauri::AppHandle, db_state: tauri::State<'_, DbState>, case_id: String, step: StepItem) -> Result<(), String> {
+  with_db(&app, &db_state, |conn| db::upsert_step(conn, &case_id, &step))
+}
+
+#[tauri::command]
+pub fn db_step_delete(app: tauri::AppHandle, db_state: tauri::State<'_, DbState>, case_id: String, step_id: String) -> Result<bool, String> {
+  with_db(&app, &db_state, |conn| db::delete_step(conn, &case_id, &step_id))
+}
+
+#[tauri::command]
+pub fn db_advisors_set(
+  app: tauri::AppHandle,
+  db_state: tauri::State<'_, DbState>,
+  case_id: String,
+  advisors: Vec<AdvisorItem>,
+) -> Result<(), String> {
+  with_db(&app, &db_state, |conn| db::set_advisors(conn, &case_id, &advisors))
+}
+
+/// 요약 전문 검색(FTS5). 말마다 앞부분 일치, 모두 들어간 기록만.
+#[tauri::command]
+pub fn db_search(app: tauri::AppHandle, db_state: tauri::State<'_, DbState>, query: String, limit: Option<usize>) -> Result<Vec<db::FtsHit>, String> {
+  with_db(&app, &db_state, |conn| db::search(conn, &query, limit.unwrap_or(50)))
+}
+
+/* -------------------- schema (v1~v7 → v8) -------------------- */
+
+/// 예전 백업/저장 파일(JSON 텍스트)을 v8로 올려 돌려준다. 저장은 하지 않는다.
+#[tauri::command]
+pub fn schema_upgrade(json: String) -> Result<schema::Upgraded, String> {
+  let v: serde_json::Value = serde_json::from_str(&json).map_err(|e| format!("json parse failed: {e}"))?;
+  schema::upgrade(v)
+}
+
+/* -------------------- advisor pack lint -------------------- */
+
+/// json을 주면 그 텍스트를, 없으면 현재 쓰이는 규칙팩(AppDataDir 파일 → 내장 팩)을 검사
+#[tauri::command]
+pub fn advisor_lint(app: tauri::AppHandle, json: Option<String>) -> Result<engine::advisor_lint::LintReport, String> {
+  let text = match json {
+    Some(t) => t,
+    None => {
+      let path = app
+        .path()
+        .app_data_dir()
+        .ok()
+        .map(|d| d.join(engine::advisor::ADVISOR_PACK_FILE))
+        .filter(|p| p.exists());
+      match path {
+        Some(p) => std::fs::read_to_string(&p).map_err(|e| format!("advisor pack read failed: {e}"))?,
+        None => engine::advisor::BUILTIN_PACK_JSON.to_string(),
+      }
+    }
+  };
+  Ok(engine::advisor_lint::lint_pack_json(&text))
+}
+
+/* -------------------- advisor custom rules (AppDataDir overlay) -------------------- */
+
+fn app_data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+  app
+    .path()
+    .app_data_dir()
+    .map_err(|e| format!("cannot resolve app data dir: {e}"))
+}
+
+fn edit_overlay(
+  app: &tauri::AppHandle,
+  f: impl FnOnce(&mut AdvisorOverlay) -> Result<(), String>,
+) -> Result<AdvisorOverlay, String> {
+  let dir = app_data_dir(app)?;
+  let mut overlay = advisor_custom::load_overlay(&dir)?;
+  f(&mut overlay)?;
+  advisor_custom::save_overlay(&dir, &overlay)?;
+  Ok(overlay)
+}
+
+#[tauri::command]
+pub fn advisor_custom_list(app: tauri::AppHandle) -> Result<AdvisorOverlay, String> {
+  advisor_custom::load_overlay(&app_data_dir(&app)?)
+}
+
+#[tauri::command]
+pub fn advisor_custom_add(app: tauri::AppHandle, rule: AdvisorRule) -> Result<AdvisorOverlay, String> {
+  edit_overlay(&app, |o| advisor_custom::add_rule(o, rule))
+}
+
+#[tauri::command]
+pub fn advisor_custom_update(app: tauri::AppHandle, rule: AdvisorRule) -> Result<AdvisorOverlay, String> {
+  edit_overlay(&app, |o| advisor_custom::update_rule(o, rule))
+}
+
+#[tauri::command]
+pub fn advisor_custom_delete(app: tauri::AppHandle, rule_id: String) -> Result<AdvisorOverlay, String> {
+  edit_overlay(&app, |o| advisor_custom::delete_rule(o, &rule_id))
+}
+
+/// 기본 팩 규칙도 id로 끌 수 있음(disabled=false면 다시 켬)
+#[tauri::command]
+pub fn advisor_custom_disable(app: tauri::AppHandle, rule_id: String, disabled: bool) -> Result<AdvisorOverlay, String> {
+  edit_overlay(&app, |o| {
+    advisor_custom::set_disabled(o, &rule_id, disabled);
+    Ok(())
+  })
 }
 
 /* -------------------- PDF export (case paper) -------------------- */
@@ -602,6 +1054,9 @@ pub struct ExportBackupArgs {
   #[serde(default, alias = "fileName", alias = "filePath", alias = "path", alias = "savePath", alias = "outputPath")]
   pub file_name: Option<String>,
   pub json: String,
+  /// 있으면 암호화 컨테이너로 저장(Argon2id + XChaCha20-Poly1305)
+  #[serde(default)]
+  pub passphrase: Option<String>,
 }
 
 #[tauri::command]
@@ -616,8 +1071,74 @@ pub fn export_backup_json(args: ExportBackupArgs) -> Result<String, String> {
   let out_path = ensure_json_ext(PathBuf::from(file_name));
   ensure_parent_dir(&out_path)?;
 
-  std::fs::write(&out_path, args.json)
+  // 비밀번호 칸을 비운 채 넘어온 Some("")은 평문으로 내려가지 않고 encrypt의 길이 검사에서 막힌다
+  let passphrase = args.passphrase.map(Zeroizing::new);
+  let json = Zeroizing::new(args.json);
+  let body = match passphrase.as_deref() {
+    Some(p) => Zeroizing::new(backup_crypto::encrypt(&json, p)?),
+    None => json,
+  };
+
+  std::fs::write(&out_path, body.as_bytes())
     .map_err(|e| format!("backup write failed: {e}"))?;
 
   Ok(out_path.to_string_lossy().to_string())
 }
+
+/* -------------------- Backup import (JSON) -------------------- */
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportBackupArgs {
+  /// openDialog로 받은 전체 경로(json을 직접 주면 생략)
+  #[serde(default, alias = "filePath", alias = "path")]
+  pub file_name: Option<String>,
+  /// 파일 내용(웹 파일 선택으로 이미 읽은 경우)
+  #[serde(default)]
+  pub json: Option<String>,
+  pub mode: backup::MergeMode,
+  /// 미리보기에서 받은 planId. 없으면 미리보기만 한다.
+  #[serde(default)]
+  pub confirm: Option<String>,
+  /// 암호화 백업이면 필요
+  #[serde(default)]
+  pub passphrase: Option<String>,
+}
+
+/// 먼저 confirm 없이 불러 미리보기(충돌/바뀌는 수)를 보여 주고, 확인하면 받은 planId를 confirm에 넣어 다시 부른다.
+/// 그사이 DB나 파일이 바뀌었으면 planId가 달라져 저장하지 않는다.
+#[tauri::command]
+pub fn import_backup_json(
+  app: tauri::AppHandle,
+  db_state: tauri::State<'_, DbState>,
+  args: ImportBackupArgs,
+) -> Result<backup::ImportResult, String> {
+  let json = match (args.json, args.file_name.as_deref().map(str::trim).filter(|s| !s.is_empty())) {
+    (Some(j), _) => j,
+    (None, Some(p)) => std::fs::read_to_string(p).map_err(|e| format!("backup read failed: {e}"))?,
+    (None, None) => return Err("json 또는 fileName(전체 경로)이 필요해요.".into()),
+  };
+  let passphrase = args.passphrase.map(Zeroizing::new);
+  let json = if backup_crypto::is_encrypted(&json) {
+    let p = passphrase
+      .as_deref()
+      .filter(|p| !p.is_empty())
+      .ok_or("암호화된 백업이에요. 비밀번호를 입력해 주세요.")?;
+    Zeroizing::new(backup_crypto::decrypt(&json, p)?)
+  } else {
+    Zeroizing::new(json)
+  };
+  with_db(&app, &db_state, |conn| {
+    let current = db::load_app_state(conn)?
+      .map(schema::upgrade)
+      .transpose()?
+      .map(|u| u.state);
+    let plan = backup::plan(&json, current, args.mode)?;
+    let Some(confirm) = args.confirm else {
+      return Ok(backup::ImportResult { plan, applied: false, sync: None });
+    };
+    backup::check_confirm(&plan, &confirm)?;
+    let sync = db::save_app_state(conn, &plan.state)?;
+    Ok(backup::ImportResult { plan, applied: true, sync: Some(sync) })
+  })
+}
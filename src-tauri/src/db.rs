@@ -0,0 +1,1411 @@
+// src-tauri/src/db.rs
+// AppDataDir/roosycozy.db (SQLite): 기록/케이스/단계/권고를 행 단위로 저장.
+// 저장할 때 바뀐 행만 쓰고, "케이스에 포함된 기록은 삭제 불가"는 외래 키(ON DELETE RESTRICT)로 지킨다.
+
+use crate::engine::advisor::fnv1a64;
+use crate::engine::fsutil::sync_dir;
+use crate::engine::{ActorRef, AdvisorItem, CaseItem, RecordItem, StepItem};
+use crate::schema::{AppState, StoredCase, CURRENT_VERSION};
+
+use rusqlite::{params, Connection, ErrorCode, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DB_FILE: &str = "roosycozy.db";
+
+/// PRAGMA user_version. 스키마를 바꾸면 올리고 migrate()에 단계를 더한다.
+/// v2: records에 rid(INTEGER PRIMARY KEY)를 두고 records_fts가 그것을 가리킨다(VACUUM 뒤에도 rowid가 그대로)
+/// v3: records/cases에 updated_at(마지막으로 고친 시각)
+const DB_SCHEMA_VERSION: i32 = 3;
+
+/// 스냅샷(roosycozy.db.1 이 가장 최근)을 몇 세대까지 남길지
+pub const DB_SNAPSHOTS: usize = 5;
+
+/// 저장할 때마다 스냅샷을 뜨면 무거우니 가장 최근 것이 이보다 오래됐을 때만 뜬다
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// 손상된 DB는 지우지 않고 이 이름으로 옮겨 둔다(덮어씀)
+const CORRUPT_SUFFIX: &str = "corrupt";
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS meta (
+  key   TEXT PRIMARY KEY,
+  value TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS records (
+  rid         INTEGER PRIMARY KEY,
+  id          TEXT NOT NULL UNIQUE,
+  ts          TEXT NOT NULL,
+  store_type  TEXT NOT NULL,
+  store_other TEXT NOT NULL DEFAULT '',
+  lv          TEXT NOT NULL,
+  actor_type  TEXT NOT NULL,
+  actor_name  TEXT NOT NULL,
+  place       TEXT NOT NULL,
+  place_other TEXT NOT NULL DEFAULT '',
+  summary     TEXT NOT NULL,
+  updated_at  TEXT NOT NULL DEFAULT '',
+  hash        TEXT NOT NULL DEFAULT ''
+);
+CREATE INDEX IF NOT EXISTS records_ts ON records(ts);
+
+CREATE TABLE IF NOT EXISTS record_related (
+  record_id  TEXT NOT NULL REFERENCES records(id) ON DELETE CASCADE,
+  pos        INTEGER NOT NULL,
+  actor_type TEXT NOT NULL,
+  actor_name TEXT NOT NULL,
+  PRIMARY KEY (record_id, pos)
+);
+CREATE INDEX IF NOT EXISTS record_related_name ON record_related(actor_name);
+
+CREATE TABLE IF NOT EXISTS cases (
+  id              TEXT PRIMARY KEY,
+  title           TEXT NOT NULL,
+  query           TEXT NOT NULL DEFAULT '',
+  time_from       TEXT NOT NULL DEFAULT '',
+  time_to         TEXT NOT NULL DEFAULT '',
+  max_results     INTEGER,
+  only_main_actor INTEGER NOT NULL DEFAULT 0,
+  sens_filter     TEXT NOT NULL DEFAULT 'any',
+  status          TEXT NOT NULL DEFAULT '',
+  mode            TEXT,
+  created_at      TEXT NOT NULL DEFAULT '',
+  updated_at      TEXT NOT NULL DEFAULT '',
+  extra           TEXT NOT NULL DEFAULT '{}',
+  hash            TEXT NOT NULL DEFAULT ''
+);
+
+CREATE TABLE IF NOT EXISTS case_actors (
+  case_id    TEXT NOT NULL REFERENCES cases(id) ON DELETE CASCADE,
+  pos        INTEGER NOT NULL,
+  actor_type TEXT NOT NULL,
+  actor_name TEXT NOT NULL,
+  PRIMARY KEY (case_id, pos)
+);
+
+-- 기록 삭제 정책: 케이스에 포함된 기록은 지울 수 없다(케이스를 지우면 연결만 사라진다)
+CREATE TABLE IF NOT EXISTS case_records (
+  case_id   TEXT NOT NULL REFERENCES cases(id) ON DELETE CASCADE,
+  record_id TEXT NOT NULL REFERENCES records(id) ON DELETE RESTRICT,
+  pos       INTEGER NOT NULL,
+  PRIMARY KEY (case_id, record_id)
+);
+CREATE INDEX IF NOT EXISTS case_records_record ON case_records(record_id);
+
+CREATE TABLE IF NOT EXISTS steps (
+  case_id TEXT NOT NULL REFERENCES cases(id) ON DELETE CASCADE,
+  id      TEXT NOT NULL,
+  pos     INTEGER NOT NULL,
+  ts      TEXT NOT NULL,
+  name    TEXT NOT NULL,
+  note    TEXT NOT NULL DEFAULT '',
+  text    TEXT,
+  place   TEXT,
+  owner   TEXT,
+  lv      TEXT,
+  PRIMARY KEY (case_id, id)
+);
+
+CREATE TABLE IF NOT EXISTS advisors (
+  case_id TEXT NOT NULL REFERENCES cases(id) ON DELETE CASCADE,
+  id      TEXT NOT NULL,
+  pos     INTEGER NOT NULL,
+  ts      TEXT NOT NULL,
+  title   TEXT NOT NULL,
+  body    TEXT NOT NULL,
+  level   TEXT NOT NULL,
+  tags    TEXT NOT NULL DEFAULT '[]',
+  state   TEXT NOT NULL,
+  rule_id TEXT,
+  stale   INTEGER NOT NULL DEFAULT 0,
+  reasons TEXT NOT NULL DEFAULT '[]',
+  explain TEXT,
+  PRIMARY KEY (case_id, id)
+);
+
+"#;
+
+/// 요약 전문 검색(외부 콘텐츠 테이블: 본문은 records에만 있다).
+/// 암묵적 rowid는 VACUUM 때 바뀔 수 있어 명시한 rid를 가리킨다.
+const RECORDS_FTS: &str = r#"
+CREATE VIRTUAL TABLE IF NOT EXISTS records_fts USING fts5(
+  summary,
+  content = 'records',
+  content_rowid = 'rid',
+  tokenize = 'unicode61'
+);
+CREATE TRIGGER IF NOT EXISTS records_fts_ai AFTER INSERT ON records BEGIN
+  INSERT INTO records_fts(rowid, summary) VALUES (new.rid, new.summary);
+END;
+CREATE TRIGGER IF NOT EXISTS records_fts_ad AFTER DELETE ON records BEGIN
+  INSERT INTO records_fts(records_fts, rowid, summary) VALUES ('delete', old.rid, old.summary);
+END;
+CREATE TRIGGER IF NOT EXISTS records_fts_au AFTER UPDATE OF summary ON records BEGIN
+  INSERT INTO records_fts(records_fts, rowid, summary) VALUES ('delete', old.rid, old.summary);
+  INSERT INTO records_fts(rowid, summary) VALUES (new.rid, new.summary);
+END;
+"#;
+
+/// v1 → v2: records를 rid가 있는 표로 옮겨 담고 records_fts를 다시 만든다(외래 키는 끈 채로)
+const MIGRATE_V2: &str = r#"
+CREATE TABLE records_v2 (
+  rid         INTEGER PRIMARY KEY,
+  id          TEXT NOT NULL UNIQUE,
+  ts          TEXT NOT NULL,
+  store_type  TEXT NOT NULL,
+  store_other TEXT NOT NULL DEFAULT '',
+  lv          TEXT NOT NULL,
+  actor_type  TEXT NOT NULL,
+  actor_name  TEXT NOT NULL,
+  place       TEXT NOT NULL,
+  place_other TEXT NOT NULL DEFAULT '',
+  summary     TEXT NOT NULL,
+  hash        TEXT NOT NULL DEFAULT ''
+);
+INSERT INTO records_v2 (id, ts, store_type, store_other, lv, actor_type, actor_name, place, place_other, summary, hash)
+  SELECT id, ts, store_type, store_other, lv, actor_type, actor_name, place, place_other, summary, hash
+  FROM records ORDER BY rowid;
+DROP TABLE records_fts;
+DROP TABLE records;
+ALTER TABLE records_v2 RENAME TO records;
+CREATE INDEX records_ts ON records(ts);
+"#;
+
+/// v2 → v3: 수정 시각 칸(예전 행은 빈 문자열 — 백업 합치기에서 "모름"으로 다룬다)
+const MIGRATE_V3: &str = r#"
+ALTER TABLE records ADD COLUMN updated_at TEXT NOT NULL DEFAULT '';
+ALTER TABLE cases ADD COLUMN updated_at TEXT NOT NULL DEFAULT '';
+"#;
+
+/// 화면 상태(tab, selectedCaseId, v 등 records/cases 밖의 키)를 담는 meta 키.
+/// 이 행이 없으면 아직 한 번도 저장하지 않은 DB다.
+const META_APP: &str = "app";
+
+/* -------------------- types -------------------- */
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FtsHit {
+  pub record_id: String,
+  /// 일치한 부분을 [ ]로 감싼 발췌
+  pub snippet: String,
+  /// 클수록 잘 맞음(-bm25)
+  pub score: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+  pub records_written: usize,
+  pub records_deleted: usize,
+  pub cases_written: usize,
+  pub cases_deleted: usize,
+  /// 없는 기록을 가리켜 버린 케이스 연결("caseId → recordId")
+  pub dropped_links: Vec<String>,
+  /// 저장은 됐지만 알릴 것(스냅샷 실패 등)
+  pub warnings: Vec<String>,
+}
+
+/// 보통 저장: 지난 저장 뒤 바뀐 기록/케이스와 사라진 id만(프론트가 지난 저장과 비교해 만든다)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateChanges {
+  #[serde(default)]
+  pub records: Vec<RecordItem>,
+  #[serde(default)]
+  pub deleted_record_ids: Vec<String>,
+  /// 케이스 하나를 통째로(당사자/연결/단계/권고까지 다시 쓴다)
+  #[serde(default)]
+  pub cases: Vec<StoredCase>,
+  #[serde(default)]
+  pub deleted_case_ids: Vec<String>,
+  pub tab: String,
+  #[serde(default)]
+  pub selected_case_id: Option<String>,
+}
+
+/* -------------------- open / schema -------------------- */
+
+fn configure(conn: &Connection) -> Result<(), String> {
+  conn
+    .execute_batch("PRAGMA foreign_keys = ON; PRAGMA synchronous = NORMAL;")
+    .map_err(|e| format!("db setup failed: {e}"))
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), String> {
+  let version: i32 = conn
+    .query_row("PRAGMA user_version", [], |r| r.get(0))
+    .map_err(|e| format!("db version read failed: {e}"))?;
+  if version > DB_SCHEMA_VERSION {
+    return Err(format!("db 스키마 v{version}은 이 앱(v{DB_SCHEMA_VERSION})보다 새 버전이에요 — 앱을 업데이트해 주세요"));
+  }
+  if version == DB_SCHEMA_VERSION {
+    return Ok(());
+  }
+  let fail = |e: rusqlite::Error| format!("db migrate failed: {e}");
+
+  // 표를 갈아 끼우는 동안 외래 키 검사를 끈다(트랜잭션 안에서는 바꿀 수 없다)
+  conn.execute_batch("PRAGMA foreign_keys = OFF").map_err(fail)?;
+  let tx = conn.transaction().map_err(fail)?;
+  if version < 1 {
+    tx.execute_batch(SCHEMA).map_err(fail)?;
+  } else {
+    if version < 2 {
+      tx.execute_batch(MIGRATE_V2).map_err(fail)?;
+    }
+    tx.execute_batch(MIGRATE_V3).map_err(fail)?;
+  }
+  tx.execute_batch(RECORDS_FTS).map_err(fail)?;
+  if version >= 1 {
+    tx.execute("INSERT INTO records_fts(records_fts) VALUES ('rebuild')", []).map_err(fail)?;
+    let broken: i64 = tx
+      .query_row("SELECT count(*) FROM pragma_foreign_key_check", [], |r| r.get(0))
+      .map_err(fail)?;
+    if broken > 0 {
+      return Err(format!("db migrate failed: 외래 키가 어긋난 행 {broken}개"));
+    }
+  }
+  tx.execute_batch(&format!("PRAGMA user_version = {DB_SCHEMA_VERSION}"))
+    .map_err(fail)?;
+  tx.commit().map_err(fail)?;
+  configure(conn)
+}
+
+/// 손상 여부를 가릴 수 있게 여는 단계의 오류를 나눈다
+enum OpenFail {
+  /// 파일이 망가짐(quick_check 실패, "not a database" 등) → 스냅샷으로 되살린다
+  Corrupt(String),
+  /// 그 밖의 오류(권한, 더 새 스키마 등) → 그대로 알린다
+  Other(String),
+}
+
+impl OpenFail {
+  fn sqlite(ctx: &str, e: rusqlite::Error) -> Self {
+    let corrupt = matches!(
+      e.sqlite_error_code(),
+      Some(ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase)
+    );
+    let msg = format!("{ctx}: {e}");
+    if corrupt {
+      OpenFail::Corrupt(msg)
+    } else {
+      OpenFail::Other(msg)
+    }
+  }
+
+  fn message(self) -> String {
+    match self {
+      OpenFail::Corrupt(m) | OpenFail::Other(m) => m,
+    }
+  }
+}
+
+fn open_checked(path: &Path) -> Result<Connection, OpenFail> {
+  let mut conn = Connection::open(path).map_err(|e| OpenFail::sqlite(&format!("db open failed ({})", path.display()), e))?;
+  // WAL: 쓰는 동안 꺼져도 마지막 커밋까지는 남는다
+  conn
+    .query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))
+    .map_err(|e| OpenFail::sqlite("db setup failed", e))?;
+  let check: String = conn
+    .query_row("PRAGMA quick_check", [], |r| r.get(0))
+    .map_err(|e| OpenFail::sqlite("db check failed", e))?;
+  if check != "ok" {
+    return Err(OpenFail::Corrupt(format!("quick_check: {check}")));
+  }
+  configure(&conn).map_err(OpenFail::Other)?;
+  migrate(&mut conn).map_err(OpenFail::Other)?;
+  Ok(conn)
+}
+
+/// 열 때 손상돼 스냅샷에서 되살린 내역
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recovery {
+  /// "snapshot:N"
+  pub source: String,
+  /// 건너뛴 파일과 이유
+  pub errors: Vec<String>,
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+  let mut s = path.as_os_str().to_owned();
+  s.push(suffix);
+  PathBuf::from(s)
+}
+
+fn snapshot_path(path: &Path, n: usize) -> PathBuf {
+  sibling(path, &format!(".{n}"))
+}
+
+/// 본 DB와 WAL을 함께 옆으로 옮긴다(shm은 다시 만들어지므로 지운다)
+fn set_aside(path: &Path, suffix: &str) {
+  for ext in ["", "-wal"] {
+    let from = sibling(path, ext);
+    if from.exists() {
+      let _ = std::fs::rename(&from, sibling(path, &format!(".{suffix}{ext}")));
+    }
+  }
+  let _ = std::fs::remove_file(sibling(path, "-shm"));
+}
+
+pub fn open(path: &Path) -> Result<Connection, String> {
+  open_with_recovery(path).map(|(conn, _)| conn)
+}
+
+/// 열고 PRAGMA quick_check로 확인. 손상됐으면 옆으로 옮기고 가장 최근의 멀쩡한 스냅샷으로 되살린다.
+/// 스냅샷이 하나도 없으면 원래 오류를 그대로 돌려준다(빈 DB로 덮지 않는다).
+pub fn open_with_recovery(path: &Path) -> Result<(Connection, Option<Recovery>), String> {
+  if let Some(dir) = path.parent() {
+    std::fs::create_dir_all(dir).map_err(|e| format!("cannot create app data dir: {e}"))?;
+  }
+  let first = match open_checked(path) {
+    Ok(conn) => return Ok((conn, None)),
+    Err(OpenFail::Other(e)) => return Err(e),
+    Err(OpenFail::Corrupt(e)) => e,
+  };
+  let snaps: Vec<(usize, PathBuf)> = (1..=DB_SNAPSHOTS)
+    .map(|n| (n, snapshot_path(path, n)))
+    .filter(|(_, p)| p.exists())
+    .collect();
+  if snaps.is_empty() {
+    return Err(first);
+  }
+
+  let mut errors = vec![format!("{DB_FILE}: {first}")];
+  set_aside(path, CORRUPT_SUFFIX);
+  for (n, snap) in snaps {
+    if let Err(e) = std::fs::copy(&snap, path) {
+      errors.push(format!("{DB_FILE}.{n}: copy failed: {e}"));
+      continue;
+    }
+    match open_checked(path) {
+      Ok(conn) => {
+        sync_dir(path.parent().unwrap_or(Path::new(".")));
+        let source = format!("snapshot:{n}");
+        return Ok((conn, Some(Recovery { source, errors })));
+      }
+      Err(e) => {
+        errors.push(format!("{DB_FILE}.{n}: {}", e.message()));
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(sibling(path, "-wal"));
+        let _ = std::fs::remove_file(sibling(path, "-shm"));
+      }
+    }
+  }
+  Err(format!("DB가 손상됐고 쓸 수 있는 스냅샷도 없어요: {}", errors.join("; ")))
+}
+
+/// 저장 뒤 스냅샷을 한 세대 뜬다(VACUUM INTO → fsync → .1로 회전).
+/// 가장 최근 스냅샷이 min_interval보다 새것이면 건너뛰고 false.
+pub fn snapshot(conn: &Connection, path: &Path, min_interval: Duration) -> Result<bool, String> {
+  let latest = snapshot_path(path, 1);
+  let fresh = std::fs::metadata(&latest)
+    .and_then(|m| m.modified())
+    .ok()
+    .and_then(|t| t.elapsed().ok())
+    .is_some_and(|age| age < min_interval);
+  if fresh {
+    return Ok(false);
+  }
+
+  let tmp = sibling(path, ".snap.tmp");
+  let _ = std::fs::remove_file(&tmp);
+  conn
+    .execute("VACUUM INTO ?1", [tmp.to_string_lossy()])
+    .map_err(|e| format!("snapshot failed: {e}"))?;
+  File::open(&tmp)
+    .and_then(|f| f.sync_all())
+    .map_err(|e| format!("snapshot fsync failed: {e}"))?;
+
+  let _ = std::fs::remove_file(snapshot_path(path, DB_SNAPSHOTS));
+  for n in (1..DB_SNAPSHOTS).rev() {
+    let from = snapshot_path(path, n);
+    if from.exists() {
+      std::fs::rename(&from, snapshot_path(path, n + 1)).map_err(|e| format!("snapshot rotate failed: {e}"))?;
+    }
+  }
+  std::fs::rename(&tmp, &latest).map_err(|e| format!("snapshot rotate failed: {e}"))?;
+  sync_dir(path.parent().unwrap_or(Path::new(".")));
+  Ok(true)
+}
+
+/* -------------------- helpers -------------------- */
+
+fn db_err(e: rusqlite::Error) -> String {
+  format!("db error: {e}")
+}
+
+/// 외래 키 위반만(NOT NULL/UNIQUE 등 다른 제약 위반은 db error 그대로).
+/// ON DELETE RESTRICT 위반은 SQLite가 SQLITE_CONSTRAINT_TRIGGER로 알려서 메시지까지 본다.
+fn is_fk_violation(e: &rusqlite::Error) -> bool {
+  use rusqlite::ffi::{SQLITE_CONSTRAINT_FOREIGNKEY, SQLITE_CONSTRAINT_TRIGGER};
+  match e {
+    rusqlite::Error::SqliteFailure(f, msg) => {
+      f.extended_code == SQLITE_CONSTRAINT_FOREIGNKEY
+        || (f.extended_code == SQLITE_CONSTRAINT_TRIGGER && msg.as_deref() == Some("FOREIGN KEY constraint failed"))
+    }
+    _ => false,
+  }
+}
+
+/// 행 내용이 바뀌었는지 보는 지문(직렬화 결과의 FNV)
+fn row_hash<T: Serialize>(v: &T) -> Result<String, String> {
+  let text = serde_json::to_string(v).map_err(|e| format!("encode failed: {e}"))?;
+  Ok(format!("{:016x}", fnv1a64(&[&text])))
+}
+
+fn to_json<T: Serialize>(v: &T) -> Result<String, String> {
+  serde_json::to_string(v).map_err(|e| format!("encode failed: {e}"))
+}
+
+fn from_json<T: for<'de> Deserialize<'de>>(s: &str) -> Result<T, String> {
+  serde_json::from_str(s).map_err(|e| format!("decode failed: {e}"))
+}
+
+fn hashes(tx: &Transaction, table: &str) -> Result<HashMap<String, String>, String> {
+  let mut stmt = tx.prepare(&format!("SELECT id, hash FROM {table}")).map_err(db_err)?;
+  let rows = stmt
+    .query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))
+    .map_err(db_err)?;
+  rows.collect::<Result<_, _>>().map_err(db_err)
+}
+
+/// 기록을 쥐고 있는 케이스 제목들(삭제 거부 메시지용)
+fn holders(conn: &Connection, record_id: &str) -> Result<Vec<String>, String> {
+  let mut stmt = conn
+    .prepare("SELECT c.title FROM case_records cr JOIN cases c ON c.id = cr.case_id WHERE cr.record_id = ?1 ORDER BY c.title")
+    .map_err(db_err)?;
+  let rows = stmt.query_map([record_id], |r| r.get::<_, String>(0)).map_err(db_err)?;
+  rows.collect::<Result<_, _>>().map_err(db_err)
+}
+
+/// 케이스 안의 행(단계/권고/연결)을 따로 고치면 지문을 비워 다음 전체 저장 때 다시 쓰게 한다
+fn touch_case(tx: &Transaction, case_id: &str) -> Result<(), String> {
+  let n = tx
+    .execute("UPDATE cases SET hash = '' WHERE id = ?1", [case_id])
+    .map_err(db_err)?;
+  if n == 0 {
+    return Err(format!("case not found: {case_id}"));
+  }
+  Ok(())
+}
+
+/* -------------------- records -------------------- */
+
+fn write_record(tx: &Transaction, r: &RecordItem, hash: &str) -> Result<(), String> {
+  tx.execute(
+    "INSERT INTO records (id, ts, store_type, store_other, lv, actor_type, actor_name, place, place_other, summary, updated_at, hash)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+     ON CONFLICT(id) DO UPDATE SET
+       ts = excluded.ts, store_type = excluded.store_type, store_other = excluded.store_other, lv = excluded.lv,
+       actor_type = excluded.actor_type, actor_name = excluded.actor_name, place = excluded.place,
+       place_other = excluded.place_other, summary = excluded.summary, updated_at = excluded.updated_at,
+       hash = excluded.hash",
+    params![
+      r.id,
+      r.ts,
+      r.store_type,
+      r.store_other,
+      r.lv,
+      r.actor.r#type,
+      r.actor.name,
+      r.place,
+      r.place_other,
+      r.summary,
+      r.updated_at,
+      hash
+    ],
+  )
+  .map_err(db_err)?;
+
+  tx.execute("DELETE FROM record_related WHERE record_id = ?1", [&r.id])
+    .map_err(db_err)?;
+  for (pos, a) in r.related.iter().enumerate() {
+    tx.execute(
+      "INSERT INTO record_related (record_id, pos, actor_type, actor_name) VALUES (?1, ?2, ?3, ?4)",
+      params![r.id, pos as i64, a.r#type, a.name],
+    )
+    .map_err(db_err)?;
+  }
+  Ok(())
+}
+
+/// 지울 수 없으면(케이스에 포함) 어떤 케이스인지 알려 준다
+fn delete_record_row(tx: &Transaction, id: &str) -> Result<bool, String> {
+  match tx.execute("DELETE FROM records WHERE id = ?1", [id]) {
+    Ok(n) => Ok(n > 0),
+    Err(e) if is_fk_violation(&e) => {
+      let titles = holders(tx, id)?;
+      Err(format!(
+        "케이스 {}개에 포함된 기록이라 삭제할 수 없음: {id} ({})",
+        titles.len(),
+        titles.join(", ")
+      ))
+    }
+    Err(e) => Err(db_err(e)),
+  }
+}
+
+pub fn upsert_records(conn: &mut Connection, records: &[RecordItem]) -> Result<usize, String> {
+  let tx = conn.transaction().map_err(db_err)?;
+  for r in records {
+    write_record(&tx, r, &row_hash(r)?)?;
+  }
+  tx.commit().map_err(db_err)?;
+  Ok(records.len())
+}
+
+/// 하나라도 지울 수 없으면 아무것도 지우지 않는다. 실제로 지운 개수를 돌려준다.
+pub fn delete_records(conn: &mut Connection, ids: &[String]) -> Result<usize, String> {
+  let tx = conn.transaction().map_err(db_err)?;
+  let mut n = 0usize;
+  for id in ids {
+    if delete_record_row(&tx, id)? {
+      n += 1;
+    }
+  }
+  tx.commit().map_err(db_err)?;
+  Ok(n)
+}
+
+fn load_related(conn: &Connection) -> Result<HashMap<String, Vec<ActorRef>>, String> {
+  let mut stmt = conn
+    .prepare("SELECT record_id, actor_type, actor_name FROM record_related ORDER BY record_id, pos")
+    .map_err(db_err)?;
+  let rows = stmt
+    .query_map([], |r| {
+      Ok((
+        r.get::<_, String>(0)?,
+        ActorRef {
+          r#type: r.get(1)?,
+          name: r.get(2)?,
+        },
+      ))
+    })
+    .map_err(db_err)?;
+  let mut out: HashMap<String, Vec<ActorRef>> = HashMap::new();
+  for row in rows {
+    let (id, a) = row.map_err(db_err)?;
+    out.entry(id).or_default().push(a);
+  }
+  Ok(out)
+}
+
+const RECORD_COLS: &str =
+  "id, ts, store_type, store_other, lv, actor_type, actor_name, place, place_other, summary, updated_at";
+
+fn record_from_row(r: &rusqlite::Row) -> rusqlite::Result<RecordItem> {
+  Ok(RecordItem {
+    id: r.get(0)?,
+    ts: r.get(1)?,
+    store_type: r.get(2)?,
+    store_other: r.get(3)?,
+    lv: r.get(4)?,
+    actor: ActorRef {
+      r#type: r.get(5)?,
+      name: r.get(6)?,
+    },
+    related: Vec::new(),
+    place: r.get(7)?,
+    place_other: r.get(8)?,
+    summary: r.get(9)?,
+    updated_at: r.get(10)?,
+  })
+}
+
+/// 최신순
+pub fn list_records(conn: &Connection) -> Result<Vec<RecordItem>, String> {
+  let mut related = load_related(conn)?;
+  let mut stmt = conn
+    .prepare(&format!("SELECT {RECORD_COLS} FROM records ORDER BY ts DESC, id"))
+    .map_err(db_err)?;
+  let rows = stmt.query_map([], record_from_row).map_err(db_err)?;
+  let mut out: Vec<RecordItem> = Vec::new();
+  for row in rows {
+    let mut r = row.map_err(db_err)?;
+    r.related = related.remove(&r.id).unwrap_or_default();
+    out.push(r);
+  }
+  Ok(out)
+}
+
+pub fn get_record(conn: &Connection, id: &str) -> Result<Option<RecordItem>, String> {
+  let rec = conn
+    .query_row(&format!("SELECT {RECORD_COLS} FROM records WHERE id = ?1"), [id], record_from_row)
+    .optional()
+    .map_err(db_err)?;
+  let Some(mut rec) = rec else {
+    return Ok(None);
+  };
+  let mut stmt = conn
+    .prepare("SELECT actor_type, actor_name FROM record_related WHERE record_id = ?1 ORDER BY pos")
+    .map_err(db_err)?;
+  let rows = stmt
+    .query_map([id], |r| {
+      Ok(ActorRef {
+        r#type: r.get(0)?,
+        name: r.get(1)?,
+      })
+    })
+    .map_err(db_err)?;
+  rec.related = rows.collect::<Result<_, _>>().map_err(db_err)?;
+  Ok(Some(rec))
+}
+
+/* -------------------- cases -------------------- */
+
+/// 없는 기록을 가리키면 오류
+fn write_case_records(tx: &Transaction, case_id: &str, record_ids: &[String]) -> Result<(), String> {
+  tx.execute("DELETE FROM case_records WHERE case_id = ?1", [case_id])
+    .map_err(db_err)?;
+  let mut seen: HashSet<&str> = HashSet::new();
+  for rid in record_ids {
+    if !seen.insert(rid.as_str()) {
+      continue;
+    }
+    let res = tx.execute(
+      "INSERT INTO case_records (case_id, record_id, pos) VALUES (?1, ?2, ?3)",
+      params![case_id, rid, (seen.len() - 1) as i64],
+    );
+    match res {
+      Ok(_) => {}
+      Err(e) if is_fk_violation(&e) => return Err(format!("케이스 {case_id}: 없는 기록 id {rid}")),
+      Err(e) => return Err(db_err(e)),
+    }
+  }
+  Ok(())
+}
+
+fn write_steps(tx: &Transaction, case_id: &str, steps: &[StepItem]) -> Result<(), String> {
+  tx.execute("DELETE FROM steps WHERE case_id = ?1", [case_id])
+    .map_err(db_err)?;
+  for (pos, s) in steps.iter().enumerate() {
+    insert_step(tx, case_id, pos as i64, s)?;
+  }
+  Ok(())
+}
+
+fn insert_step(tx: &Transaction, case_id: &str, pos: i64, s: &StepItem) -> Result<(), String> {
+  tx.execute(
+    "INSERT INTO steps (case_id, id, pos, ts, name, note, text, place, owner, lv) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+     ON CONFLICT(case_id, id) DO UPDATE SET
+       ts = excluded.ts, name = excluded.name, note = excluded.note, text = excluded.text,
+       place = excluded.place, owner = excluded.owner, lv = excluded.lv",
+    params![case_id, s.id, pos, s.ts, s.name, s.note, s.text, s.place, s.owner, s.lv],
+  )
+  .map_err(db_err)?;
+  Ok(())
+}
+
+fn write_advisors(tx: &Transaction, case_id: &str, advisors: &[AdvisorItem]) -> Result<(), String> {
+  tx.execute("DELETE FROM advisors WHERE case_id = ?1", [case_id])
+    .map_err(db_err)?;
+  for (pos, a) in advisors.iter().enumerate() {
+    let explain = a.explain.as_ref().map(to_json).transpose()?;
+    tx.execute(
+      "INSERT INTO advisors (case_id, id, pos, ts, title, body, level, tags, state, rule_id, stale, reasons, explain)
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+      params![
+        case_id,
+        a.id,
+        pos as i64,
+        a.ts,
+        a.title,
+        a.body,
+        a.level,
+        to_json(&a.tags)?,
+        a.state,
+        a.rule_id,
+        a.stale,
+        to_json(&a.reasons)?,
+        explain
+      ],
+    )
+    .map_err(db_err)?;
+  }
+  Ok(())
+}
+
+/// 케이스 행과 딸린 행(당사자/연결/단계/권고)을 통째로 다시 쓴다
+fn write_case(tx: &Transaction, c: &StoredCase, hash: &str) -> Result<(), String> {
+  let ci = &c.case_item;
+  tx.execute(
+    "INSERT INTO cases (id, title, query, time_from, time_to, max_results, only_main_actor, sens_filter, status, mode, created_at, updated_at, extra, hash)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+     ON CONFLICT(id) DO UPDATE SET
+       title = excluded.title, query = excluded.query, time_from = excluded.time_from, time_to = excluded.time_to,
+       max_results = excluded.max_results, only_main_actor = excluded.only_main_actor, sens_filter = excluded.sens_filter,
+       status = excluded.status, mode = excluded.mode, created_at = excluded.created_at, updated_at = excluded.updated_at,
+       extra = excluded.extra, hash = excluded.hash",
+    params![
+      ci.id,
+      ci.title,
+      ci.query,
+      ci.time_from,
+      ci.time_to,
+      ci.max_results,
+      ci.only_main_actor,
+      ci.sens_filter,
+      c.status,
+      c.mode,
+      ci.created_at,
+      c.updated_at,
+      to_json(&c.extra)?,
+      hash
+    ],
+  )
+  .map_err(db_err)?;
+
+  tx.execute("DELETE FROM case_actors WHERE case_id = ?1", [&ci.id])
+    .map_err(db_err)?;
+  for (pos, a) in ci.actors.iter().enumerate() {
+    tx.execute(
+      "INSERT INTO case_actors (case_id, pos, actor_type, actor_name) VALUES (?1, ?2, ?3, ?4)",
+      params![ci.id, pos as i64, a.r#type, a.name],
+    )
+    .map_err(db_err)?;
+  }
+
+  write_case_records(tx, &ci.id, &ci.record_ids)?;
+  write_steps(tx, &ci.id, &c.steps)?;
+  write_advisors(tx, &ci.id, &ci.advisors)
+}
+
+/// 없는 기록을 가리키면 오류
+pub fn upsert_case(conn: &mut Connection, c: &StoredCase) -> Result<(), String> {
+  let tx = conn.transaction().map_err(db_err)?;
+  write_case(&tx, c, &row_hash(c)?)?;
+  tx.commit().map_err(db_err)
+}
+
+/// 케이스를 지워도 기록은 남는다
+pub fn delete_case(conn: &mut Connection, id: &str) -> Result<bool, String> {
+  let n = conn.execute("DELETE FROM cases WHERE id = ?1", [id]).map_err(db_err)?;
+  Ok(n > 0)
+}
+
+fn read_case(conn: &Connection, id: &str) -> Result<Option<StoredCase>, String> {
+  let row = conn
+    .query_row(
+      "SELECT title, query, time_from, time_to, max_results, only_main_actor, sens_filter, status, mode, created_at, extra, updated_at
+       FROM cases WHERE id = ?1",
+      [id],
+      |r| {
+        Ok((
+          CaseItem {
+            id: id.to_string(),
+            title: r.get(0)?,
+            query: r.get(1)?,
+            time_from: r.get(2)?,
+            time_to: r.get(3)?,
+            max_results: r.get(4)?,
+            actors: Vec::new(),
+            only_main_actor: r.get(5)?,
+            sens_filter: r.get(6)?,
+            created_at: r.get(9)?,
+            record_ids: Vec::new(),
+            advisors: Vec::new(),
+          },
+          r.get::<_, String>(7)?,
+          r.get::<_, Option<String>>(8)?,
+          r.get::<_, String>(10)?,
+          r.get::<_, String>(11)?,
+        ))
+      },
+    )
+    .optional()
+    .map_err(db_err)?;
+  let Some((mut ci, status, mode, extra, updated_at)) = row else {
+    return Ok(None);
+  };
+
+  let mut stmt = conn
+    .prepare("SELECT actor_type, actor_name FROM case_actors WHERE case_id = ?1 ORDER BY pos")
+    .map_err(db_err)?;
+  ci.actors = stmt
+    .query_map([id], |r| {
+      Ok(ActorRef {
+        r#type: r.get(0)?,
+        name: r.get(1)?,
+      })
+    })
+    .map_err(db_err)?
+    .collect::<Result<_, _>>()
+    .map_err(db_err)?;
+
+  let mut stmt = conn
+    .prepare("SELECT record_id FROM case_records WHERE case_id = ?1 ORDER BY pos")
+    .map_err(db_err)?;
+  ci.record_ids = stmt
+    .query_map([id], |r| r.get(0))
+    .map_err(db_err)?
+    .collect::<Result<_, _>>()
+    .map_err(db_err)?;
+
+  let mut stmt = conn
+    .prepare("SELECT id, ts, title, body, level, tags, state, rule_id, stale, reasons, explain FROM advisors WHERE case_id = ?1 ORDER BY pos")
+    .map_err(db_err)?;
+  let rows = stmt
+    .query_map([id], |r| {
+      Ok((
+        r.get::<_, String>(0)?,
+        r.get::<_, String>(1)?,
+        r.get::<_, String>(2)?,
+        r.get::<_, String>(3)?,
+        r.get::<_, String>(4)?,
+        r.get::<_, String>(5)?,
+        r.get::<_, String>(6)?,
+        r.get::<_, Option<String>>(7)?,
+        r.get::<_, bool>(8)?,
+        r.get::<_, String>(9)?,
+        r.get::<_, Option<String>>(10)?,
+      ))
+    })
+    .map_err(db_err)?;
+  for row in rows {
+    let (aid, ts, title, body, level, tags, state, rule_id, stale, reasons, explain) = row.map_err(db_err)?;
+    ci.advisors.push(AdvisorItem {
+      id: aid,
+      ts,
+      title,
+      body,
+      level,
+      tags: from_json(&tags)?,
+      state,
+      rule_id,
+      stale,
+      reasons: from_json(&reasons)?,
+      explain: explain.as_deref().map(from_json).transpose()?,
+    });
+  }
+
+  let mut stmt = conn
+    .prepare("SELECT id, ts, name, note, text, place, owner, lv FROM steps WHERE case_id = ?1 ORDER BY pos")
+    .map_err(db_err)?;
+  let steps: Vec<StepItem> = stmt
+    .query_map([id], |r| {
+      Ok(StepItem {
+        id: r.get(0)?,
+        ts: r.get(1)?,
+        name: r.get(2)?,
+        note: r.get(3)?,
+        text: r.get(4)?,
+        place: r.get(5)?,
+        owner: r.get(6)?,
+        lv: r.get(7)?,
+      })
+    })
+    .map_err(db_err)?
+    .collect::<Result<_, _>>()
+    .map_err(db_err)?;
+
+  Ok(Some(StoredCase {
+    case_item: ci,
+    status,
+    mode,
+    steps,
+    updated_at,
+    extra: from_json(&extra)?,
+  }))
+}
+
+pub fn get_case(conn: &Connection, id: &str) -> Result<Option<StoredCase>, String> {
+  read_case(conn, id)
+}
+
+/// 만든 순서(오래된 것 먼저)
+pub fn list_cases(conn: &Connection) -> Result<Vec<StoredCase>, String> {
+  let mut stmt = conn
+    .prepare("SELECT id FROM cases ORDER BY created_at, rowid")
+    .map_err(db_err)?;
+  let ids: Vec<String> = stmt
+    .query_map([], |r| r.get(0))
+    .map_err(db_err)?
+    .collect::<Result<_, _>>()
+    .map_err(db_err)?;
+  ids.iter().filter_map(|id| read_case(conn, id).transpose()).collect()
+}
+
+/// 케이스에 기록을 더한다(이미 있는 건 그대로). 없는 기록이면 오류.
+pub fn add_case_records(conn: &mut Connection, case_id: &str, record_ids: &[String]) -> Result<usize, String> {
+  let tx = conn.transaction().map_err(db_err)?;
+  touch_case(&tx, case_id)?;
+  let next: i64 = tx
+    .query_row("SELECT COALESCE(MAX(pos) + 1, 0) FROM case_records WHERE case_id = ?1", [case_id], |r| r.get(0))
+    .map_err(db_err)?;
+  let mut added = 0usize;
+  for rid in record_ids {
+    let res = tx.execute(
+      "INSERT OR IGNORE INTO case_records (case_id, record_id, pos) VALUES (?1, ?2, ?3)",
+      params![case_id, rid, next + added as i64],
+    );
+    match res {
+      Ok(n) => added += n,
+      Err(e) if is_fk_violation(&e) => return Err(format!("케이스 {case_id}: 없는 기록 id {rid}")),
+      Err(e) => return Err(db_err(e)),
+    }
+  }
+  tx.commit().map_err(db_err)?;
+  Ok(added)
+}
+
+/// 케이스에서 빼기만 한다(기록 자체는 남는다)
+pub fn remove_case_records(conn: &mut Connection, case_id: &str, record_ids: &[String]) -> Result<usize, String> {
+  let tx = conn.transaction().map_err(db_err)?;
+  touch_case(&tx, case_id)?;
+  let mut removed = 0usize;
+  for rid in record_ids {
+    removed += tx
+      .execute("DELETE FROM case_records WHERE case_id = ?1 AND record_id = ?2", params![case_id, rid])
+      .map_err(db_err)?;
+  }
+  tx.commit().map_err(db_err)?;
+  Ok(removed)
+}
+
+/* -------------------- steps / advisors -------------------- */
+
+/// 같은 id면 고치고, 없으면 맨 뒤에 더한다
+pub fn upsert_step(conn: &mut Connection, case_id: &str, step: &StepItem) -> Result<(), String> {
+  let tx = conn.transaction().map_err(db_err)?;
+  touch_case(&tx, case_id)?;
+  let pos: i64 = tx
+    .query_row(
+      "SELECT COALESCE((SELECT pos FROM steps WHERE case_id = ?1 AND id = ?2), (SELECT COALESCE(MAX(pos) + 1, 0) FROM steps WHERE case_id = ?1))",
+      params![case_id, step.id],
+      |r| r.get(0),
+    )
+    .map_err(db_err)?;
+  insert_step(&tx, case_id, pos, step)?;
+  tx.commit().map_err(db_err)
+}
+
+pub fn delete_step(conn: &mut Connection, case_id: &str, step_id: &str) -> Result<bool, String> {
+  let tx = conn.transaction().map_err(db_err)?;
+  touch_case(&tx, case_id)?;
+  let n = tx
+    .execute("DELETE FROM steps WHERE case_id = ?1 AND id = ?2", params![case_id, step_id])
+    .map_err(db_err)?;
+  tx.commit().map_err(db_err)?;
+  Ok(n > 0)
+}
+
+/// 권고는 재생성 단위로 통째로 바꾼다
+pub fn set_advisors(conn: &mut Connection, case_id: &str, advisors: &[AdvisorItem]) -> Result<(), String> {
+  let tx = conn.transaction().map_err(db_err)?;
+  touch_case(&tx, case_id)?;
+  write_advisors(&tx, case_id, advisors)?;
+  tx.commit().map_err(db_err)
+}
+
+/* -------------------- full-text search -------------------- */
+
+/// 공백으로 나눈 말마다 앞부분 일치("폭언" → "폭언을"), 모두 들어간 기록만
+fn fts_query(q: &str) -> String {
+  q.split_whitespace()
+    .map(|t| format!("\"{}\"*", t.replace('"', "\"\"")))
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+pub fn search(conn: &Connection, query: &str, limit: usize) -> Result<Vec<FtsHit>, String> {
+  let q = fts_query(query);
+  if q.is_empty() {
+    return Ok(Vec::new());
+  }
+  let mut stmt = conn
+    .prepare(
+      "SELECT r.id, snippet(records_fts, 0, '[', ']', '…', 12), bm25(records_fts)
+       FROM records_fts JOIN records r ON r.rid = records_fts.rowid
+       WHERE records_fts MATCH ?1
+       ORDER BY bm25(records_fts), r.ts DESC
+       LIMIT ?2",
+    )
+    .map_err(db_err)?;
+  let rows = stmt
+    .query_map(params![q, limit as i64], |r| {
+      Ok(FtsHit {
+        record_id: r.get(0)?,
+        snippet: r.get(1)?,
+        score: -r.get::<_, f64>(2)?,
+      })
+    })
+    .map_err(|e| format!("search failed: {e}"))?;
+  rows.collect::<Result<_, _>>().map_err(|e| format!("search failed: {e}"))
+}
+
+/* -------------------- AppState bridge (load_state / save_state) -------------------- */
+
+/// 한 번이라도 저장했는지(처음이면 JSON 파일에서 옮겨 온다)
+pub fn has_state(conn: &Connection) -> Result<bool, String> {
+  conn
+    .query_row("SELECT 1 FROM meta WHERE key = ?1", [META_APP], |_| Ok(()))
+    .optional()
+    .map(|r| r.is_some())
+    .map_err(db_err)
+}
+
+/// AppState 모양으로 다시 조립(records 최신순, cases는 id → 케이스)
+pub fn load_app_state(conn: &Connection) -> Result<Option<Value>, String> {
+  let app: Option<String> = conn
+    .query_row("SELECT value FROM meta WHERE key = ?1", [META_APP], |r| r.get(0))
+    .optional()
+    .map_err(db_err)?;
+  let Some(app) = app else {
+    return Ok(None);
+  };
+  let mut state: Map<String, Value> = from_json(&app)?;
+  // "전체 삭제" 직후는 빈 객체 그대로
+  if state.is_empty() {
+    return Ok(Some(Value::Object(state)));
+  }
+
+  let records = list_records(conn)?;
+  let mut cases = Map::new();
+  for c in list_cases(conn)? {
+    let v = serde_json::to_value(&c).map_err(|e| format!("encode failed: {e}"))?;
+    cases.insert(c.case_item.id.clone(), v);
+  }
+  state.insert(
+    "records".into(),
+    serde_json::to_value(records).map_err(|e| format!("encode failed: {e}"))?,
+  );
+  state.insert("cases".into(), Value::Object(cases));
+  Ok(Some(Value::Object(state)))
+}
+
+/// 전체 AppState(v8)를 받아 바뀐 행만 쓴다. 한 트랜잭션이라 실패하면 아무것도 바뀌지 않는다.
+/// 새 상태에도 DB에도 없는 기록을 가리키는 케이스 연결은 버리고 dropped_links에 남긴다.
+pub fn save_app_state(conn: &mut Connection, state: &AppState) -> Result<SyncReport, String> {
+  let records = &state.records;
+  let mut cases: Vec<StoredCase> = state.cases.values().cloned().collect();
+  let app = serde_json::json!({
+    "v": state.v,
+    "tab": state.tab,
+    "selectedCaseId": state.selected_case_id,
+  });
+
+  let mut report = SyncReport::default();
+  let tx = conn.transaction().map_err(db_err)?;
+  let old_records = hashes(&tx, "records")?;
+  let old_cases = hashes(&tx, "cases")?;
+
+  // 새 상태에도 DB에도 없는 기록을 가리키는 연결은 지문을 내기 전에 버린다(다시 저장할 때 또 쓰지 않게).
+  // DB에만 있는 기록을 가리키면 그대로 두어, 그 기록을 지우려는 저장을 외래 키가 막게 한다.
+  let keep_records: HashSet<&str> = records.iter().map(|r| r.id.as_str()).collect();
+  for c in &mut cases {
+    let case_id = &c.case_item.id;
+    c.case_item.record_ids.retain(|rid| {
+      let ok = keep_records.contains(rid.as_str()) || old_records.contains_key(rid);
+      if !ok {
+        report.dropped_links.push(format!("{case_id} → {rid}"));
+      }
+      ok
+    });
+  }
+
+  // 1) 사라진 케이스 먼저(연결이 풀려야 기록을 지울 수 있다)
+  let keep_cases: HashSet<&str> = cases.iter().map(|c| c.case_item.id.as_str()).collect();
+  for id in old_cases.keys().filter(|id| !keep_cases.contains(id.as_str())) {
+    tx.execute("DELETE FROM cases WHERE id = ?1", [id]).map_err(db_err)?;
+    report.cases_deleted += 1;
+  }
+
+  // 2) 바뀐 기록
+  for r in records {
+    let h = row_hash(r)?;
+    if old_records.get(&r.id) != Some(&h) {
+      write_record(&tx, r, &h)?;
+      report.records_written += 1;
+    }
+  }
+
+  // 3) 바뀐 케이스
+  for c in &cases {
+    let h = row_hash(c)?;
+    if old_cases.get(&c.case_item.id) != Some(&h) {
+      write_case(&tx, c, &h)?;
+      report.cases_written += 1;
+    }
+  }
+
+  // 4) 사라진 기록(케이스가 아직 쥐고 있으면 외래 키가 막는다)
+  for id in old_records.keys().filter(|id| !keep_records.contains(id.as_str())) {
+    delete_record_row(&tx, id)?;
+    report.records_deleted += 1;
+  }
+
+  tx.execute(
+    "INSERT INTO meta (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+    params![META_APP, to_json(&app)?],
+  )
+  .map_err(db_err)?;
+  tx.commit().map_err(db_err)?;
+  Ok(report)
+}
+
+/// 변경분만 한 트랜잭션으로 쓴다(순서는 save_app_state와 같다).
+/// 없는 기록을 가리키는 케이스나 케이스에 포함된 기록 삭제는 오류로 되돌린다.
+pub fn apply_changes(conn: &mut Connection, ch: &StateChanges) -> Result<SyncReport, String> {
+  let app = serde_json::json!({
+    "v": CURRENT_VERSION,
+    "tab": ch.tab,
+    "selectedCaseId": ch.selected_case_id,
+  });
+
+  let mut report = SyncReport::default();
+  let tx = conn.transaction().map_err(db_err)?;
+  for id in &ch.deleted_case_ids {
+    report.cases_deleted += tx.execute("DELETE FROM cases WHERE id = ?1", [id]).map_err(db_err)?;
+  }
+  for r in &ch.records {
+    write_record(&tx, r, &row_hash(r)?)?;
+    report.records_written += 1;
+  }
+  for c in &ch.cases {
+    write_case(&tx, c, &row_hash(c)?)?;
+    report.cases_written += 1;
+  }
+  for id in &ch.deleted_record_ids {
+    if delete_record_row(&tx, id)? {
+      report.records_deleted += 1;
+    }
+  }
+  tx.execute(
+    "INSERT INTO meta (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+    params![META_APP, to_json(&app)?],
+  )
+  .map_err(db_err)?;
+  tx.commit().map_err(db_err)?;
+  Ok(report)
+}
+
+/// "전체 삭제": 모든 행을 지우고 빈 상태({})를 남긴다
+pub fn clear_app_state(conn: &mut Connection) -> Result<(), String> {
+  let tx = conn.transaction().map_err(db_err)?;
+  tx.execute_batch("DELETE FROM cases; DELETE FROM records;").map_err(db_err)?;
+  tx.execute(
+    "INSERT INTO meta (key, value) VALUES (?1, '{}') ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+    [META_APP],
+  )
+  .map_err(db_err)?;
+  tx.commit().map_err(db_err)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::test_util::{case_json, record};
+  use serde_json::json;
+
+  fn tmp_dir(name: &str) -> PathBuf {
+    let d = std::env::temp_dir().join(format!("db-test-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&d);
+    std::fs::create_dir_all(&d).unwrap();
+    d
+  }
+
+  fn rec(id: &str, summary: &str) -> RecordItem {
+    record(id, summary, json!({}))
+  }
+
+  fn hit_ids(conn: &Connection, q: &str) -> Vec<String> {
+    let mut ids: Vec<String> = search(conn, q, 10).unwrap().into_iter().map(|h| h.record_id).collect();
+    ids.sort();
+    ids
+  }
+
+  #[test]
+  fn fts_follows_rid_across_delete_and_vacuum() {
+    let d = tmp_dir("fts");
+    let mut conn = open(&d.join(DB_FILE)).unwrap();
+    upsert_records(&mut conn, &[rec("a", "수업 중 폭언"), rec("b", "급식 메뉴"), rec("c", "복도에서 폭언")]).unwrap();
+    delete_records(&mut conn, &["a".to_string()]).unwrap();
+    conn.execute_batch("VACUUM").unwrap();
+    upsert_records(&mut conn, &[rec("b", "쉬는 시간 폭언")]).unwrap();
+    assert_eq!(hit_ids(&conn, "폭언"), vec!["b", "c"]);
+    assert!(hit_ids(&conn, "급식").is_empty());
+    drop(conn);
+    std::fs::remove_dir_all(&d).unwrap();
+  }
+
+  #[test]
+  fn only_foreign_key_failures_count_as_fk_violations() {
+    let d = tmp_dir("fk");
+    let mut conn = open(&d.join(DB_FILE)).unwrap();
+    upsert_records(&mut conn, &[rec("a", "수업 중 폭언")]).unwrap();
+
+    let fk = conn
+      .execute("INSERT INTO case_records (case_id, record_id, pos) VALUES ('없음', 'a', 0)", [])
+      .unwrap_err();
+    assert!(is_fk_violation(&fk));
+    conn.execute("INSERT INTO cases (id, title) VALUES ('c1', '사건')", []).unwrap();
+    conn.execute("INSERT INTO case_records (case_id, record_id, pos) VALUES ('c1', 'a', 0)", []).unwrap();
+    let restrict = conn.execute("DELETE FROM records WHERE id = 'a'", []).unwrap_err();
+    assert!(is_fk_violation(&restrict));
+    let err = delete_records(&mut conn, &["a".to_string()]).unwrap_err();
+    assert!(err.contains("케이스 1개에 포함된 기록이라 삭제할 수 없음: a (사건)"), "{err}");
+    let not_null = conn.execute("INSERT INTO records (id) VALUES ('b')", []).unwrap_err();
+    assert!(!is_fk_violation(&not_null));
+    let unique = conn
+      .execute("INSERT INTO meta (key, value) VALUES ('k', '1'), ('k', '2')", [])
+      .unwrap_err();
+    assert!(!is_fk_violation(&unique));
+    drop(conn);
+    std::fs::remove_dir_all(&d).unwrap();
+  }
+
+  #[test]
+  fn changes_apply_in_one_transaction() {
+    let d = tmp_dir("changes");
+    let mut conn = open(&d.join(DB_FILE)).unwrap();
+    let case: StoredCase = serde_json::from_value(case_json("c1", json!({"recordIds": ["a"]}))).unwrap();
+    let changes = |v: Value| -> StateChanges { serde_json::from_value(v).unwrap() };
+
+    let r = apply_changes(
+      &mut conn,
+      &StateChanges {
+        records: vec![rec("a", "수업 중 폭언"), rec("b", "급식 메뉴")],
+        cases: vec![case],
+        ..changes(json!({"tab": "cases", "selectedCaseId": "c1"}))
+      },
+    )
+    .unwrap();
+    assert_eq!((r.records_written, r.cases_written), (2, 1));
+    let st = load_app_state(&conn).unwrap().unwrap();
+    assert_eq!(st["tab"], "cases");
+    assert_eq!(st["cases"]["c1"]["recordIds"], json!(["a"]));
+
+    // 케이스에 포함된 기록을 지우려 하면 같은 변경분의 다른 쓰기도 되돌린다
+    let blocked = StateChanges {
+      records: vec![rec("b", "급식 메뉴(고침)")],
+      deleted_record_ids: vec!["a".into()],
+      ..changes(json!({"tab": "records"}))
+    };
+    assert!(apply_changes(&mut conn, &blocked).is_err());
+    assert_eq!(get_record(&conn, "b").unwrap().unwrap().summary, "급식 메뉴");
+
+    let r = apply_changes(
+      &mut conn,
+      &changes(json!({"tab": "records", "deletedCaseIds": ["c1"], "deletedRecordIds": ["a", "없음"]})),
+    )
+    .unwrap();
+    assert_eq!((r.cases_deleted, r.records_deleted), (1, 1));
+    assert_eq!(list_records(&conn).unwrap().len(), 1);
+    drop(conn);
+    std::fs::remove_dir_all(&d).unwrap();
+  }
+
+  #[test]
+  fn v1_database_migrates_to_rid_layout() {
+    let d = tmp_dir("v1");
+    let path = d.join(DB_FILE);
+    {
+      // v1: records는 암묵적 rowid, records_fts도 그것을 가리켰다
+      let conn = Connection::open(&path).unwrap();
+      conn
+        .execute_batch(
+          "CREATE TABLE records (
+             id TEXT PRIMARY KEY, ts TEXT NOT NULL, store_type TEXT NOT NULL, store_other TEXT NOT NULL DEFAULT '',
+             lv TEXT NOT NULL, actor_type TEXT NOT NULL, actor_name TEXT NOT NULL, place TEXT NOT NULL,
+             place_other TEXT NOT NULL DEFAULT '', summary TEXT NOT NULL, hash TEXT NOT NULL DEFAULT '');
+           CREATE VIRTUAL TABLE records_fts USING fts5(summary, content = 'records', content_rowid = 'rowid');
+           CREATE TRIGGER records_fts_ai AFTER INSERT ON records BEGIN
+             INSERT INTO records_fts(rowid, summary) VALUES (new.rowid, new.summary);
+           END;",
+        )
+        .unwrap();
+      conn.execute_batch(SCHEMA).unwrap();
+      // updated_at은 v3에서 생겼다
+      conn.execute_batch("ALTER TABLE cases DROP COLUMN updated_at").unwrap();
+      conn
+        .execute_batch(
+          "INSERT INTO records (id, ts, store_type, lv, actor_type, actor_name, place, summary)
+             VALUES ('r1', '2025-03-01', '메모', 'LV2', '학생', '김철수', '교실', '수업 중 폭언'),
+                    ('r2', '2025-03-02', '메모', 'LV2', '학생', '김철수', '교실', '학부모 민원');
+           INSERT INTO cases (id, title) VALUES ('c1', '사건');
+           INSERT INTO case_records (case_id, record_id, pos) VALUES ('c1', 'r1', 0);
+           PRAGMA user_version = 1;",
+        )
+        .unwrap();
+    }
+
+    let mut conn = open(&path).unwrap();
+    let version: i32 = conn.query_row("PRAGMA user_version", [], |r| r.get(0)).unwrap();
+    assert_eq!(version, DB_SCHEMA_VERSION);
+    let rids: i64 = conn.query_row("SELECT count(rid) FROM records", [], |r| r.get(0)).unwrap();
+    assert_eq!(rids, 2);
+    assert_eq!(hit_ids(&conn, "폭언"), vec!["r1"]);
+    assert!(list_records(&conn).unwrap().iter().all(|r| r.updated_at.is_empty()));
+    assert_eq!(get_case(&conn, "c1").unwrap().unwrap().updated_at, "");
+    // 외래 키는 새 records 표를 가리킨다
+    assert!(delete_records(&mut conn, &["r1".to_string()]).is_err());
+    assert_eq!(delete_records(&mut conn, &["r2".to_string()]).unwrap(), 1);
+    drop(conn);
+    std::fs::remove_dir_all(&d).unwrap();
+  }
+
+  #[test]
+  fn snapshots_rotate_and_respect_interval() {
+    let d = tmp_dir("snap");
+    let path = d.join(DB_FILE);
+    let mut conn = open(&path).unwrap();
+    for i in 0..DB_SNAPSHOTS + 2 {
+      upsert_records(&mut conn, &[rec(&format!("r{i}"), "메모")]).unwrap();
+      assert!(snapshot(&conn, &path, Duration::ZERO).unwrap());
+    }
+    assert!(!snapshot(&conn, &path, Duration::from_secs(3600)).unwrap());
+    assert!(snapshot_path(&path, DB_SNAPSHOTS).exists());
+    assert!(!snapshot_path(&path, DB_SNAPSHOTS + 1).exists());
+    assert!(!sibling(&path, ".snap.tmp").exists());
+    // .1이 가장 최근
+    let latest = Connection::open(snapshot_path(&path, 1)).unwrap();
+    let n: i64 = latest.query_row("SELECT count(*) FROM records", [], |r| r.get(0)).unwrap();
+    assert_eq!(n as usize, DB_SNAPSHOTS + 2);
+    drop(conn);
+    std::fs::remove_dir_all(&d).unwrap();
+  }
+
+  #[test]
+  fn corrupt_database_recovers_from_newest_good_snapshot() {
+    let d = tmp_dir("recover");
+    let path = d.join(DB_FILE);
+    let mut conn = open(&path).unwrap();
+    upsert_records(&mut conn, &[rec("old", "예전 메모")]).unwrap();
+    snapshot(&conn, &path, Duration::ZERO).unwrap();
+    upsert_records(&mut conn, &[rec("new", "새 메모")]).unwrap();
+    snapshot(&conn, &path, Duration::ZERO).unwrap();
+    drop(conn);
+
+    // 가장 최근 스냅샷도 망가졌으면 그다음 것으로
+    std::fs::write(snapshot_path(&path, 1), b"garbage").unwrap();
+    std::fs::write(&path, b"definitely not sqlite").unwrap();
+    let _ = std::fs::remove_file(sibling(&path, "-wal"));
+
+    let (conn, recovery) = open_with_recovery(&path).unwrap();
+    let recovery = recovery.expect("recovered");
+    assert_eq!(recovery.source, "snapshot:2");
+    assert_eq!(recovery.errors.len(), 2);
+    let ids: Vec<String> = list_records(&conn).unwrap().into_iter().map(|r| r.id).collect();
+    assert_eq!(ids, vec!["old"]);
+    assert!(sibling(&path, &format!(".{CORRUPT_SUFFIX}")).exists());
+    drop(conn);
+    std::fs::remove_dir_all(&d).unwrap();
+  }
+
+  #[test]
+  fn corrupt_database_without_snapshots_is_an_error() {
+    let d = tmp_dir("nosnap");
+    let path = d.join(DB_FILE);
+    std::fs::write(&path, b"definitely not sqlite").unwrap();
+    assert!(open_with_recovery(&path).is_err());
+    // 원본은 그대로 둔다
+    assert_eq!(std::fs::read(&path).unwrap(), b"definitely not sqlite");
+    std::fs::remove_dir_all(&d).unwrap();
+  }
+}
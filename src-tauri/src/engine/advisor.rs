@@ -0,0 +1,684 @@
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::advisor_custom::{load_overlay, merge_overlay};
+use super::{lv_rank, norm, parse_ts, CaseItem, RecordItem};
+
+/* -------------------- rule pack (assets/advisor.json) -------------------- */
+
+/// AppDataDir에 이 이름으로 파일을 두면 내장 규칙팩 대신 사용(버전이 같거나 높을 때만)
+pub const ADVISOR_PACK_FILE: &str = "advisor.json";
+
+pub const ANCHOR_TYPES: &[&str] = &["case_created", "first_record", "last_record", "after_first_match"];
+pub const LEVELS: &[&str] = &["info", "warn", "critical"];
+
+pub const BUILTIN_PACK_JSON: &str = include_str!("../../assets/advisor.json");
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdvisorPack {
+  pub version: u32,
+  #[serde(default)]
+  pub rules: Vec<AdvisorRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdvisorRule {
+  pub id: String,
+  #[serde(default)]
+  pub priority: i32,
+  pub level: String,
+  #[serde(default)]
+  pub tags: Vec<String>,
+  pub title: String,
+  #[serde(default)]
+  pub body: Vec<String>,
+  #[serde(default)]
+  pub when: RuleWhen,
+  pub anchor: RuleAnchor,
+}
+
+/// 규칙 발동 조건. 기록 단위 조건을 모두 만족하는 기록을 "매칭 기록"으로 보고,
+/// 케이스 단위 조건(recordCountAtLeast / storeTypesAll / hasRelated=false)은 매칭 기록 집합에 적용한다.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleWhen {
+  #[serde(default)]
+  pub keywords_any: Option<Vec<String>>,
+  #[serde(default)]
+  pub min_sensitivity: Option<String>,
+  #[serde(default)]
+  pub store_types_any: Option<Vec<String>>,
+  #[serde(default)]
+  pub store_types_all: Option<Vec<String>>,
+  #[serde(default)]
+  pub places_any: Option<Vec<String>>,
+  #[serde(default)]
+  pub actor_types_any: Option<Vec<String>>,
+  #[serde(default)]
+  pub has_related: Option<bool>,
+  #[serde(default)]
+  pub record_count_at_least: Option<u32>,
+  /// true면 placeOther가 비어 있는 기록에서 발동(상세 위치 입력 권장)
+  #[serde(default)]
+  pub require_place_other: Option<bool>,
+  /// true면 storeOther가 비어 있는 기록에서 발동(식별 단서 입력 권장)
+  #[serde(default)]
+  pub require_store_other: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleAnchor {
+  #[serde(rename = "type")]
+  pub r#type: String,
+  #[serde(default)]
+  pub offset_minutes: i64,
+  #[serde(default, rename = "match")]
+  pub r#match: Option<AnchorMatch>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorMatch {
+  #[serde(default)]
+  pub store_type: Option<String>,
+  #[serde(default)]
+  pub place: Option<String>,
+}
+
+static BUILTIN_PACK: Lazy<AdvisorPack> =
+  Lazy::new(|| serde_json::from_str(BUILTIN_PACK_JSON).expect("assets/advisor.json must be a valid advisor pack"));
+
+pub fn builtin_pack() -> &'static AdvisorPack {
+  &BUILTIN_PACK
+}
+
+/// AppDataDir의 규칙팩이 있고 내장 버전 이상이면 그것을, 아니면 내장 팩을 사용
+pub fn load_base_pack(app_data_dir: Option<&Path>) -> Result<AdvisorPack, String> {
+  let builtin = builtin_pack();
+
+  let Some(dir) = app_data_dir else {
+    return Ok(builtin.clone());
+  };
+  let path = dir.join(ADVISOR_PACK_FILE);
+  if !path.exists() {
+    return Ok(builtin.clone());
+  }
+
+  let text = std::fs::read_to_string(&path).map_err(|e| format!("advisor pack read failed: {e}"))?;
+  let pack: AdvisorPack =
+    serde_json::from_str(&text).map_err(|e| format!("advisor pack parse failed ({}): {e}", path.display()))?;
+
+  if pack.version < builtin.version {
+    return Ok(builtin.clone());
+  }
+  Ok(pack)
+}
+
+/// 쓸 규칙팩과, 그걸 고르며 생긴 경고(사용자 규칙을 못 읽어 건너뜀 등)
+#[derive(Debug, Clone)]
+pub struct LoadedPack {
+  pub pack: AdvisorPack,
+  pub warnings: Vec<String>,
+}
+
+/// engine_advise / engine_regenerate_advisors 결과
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdviseResult {
+  pub advisors: Vec<AdvisorItem>,
+  pub warnings: Vec<String>,
+}
+
+/// 기본 팩 + AppDataDir 사용자 규칙(advisor_custom.json) 병합본.
+/// 사용자 규칙 파일이 깨졌으면 권고를 막지 않고 기본 팩만 쓰고 경고로 알린다(파일은 그대로 둬서 고칠 수 있게).
+pub fn load_pack(app_data_dir: Option<&Path>) -> Result<LoadedPack, String> {
+  let base = load_base_pack(app_data_dir)?;
+  let (pack, warnings) = match app_data_dir.map(load_overlay) {
+    Some(Ok(overlay)) => (merge_overlay(&base, &overlay), Vec::new()),
+    Some(Err(e)) => (base, vec![format!("사용자 규칙을 읽지 못해 기본 규칙만 썼어요: {e}")]),
+    None => (base, Vec::new()),
+  };
+  Ok(LoadedPack { pack, warnings })
+}
+
+/* -------------------- rule evaluation -------------------- */
+
+fn any_eq(list: &[String], v: &str) -> bool {
+  let v = v.trim();
+  list.iter().any(|x| x.trim() == v)
+}
+
+/// 기록 단위 조건 검사(keywordsAny/minSensitivity/storeTypesAny/placesAny/actorTypesAny/hasRelated=true/require*Other)
+fn record_matches(w: &RuleWhen, r: &RecordItem) -> bool {
+  if let Some(kws) = &w.keywords_any {
+    let s = norm(&r.summary);
+    let hit = kws.iter().map(|k| norm(k)).any(|k| !k.is_empty() && s.contains(&k));
+    if !hit {
+      return false;
+    }
+  }
+
+  if let Some(min) = &w.min_sensitivity {
+    match (lv_rank(&r.lv), lv_rank(min)) {
+      (Some(have), Some(need)) if have >= need => {}
+      _ => return false,
+    }
+  }
+
+  if let Some(list) = &w.store_types_any {
+    if !any_eq(list, &r.store_type) {
+      return false;
+    }
+  }
+
+  if let Some(list) = &w.places_any {
+    if !any_eq(list, &r.place) {
+      return false;
+    }
+  }
+
+  if let Some(list) = &w.actor_types_any {
+    let any_actor = std::iter::once(&r.actor)
+      .chain(r.related.iter())
+      .any(|a| any_eq(list, &a.r#type));
+    if !any_actor {
+      return false;
+    }
+  }
+
+  if w.has_related == Some(true) && !r.related.iter().any(|a| !a.name.trim().is_empty()) {
+    return false;
+  }
+
+  if w.require_place_other == Some(true) && !r.place_other.trim().is_empty() {
+    return false;
+  }
+
+  if w.require_store_other == Some(true) && !r.store_other.trim().is_empty() {
+    return false;
+  }
+
+  true
+}
+
+/// 케이스 단위 조건까지 통과하면 매칭 기록 목록을 돌려준다(입력 순서 유지)
+pub fn matching_records<'a>(w: &RuleWhen, records: &[&'a RecordItem]) -> Option<Vec<&'a RecordItem>> {
+  let matched: Vec<&RecordItem> = records.iter().copied().filter(|r| record_matches(w, r)).collect();
+
+  let need = w.record_count_at_least.unwrap_or(1).max(1) as usize;
+  if matched.len() < need {
+    return None;
+  }
+
+  if let Some(all) = &w.store_types_all {
+    let have: HashSet<&str> = matched.iter().map(|r| r.store_type.trim()).collect();
+    if !all.iter().all(|s| have.contains(s.trim())) {
+      return None;
+    }
+  }
+
+  if w.has_related == Some(false) && records.iter().any(|r| r.related.iter().any(|a| !a.name.trim().is_empty())) {
+    return None;
+  }
+
+  Some(matched)
+}
+
+/// 케이스에 포함된 기록만 추림(recordIds 기준)
+pub fn case_records<'a>(case_item: &CaseItem, records: &'a [RecordItem]) -> Vec<&'a RecordItem> {
+  let ids: HashSet<&str> = case_item.record_ids.iter().map(|s| s.as_str()).collect();
+  records.iter().filter(|r| ids.contains(r.id.as_str())).collect()
+}
+
+/// priority 내림차순, 같으면 id 오름차순
+fn sorted_rules(pack: &AdvisorPack) -> Vec<&AdvisorRule> {
+  let mut rules: Vec<&AdvisorRule> = pack.rules.iter().collect();
+  rules.sort_by(|a, b| match b.priority.cmp(&a.priority) {
+    Ordering::Equal => a.id.cmp(&b.id),
+    other => other,
+  });
+  rules
+}
+
+/* -------------------- explanation (왜 이 권고가 떴나) -------------------- */
+
+/// when 조건 하나가 어떤 값/기록으로 만족됐는지
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionHit {
+  /// when 키 이름(keywordsAny, minSensitivity, ...)
+  pub key: String,
+  /// 조건을 만족시킨 값(키워드/유형/장소/민감도 등)
+  #[serde(default)]
+  pub values: Vec<String>,
+  #[serde(default)]
+  pub record_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdvisorExplain {
+  #[serde(default)]
+  pub matched_record_ids: Vec<String>,
+  pub anchor_type: String,
+  #[serde(default)]
+  pub anchor_record_id: Option<String>,
+  #[serde(default)]
+  pub conditions: Vec<ConditionHit>,
+}
+
+fn push_distinct(out: &mut Vec<String>, v: &str) {
+  let v = v.trim();
+  if !v.is_empty() && !out.iter().any(|x| x == v) {
+    out.push(v.to_string());
+  }
+}
+
+fn ids_of(records: &[&RecordItem]) -> Vec<String> {
+  records.iter().map(|r| r.id.clone()).collect()
+}
+
+/// 매칭 기록을 기준으로 조건별 근거와 사람이 읽는 reasons를 만든다
+fn explain_match(
+  rule: &AdvisorRule,
+  matched: &[&RecordItem],
+  anchor_record: Option<&RecordItem>,
+) -> (AdvisorExplain, Vec<String>) {
+  let w = &rule.when;
+  let mut conditions: Vec<ConditionHit> = Vec::new();
+  let mut reasons: Vec<String> = Vec::new();
+
+  if let Some(kws) = &w.keywords_any {
+    let mut hit = ConditionHit {
+      key: "keywordsAny".into(),
+      ..Default::default()
+    };
+    for k in kws {
+      let nk = norm(k);
+      if nk.is_empty() {
+        continue;
+      }
+      let ids: Vec<&RecordItem> = matched.iter().copied().filter(|r| norm(&r.summary).contains(&nk)).collect();
+      if ids.is_empty() {
+        continue;
+      }
+      push_distinct(&mut hit.values, k);
+      for r in ids {
+        push_distinct(&mut hit.record_ids, &r.id);
+      }
+    }
+    reasons.push(format!("키워드 일치: {} ({}건)", hit.values.join(", "), hit.record_ids.len()));
+    conditions.push(hit);
+  }
+
+  if let Some(min) = &w.min_sensitivity {
+    let mut hit = ConditionHit {
+      key: "minSensitivity".into(),
+      record_ids: ids_of(matched),
+      ..Default::default()
+    };
+    for r in matched {
+      push_distinct(&mut hit.values, &r.lv);
+    }
+    let top = matched.iter().filter_map(|r| lv_rank(&r.lv)).max().unwrap_or(0);
+    reasons.push(format!("민감도 LV{} ≥ 기준 {} ({}건)", top, min.trim(), matched.len()));
+    conditions.push(hit);
+  }
+
+  if w.store_types_any.is_some() {
+    let mut hit = ConditionHit {
+      key: "storeTypesAny".into(),
+      record_ids: ids_of(matched),
+      ..Default::default()
+    };
+    for r in matched {
+      push_distinct(&mut hit.values, &r.store_type);
+    }
+    reasons.push(format!("기록 유형: {}", hit.values.join(", ")));
+    conditions.push(hit);
+  }
+
+  if let Some(all) = &w.store_types_all {
+    let mut hit = ConditionHit {
+      key: "storeTypesAll".into(),
+      ..Default::default()
+    };
+    for st in all {
+      if let Some(r) = matched.iter().find(|r| r.store_type.trim() == st.trim()) {
+        push_distinct(&mut hit.values, st);
+        push_distinct(&mut hit.record_ids, &r.id);
+      }
+    }
+    reasons.push(format!("기록 유형 모두 포함: {}", hit.values.join(", ")));
+    conditions.push(hit);
+  }
+
+  if w.places_any.is_some() {
+    let mut hit = ConditionHit {
+      key: "placesAny".into(),
+      record_ids: ids_of(matched),
+      ..Default::default()
+    };
+    for r in matched {
+      push_distinct(&mut hit.values, &r.place);
+    }
+    reasons.push(format!("장소: {}", hit.values.join(", ")));
+    conditions.push(hit);
+  }
+
+  if let Some(list) = &w.actor_types_any {
+    let mut hit = ConditionHit {
+      key: "actorTypesAny".into(),
+      record_ids: ids_of(matched),
+      ..Default::default()
+    };
+    for a in matched.iter().flat_map(|r| std::iter::once(&r.actor).chain(r.related.iter())) {
+      if any_eq(list, &a.r#type) {
+        push_distinct(&mut hit.values, &a.r#type);
+      }
+    }
+    reasons.push(format!("관계자 유형: {}", hit.values.join(", ")));
+    conditions.push(hit);
+  }
+
+  if let Some(has) = w.has_related {
+    let with_related: Vec<&RecordItem> = matched
+      .iter()
+      .copied()
+      .filter(|r| r.related.iter().any(|a| !a.name.trim().is_empty()))
+      .collect();
+    conditions.push(ConditionHit {
+      key: "hasRelated".into(),
+      values: vec![has.to_string()],
+      record_ids: ids_of(&with_related),
+    });
+    reasons.push(if has {
+      format!("관련자 있음 ({}건)", with_related.len())
+    } else {
+      "관련자 없음".into()
+    });
+  }
+
+  if w.require_place_other == Some(true) {
+    conditions.push(ConditionHit {
+      key: "requirePlaceOther".into(),
+      values: vec![],
+      record_ids: ids_of(matched),
+    });
+    reasons.push(format!("상세 장소(placeOther) 미입력 {}건", matched.len()));
+  }
+
+  if w.require_store_other == Some(true) {
+    conditions.push(ConditionHit {
+      key: "requireStoreOther".into(),
+      values: vec![],
+      record_ids: ids_of(matched),
+    });
+    reasons.push(format!("채널 단서(storeOther) 미입력 {}건", matched.len()));
+  }
+
+  if let Some(n) = w.record_count_at_least {
+    conditions.push(ConditionHit {
+      key: "recordCountAtLeast".into(),
+      values: vec![matched.len().to_string()],
+      record_ids: ids_of(matched),
+    });
+    reasons.push(format!("매칭 기록 {}건 ≥ {}건", matched.len(), n.max(1)));
+  }
+
+  let explain = AdvisorExplain {
+    matched_record_ids: ids_of(matched),
+    anchor_type: rule.anchor.r#type.clone(),
+    anchor_record_id: anchor_record.map(|r| r.id.clone()),
+    conditions,
+  };
+  (explain, reasons)
+}
+
+/* -------------------- anchor → ts -------------------- */
+
+fn fmt_iso(t: DateTime<Utc>) -> String {
+  t.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn anchor_match_ok(m: &AnchorMatch, r: &RecordItem) -> bool {
+  if let Some(st) = &m.store_type {
+    if r.store_type.trim() != st.trim() {
+      return false;
+    }
+  }
+  if let Some(pl) = &m.place {
+    if r.place.trim() != pl.trim() {
+      return false;
+    }
+  }
+  true
+}
+
+fn earliest<'a, I: IntoIterator<Item = &'a RecordItem>>(it: I) -> Option<(DateTime<Utc>, &'a RecordItem)> {
+  it.into_iter().filter_map(|r| parse_ts(&r.ts).map(|t| (t, r))).min_by_key(|(t, _)| *t)
+}
+
+fn latest<'a, I: IntoIterator<Item = &'a RecordItem>>(it: I) -> Option<(DateTime<Utc>, &'a RecordItem)> {
+  it.into_iter().filter_map(|r| parse_ts(&r.ts).map(|t| (t, r))).max_by_key(|(t, _)| *t)
+}
+
+/// 규칙 anchor 기준 시각 + offsetMinutes, 그리고 기준이 된 기록(case_created면 None).
+/// 기준을 못 구하면 first_record → case_created → 가장 이른 기록 순으로 물러선다.
+/// 현재 시각은 쓰지 않는다(실행할 때마다 기한/id가 바뀌지 않게). 어느 것도 없으면 None.
+fn anchor_time<'a>(
+  anchor: &RuleAnchor,
+  case_item: &CaseItem,
+  scoped: &[&'a RecordItem],
+  matched: &[&'a RecordItem],
+) -> Option<(DateTime<Utc>, Option<&'a RecordItem>)> {
+  let first_record = earliest(scoped.iter().copied()).or_else(|| earliest(matched.iter().copied()));
+
+  let base = match anchor.r#type.as_str() {
+    "first_record" => first_record,
+    "last_record" => latest(scoped.iter().copied()),
+    "after_first_match" => {
+      let by_match = anchor
+        .r#match
+        .as_ref()
+        .and_then(|m| earliest(matched.iter().copied().filter(|r| anchor_match_ok(m, r))));
+      by_match.or_else(|| earliest(matched.iter().copied())).or(first_record)
+    }
+    _ => None,
+  };
+
+  let offset = Duration::minutes(anchor.offset_minutes);
+  match base {
+    Some((t, r)) => Some((t + offset, Some(r))),
+    None => parse_ts(&case_item.created_at)
+      .or_else(|| first_record.map(|(t, _)| t))
+      .map(|t| (t + offset, None)),
+  }
+}
+
+/* -------------------- core: advise -------------------- */
+
+pub const ADVISOR_STATE_ACTIVE: &str = "active";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdvisorItem {
+  pub id: String,
+  pub ts: String,
+  pub title: String,
+  pub body: String,
+  pub level: String,
+  #[serde(default)]
+  pub tags: Vec<String>,
+  pub state: String,
+  pub rule_id: Option<String>,
+  /// 재생성 시 규칙이 더 이상 맞지 않는 권고(사용자 state는 그대로 둔다)
+  #[serde(default)]
+  pub stale: bool,
+  /// 발동 근거(사람이 읽는 요약) — RankedHit.reasons와 같은 용도
+  #[serde(default)]
+  pub reasons: Vec<String>,
+  #[serde(default)]
+  pub explain: Option<AdvisorExplain>,
+}
+
+/// FNV-1a 64bit: 빌드/플랫폼이 바뀌어도 같은 입력이면 같은 값
+pub(crate) fn fnv1a64(parts: &[&str]) -> u64 {
+  let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+  for p in parts {
+    for b in p.as_bytes().iter().chain(std::iter::once(&0x1f)) {
+      h ^= *b as u64;
+      h = h.wrapping_mul(0x0100_0000_01b3);
+    }
+  }
+  h
+}
+
+/// (케이스 id, 규칙 id)에서 결정되는 권고 id. 규칙 하나는 케이스마다 권고 하나라,
+/// 기준 기록(last_record 등)이 바뀌어도 같은 권고로 남아 done/dismissed가 유지된다. 기준 기록은 explain에.
+pub fn advisor_id(case_id: &str, rule_id: &str) -> String {
+  format!("ADV_{:016x}", fnv1a64(&[case_id, rule_id]))
+}
+
+pub fn generate_advisors_for_case(case_item: &CaseItem, records: &[RecordItem], pack: &AdvisorPack) -> Vec<AdvisorItem> {
+  let scoped = case_records(case_item, records);
+
+  let mut out: Vec<AdvisorItem> = Vec::new();
+  for rule in sorted_rules(pack) {
+    let Some(matched) = matching_records(&rule.when, &scoped) else {
+      continue;
+    };
+
+    // 기준 시각을 하나도 못 구하면(만든 시각도 기록 시각도 없음) 기한을 지어내지 않고 건너뛴다
+    let Some((ts, anchor_record)) = anchor_time(&rule.anchor, case_item, &scoped, &matched) else {
+      continue;
+    };
+    let (explain, reasons) = explain_match(rule, &matched, anchor_record);
+
+    out.push(AdvisorItem {
+      id: advisor_id(&case_item.id, &rule.id),
+      ts: fmt_iso(ts),
+      title: rule.title.clone(),
+      body: rule.body.join("\n"),
+      level: rule.level.clone(),
+      tags: rule.tags.clone(),
+      state: ADVISOR_STATE_ACTIVE.into(),
+      rule_id: Some(rule.id.clone()),
+      stale: false,
+      reasons,
+      explain: Some(explain),
+    });
+  }
+
+  out
+}
+
+/// 기존 권고(case_item.advisors)의 state를 같은 id에 이어 붙이고,
+/// 더 이상 발동하지 않는 권고는 버리지 않고 stale로 표시해 뒤에 남긴다.
+pub fn regenerate_advisors_for_case(case_item: &CaseItem, records: &[RecordItem], pack: &AdvisorPack) -> Vec<AdvisorItem> {
+  let mut out = generate_advisors_for_case(case_item, records, pack);
+
+  let mut carried: HashSet<usize> = HashSet::new();
+  for a in out.iter_mut() {
+    if let Some(i) = case_item.advisors.iter().position(|p| p.id == a.id) {
+      a.state = case_item.advisors[i].state.clone();
+      carried.insert(i);
+    }
+  }
+
+  for (i, prev) in case_item.advisors.iter().enumerate() {
+    if carried.contains(&i) {
+      continue;
+    }
+    let mut kept = prev.clone();
+    kept.stale = true;
+    out.push(kept);
+  }
+
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::test_util::{self, record};
+  use serde_json::json;
+
+  fn rec(id: &str, ts: &str, summary: &str) -> RecordItem {
+    record(id, summary, json!({"ts": ts}))
+  }
+
+  fn case(created_at: &str, record_ids: &[&str]) -> CaseItem {
+    test_util::case("CASE_1", json!({"createdAt": created_at, "recordIds": record_ids}))
+  }
+
+  fn pack(anchor: &str) -> AdvisorPack {
+    serde_json::from_value(json!({
+      "version": 1,
+      "rules": [{
+        "id": "R1", "level": "info", "title": "폭언", "body": [],
+        "when": {"keywordsAny": ["폭언"]},
+        "anchor": {"type": anchor, "offsetMinutes": 60},
+      }],
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn case_created_without_created_at_uses_earliest_record() {
+    let records = vec![rec("A", "2025-03-02T09:00:00+09:00", "폭언"), rec("B", "2025-03-01T09:00:00+09:00", "폭언")];
+    let c = case("", &["A", "B"]);
+    let a = generate_advisors_for_case(&c, &records, &pack("case_created"));
+    let b = generate_advisors_for_case(&c, &records, &pack("case_created"));
+    assert_eq!(a.len(), 1);
+    // 2025-03-01 09:00 KST + 60분
+    assert_eq!(a[0].ts, "2025-03-01T01:00:00.000Z");
+    assert_eq!(a[0].ts, b[0].ts);
+    assert_eq!(a[0].id, b[0].id);
+  }
+
+  #[test]
+  fn last_record_anchor_keeps_id_and_state() {
+    let mut records = vec![rec("A", "2025-03-01T09:00:00+09:00", "폭언")];
+    let mut c = case("2025-03-01T08:00:00+09:00", &["A"]);
+    let p = pack("last_record");
+    let mut first = generate_advisors_for_case(&c, &records, &p);
+    first[0].state = "done".into();
+    c.advisors = first.clone();
+
+    records.push(rec("B", "2025-03-05T09:00:00+09:00", "폭언"));
+    c.record_ids.push("B".into());
+    let next = regenerate_advisors_for_case(&c, &records, &p);
+    assert_eq!(next.len(), 1, "no stale duplicate");
+    assert_eq!(next[0].id, first[0].id);
+    assert_eq!(next[0].state, "done");
+    let ex = next[0].explain.as_ref().unwrap();
+    assert_eq!(ex.anchor_record_id.as_deref(), Some("B"));
+  }
+
+  #[test]
+  fn corrupt_overlay_falls_back_to_base_pack() {
+    let dir = std::env::temp_dir().join(format!("adv_overlay_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(super::super::advisor_custom::ADVISOR_CUSTOM_FILE), "{ not json").unwrap();
+    let loaded = load_pack(Some(&dir)).unwrap();
+    assert_eq!(loaded.pack.rules.len(), builtin_pack().rules.len());
+    assert_eq!(loaded.warnings.len(), 1);
+    assert!(load_pack(None).unwrap().warnings.is_empty());
+    std::fs::remove_dir_all(&dir).ok();
+  }
+
+  #[test]
+  fn no_anchor_time_skips_rule() {
+    let records = vec![rec("A", "not a time", "폭언")];
+    let c = case("", &["A"]);
+    assert!(generate_advisors_for_case(&c, &records, &pack("case_created")).is_empty());
+  }
+}
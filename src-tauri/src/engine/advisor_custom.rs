@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::advisor::{AdvisorPack, AdvisorRule};
+use super::advisor_lint::lint_rule_report;
+use super::fsutil::write_atomic;
+
+/* -------------------- overlay file (AppDataDir) -------------------- */
+
+/// 학교/교사별 사용자 규칙. 내장(또는 AppDataDir advisor.json) 팩 위에 덮어쓴다.
+pub const ADVISOR_CUSTOM_FILE: &str = "advisor_custom.json";
+
+/// 병합 규칙:
+/// 1) `disabled`에 있는 id는 기본 팩/사용자 규칙 모두에서 빠진다.
+/// 2) 사용자 규칙이 기본 팩과 같은 id면 사용자 규칙이 이긴다(덮어쓰기).
+/// 3) 나머지 사용자 규칙은 추가된다.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdvisorOverlay {
+  #[serde(default = "overlay_version")]
+  pub version: u32,
+  #[serde(default)]
+  pub rules: Vec<AdvisorRule>,
+  #[serde(default)]
+  pub disabled: Vec<String>,
+}
+
+fn overlay_version() -> u32 {
+  1
+}
+
+impl Default for AdvisorOverlay {
+  fn default() -> Self {
+    Self {
+      version: overlay_version(),
+      rules: Vec::new(),
+      disabled: Vec::new(),
+    }
+  }
+}
+
+pub fn load_overlay(app_data_dir: &Path) -> Result<AdvisorOverlay, String> {
+  let path = app_data_dir.join(ADVISOR_CUSTOM_FILE);
+  if !path.exists() {
+    return Ok(AdvisorOverlay::default());
+  }
+  let text = std::fs::read_to_string(&path).map_err(|e| format!("custom rules read failed: {e}"))?;
+  serde_json::from_str(&text).map_err(|e| format!("custom rules parse failed ({}): {e}", path.display()))
+}
+
+pub fn save_overlay(app_data_dir: &Path, overlay: &AdvisorOverlay) -> Result<(), String> {
+  let text = serde_json::to_string_pretty(overlay).map_err(|e| format!("custom rules encode failed: {e}"))?;
+  write_atomic(&app_data_dir.join(ADVISOR_CUSTOM_FILE), text.as_bytes())
+    .map_err(|e| format!("custom rules write failed: {e}"))
+}
+
+pub fn merge_overlay(base: &AdvisorPack, overlay: &AdvisorOverlay) -> AdvisorPack {
+  let disabled: HashSet<&str> = overlay.disabled.iter().map(|s| s.as_str()).collect();
+  let custom_ids: HashSet<&str> = overlay.rules.iter().map(|r| r.id.as_str()).collect();
+
+  let mut rules: Vec<AdvisorRule> = base
+    .rules
+    .iter()
+    .filter(|r| !disabled.contains(r.id.as_str()) && !custom_ids.contains(r.id.as_str()))
+    .cloned()
+    .collect();
+
+  rules.extend(overlay.rules.iter().filter(|r| !disabled.contains(r.id.as_str())).cloned());
+
+  AdvisorPack {
+    version: base.version,
+    rules,
+  }
+}
+
+/* -------------------- edit ops -------------------- */
+
+fn validate(rule: &AdvisorRule) -> Result<(), String> {
+  let rep = lint_rule_report(rule);
+  if rep.is_ok() {
+    return Ok(());
+  }
+  let msgs: Vec<String> = rep
+    .issues
+    .iter()
+    .filter(|i| i.severity == "error")
+    .map(|i| format!("{}: {}", i.path, i.message))
+    .collect();
+  Err(format!("invalid rule: {}", msgs.join("; ")))
+}
+
+pub fn add_rule(overlay: &mut AdvisorOverlay, rule: AdvisorRule) -> Result<(), String> {
+  validate(&rule)?;
+  if overlay.rules.iter().any(|r| r.id == rule.id) {
+    return Err(format!("custom rule already exists: {}", rule.id));
+  }
+  overlay.rules.push(rule);
+  Ok(())
+}
+
+pub fn update_rule(overlay: &mut AdvisorOverlay, rule: AdvisorRule) -> Result<(), String> {
+  validate(&rule)?;
+  let slot = overlay
+    .rules
+    .iter_mut()
+    .find(|r| r.id == rule.id)
+    .ok_or_else(|| format!("custom rule not found: {}", rule.id))?;
+  *slot = rule;
+  Ok(())
+}
+
+pub fn delete_rule(overlay: &mut AdvisorOverlay, rule_id: &str) -> Result<(), String> {
+  let before = overlay.rules.len();
+  overlay.rules.retain(|r| r.id != rule_id);
+  if overlay.rules.len() == before {
+    return Err(format!("custom rule not found: {rule_id}"));
+  }
+  Ok(())
+}
+
+/// 기본 팩/사용자 규칙 어느 쪽 id든 끄고 켤 수 있다
+pub fn set_disabled(overlay: &mut AdvisorOverlay, rule_id: &str, disabled: bool) {
+  overlay.disabled.retain(|id| id != rule_id);
+  if disabled {
+    overlay.disabled.push(rule_id.to_string());
+  }
+}
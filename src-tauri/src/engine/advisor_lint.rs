@@ -0,0 +1,428 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+use super::advisor::{AdvisorRule, ANCHOR_TYPES, LEVELS};
+use super::lv_rank;
+
+/* -------------------- vocab (src/main/state.ts / src/engine.ts와 동일하게 유지) -------------------- */
+
+pub const KNOWN_STORE_TYPES: &[&str] = &[
+  "녹취록", "통화녹취", "음성녹음", "문서", "공문", "가정통신문", "회의록", "상담록", "상담일지", "지도일지",
+  "교무수첩", "업무일지", "학급일지", "전화", "문자", "업무메신저", "이메일", "사진", "영상", "CCTV", "진술서",
+  "방문상담", "공식채널", "기타",
+];
+
+pub const KNOWN_PLACES: &[&str] = &[
+  "교실", "복도", "급식실", "보건실", "교외", "교무실", "운동장", "상담실", "체육관", "도서관", "행정실",
+  "생활지도실", "특별실", "온라인", "기타",
+];
+
+pub const KNOWN_ACTOR_TYPES: &[&str] = &["관리자", "학부모", "학생", "동료교사", "외부인", "기타"];
+
+const RULE_KEYS: &[&str] = &["id", "priority", "level", "tags", "title", "body", "when", "anchor"];
+const WHEN_KEYS: &[&str] = &[
+  "keywordsAny",
+  "minSensitivity",
+  "storeTypesAny",
+  "storeTypesAll",
+  "placesAny",
+  "actorTypesAny",
+  "hasRelated",
+  "recordCountAtLeast",
+  "requirePlaceOther",
+  "requireStoreOther",
+];
+const ANCHOR_KEYS: &[&str] = &["type", "offsetMinutes", "match"];
+const MATCH_KEYS: &[&str] = &["storeType", "place"];
+
+/* -------------------- report -------------------- */
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintIssue {
+  /// "error" | "warn"
+  pub severity: String,
+  pub code: String,
+  /// 예: rules[12].when.minSensitivity
+  pub path: String,
+  pub rule_id: Option<String>,
+  pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LintReport {
+  pub version: Option<u64>,
+  pub rule_count: u32,
+  pub error_count: u32,
+  pub warn_count: u32,
+  pub issues: Vec<LintIssue>,
+}
+
+impl LintReport {
+  fn push(&mut self, severity: &str, code: &str, path: String, rule_id: Option<&str>, message: String) {
+    if severity == "error" {
+      self.error_count += 1;
+    } else {
+      self.warn_count += 1;
+    }
+    self.issues.push(LintIssue {
+      severity: severity.into(),
+      code: code.into(),
+      path,
+      rule_id: rule_id.map(|s| s.to_string()),
+      message,
+    });
+  }
+
+  pub fn is_ok(&self) -> bool {
+    self.error_count == 0
+  }
+}
+
+/* -------------------- lint -------------------- */
+
+fn check_keys(rep: &mut LintReport, obj: &Value, allowed: &[&str], path: &str, rule_id: Option<&str>) {
+  let Some(map) = obj.as_object() else {
+    return;
+  };
+  for k in map.keys() {
+    if !allowed.contains(&k.as_str()) {
+      rep.push("error", "unknown_key", format!("{path}.{k}"), rule_id, format!("알 수 없는 키 '{k}'"));
+    }
+  }
+}
+
+fn check_vocab(
+  rep: &mut LintReport,
+  list: &[String],
+  vocab: &[&str],
+  code: &str,
+  path: &str,
+  rule_id: Option<&str>,
+) {
+  for v in list {
+    if !vocab.contains(&v.trim()) {
+      rep.push("warn", code, path.to_string(), rule_id, format!("알려진 값이 아님: '{v}'"));
+    }
+  }
+}
+
+fn check_empty_list(rep: &mut LintReport, list: &Option<Vec<String>>, path: &str, rule_id: Option<&str>) {
+  if matches!(list, Some(v) if v.iter().all(|s| s.trim().is_empty())) {
+    rep.push("error", "never_fires", path.to_string(), rule_id, "빈 목록이라 이 규칙은 발동할 수 없음".into());
+  }
+}
+
+/// 타입이 맞게 파싱된 규칙에 대한 의미 검사
+fn lint_rule(rep: &mut LintReport, rule: &AdvisorRule, at: &str) {
+  let rid = Some(rule.id.as_str());
+  let w = &rule.when;
+
+  if rule.id.trim().is_empty() {
+    rep.push("error", "empty_id", format!("{at}.id"), None, "id가 비어 있음".into());
+  }
+  if rule.title.trim().is_empty() {
+    rep.push("error", "empty_title", format!("{at}.title"), rid, "title이 비어 있음".into());
+  }
+  if rule.body.iter().all(|s| s.trim().is_empty()) {
+    rep.push("warn", "empty_body", format!("{at}.body"), rid, "body가 비어 있음".into());
+  }
+  if !LEVELS.contains(&rule.level.as_str()) {
+    rep.push(
+      "error",
+      "invalid_level",
+      format!("{at}.level"),
+      rid,
+      format!("level은 {} 중 하나여야 함: '{}'", LEVELS.join("/"), rule.level),
+    );
+  }
+
+  if let Some(min) = &w.min_sensitivity {
+    if lv_rank(min).is_none() {
+      rep.push(
+        "error",
+        "invalid_sensitivity",
+        format!("{at}.when.minSensitivity"),
+        rid,
+        format!("minSensitivity는 LV1~LV5여야 함: '{min}'"),
+      );
+    }
+  }
+
+  if !ANCHOR_TYPES.contains(&rule.anchor.r#type.as_str()) {
+    rep.push(
+      "error",
+      "unknown_anchor",
+      format!("{at}.anchor.type"),
+      rid,
+      format!("anchor.type은 {} 중 하나여야 함: '{}'", ANCHOR_TYPES.join("/"), rule.anchor.r#type),
+    );
+  }
+
+  // vocab
+  if let Some(v) = &w.store_types_any {
+    check_vocab(rep, v, KNOWN_STORE_TYPES, "unknown_store_type", &format!("{at}.when.storeTypesAny"), rid);
+  }
+  if let Some(v) = &w.store_types_all {
+    check_vocab(rep, v, KNOWN_STORE_TYPES, "unknown_store_type", &format!("{at}.when.storeTypesAll"), rid);
+  }
+  if let Some(v) = &w.places_any {
+    check_vocab(rep, v, KNOWN_PLACES, "unknown_place", &format!("{at}.when.placesAny"), rid);
+  }
+  if let Some(v) = &w.actor_types_any {
+    check_vocab(rep, v, KNOWN_ACTOR_TYPES, "unknown_actor_type", &format!("{at}.when.actorTypesAny"), rid);
+  }
+  if let Some(m) = &rule.anchor.r#match {
+    if let Some(st) = &m.store_type {
+      check_vocab(
+        rep,
+        std::slice::from_ref(st),
+        KNOWN_STORE_TYPES,
+        "unknown_store_type",
+        &format!("{at}.anchor.match.storeType"),
+        rid,
+      );
+    }
+    if let Some(pl) = &m.place {
+      check_vocab(rep, std::slice::from_ref(pl), KNOWN_PLACES, "unknown_place", &format!("{at}.anchor.match.place"), rid);
+    }
+  }
+
+  // 발동 불가
+  check_empty_list(rep, &w.keywords_any, &format!("{at}.when.keywordsAny"), rid);
+  check_empty_list(rep, &w.store_types_any, &format!("{at}.when.storeTypesAny"), rid);
+  check_empty_list(rep, &w.places_any, &format!("{at}.when.placesAny"), rid);
+  check_empty_list(rep, &w.actor_types_any, &format!("{at}.when.actorTypesAny"), rid);
+
+  if let (Some(any), Some(all)) = (&w.store_types_any, &w.store_types_all) {
+    let any: HashSet<&str> = any.iter().map(|s| s.trim()).collect();
+    for s in all {
+      if !any.contains(s.trim()) {
+        rep.push(
+          "error",
+          "never_fires",
+          format!("{at}.when.storeTypesAll"),
+          rid,
+          format!("storeTypesAll의 '{s}'가 storeTypesAny에 없어 함께 만족할 수 없음"),
+        );
+      }
+    }
+  }
+
+  // anchor.match가 when과 모순이면 항상 대체 기준(first_record)으로 떨어짐
+  if let Some(m) = &rule.anchor.r#match {
+    if rule.anchor.r#type != "after_first_match" {
+      rep.push(
+        "warn",
+        "unused_match",
+        format!("{at}.anchor.match"),
+        rid,
+        "anchor.match는 after_first_match에서만 쓰임".into(),
+      );
+    }
+    if let (Some(st), Some(any)) = (&m.store_type, &w.store_types_any) {
+      if !any.iter().any(|s| s.trim() == st.trim()) {
+        rep.push(
+          "warn",
+          "anchor_match_unreachable",
+          format!("{at}.anchor.match.storeType"),
+          rid,
+          format!("'{st}'는 storeTypesAny에 없어 매칭 기록이 될 수 없음"),
+        );
+      }
+    }
+    if let (Some(pl), Some(any)) = (&m.place, &w.places_any) {
+      if !any.iter().any(|s| s.trim() == pl.trim()) {
+        rep.push(
+          "warn",
+          "anchor_match_unreachable",
+          format!("{at}.anchor.match.place"),
+          rid,
+          format!("'{pl}'는 placesAny에 없어 매칭 기록이 될 수 없음"),
+        );
+      }
+    }
+  }
+}
+
+/// 규칙 하나만 검사(사용자 규칙 추가/수정 시)
+pub fn lint_rule_report(rule: &AdvisorRule) -> LintReport {
+  let mut rep = LintReport {
+    rule_count: 1,
+    ..LintReport::default()
+  };
+  lint_rule(&mut rep, rule, "rule");
+  rep
+}
+
+/// advisor.json 텍스트 전체를 검사. 파싱이 깨져도 가능한 만큼 규칙별로 계속 본다.
+pub fn lint_pack_json(text: &str) -> LintReport {
+  let mut rep = LintReport::default();
+
+  let root: Value = match serde_json::from_str(text) {
+    Ok(v) => v,
+    Err(e) => {
+      rep.push("error", "parse", "$".into(), None, format!("JSON 파싱 실패 (line {}, column {}): {e}", e.line(), e.column()));
+      return rep;
+    }
+  };
+
+  rep.version = root.get("version").and_then(|v| v.as_u64());
+  if rep.version.is_none() {
+    rep.push("error", "missing_version", "version".into(), None, "정수 version이 필요함".into());
+  }
+
+  let Some(rules) = root.get("rules").and_then(|v| v.as_array()) else {
+    rep.push("error", "missing_rules", "rules".into(), None, "rules 배열이 필요함".into());
+    return rep;
+  };
+  rep.rule_count = rules.len() as u32;
+
+  let mut seen: HashMap<String, usize> = HashMap::new();
+
+  for (i, raw) in rules.iter().enumerate() {
+    let at = format!("rules[{i}]");
+    let raw_id = raw.get("id").and_then(|v| v.as_str());
+
+    check_keys(&mut rep, raw, RULE_KEYS, &at, raw_id);
+    if let Some(w) = raw.get("when") {
+      check_keys(&mut rep, w, WHEN_KEYS, &format!("{at}.when"), raw_id);
+    }
+    if let Some(a) = raw.get("anchor") {
+      check_keys(&mut rep, a, ANCHOR_KEYS, &format!("{at}.anchor"), raw_id);
+      if let Some(m) = a.get("match") {
+        check_keys(&mut rep, m, MATCH_KEYS, &format!("{at}.anchor.match"), raw_id);
+      }
+    }
+
+    if let Some(id) = raw_id {
+      if let Some(first) = seen.get(id) {
+        rep.push("error", "duplicate_id", format!("{at}.id"), Some(id), format!("id '{id}'가 rules[{first}]와 중복"));
+      } else {
+        seen.insert(id.to_string(), i);
+      }
+    }
+
+    match serde_json::from_value::<AdvisorRule>(raw.clone()) {
+      Ok(rule) => lint_rule(&mut rep, &rule, &at),
+      Err(e) => rep.push("error", "invalid_rule", at, raw_id, format!("규칙 형식 오류: {e}")),
+    }
+  }
+
+  rep
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::advisor::BUILTIN_PACK_JSON;
+  use serde_json::json;
+
+  fn rule(id: &str, when: Value, anchor: Value) -> Value {
+    json!({ "id": id, "level": "info", "title": "t", "body": ["b"], "when": when, "anchor": anchor })
+  }
+
+  fn lint(rules: Vec<Value>) -> LintReport {
+    lint_pack_json(&json!({ "version": 2, "rules": rules }).to_string())
+  }
+
+  fn codes(rep: &LintReport) -> Vec<(&str, &str)> {
+    rep.issues.iter().map(|i| (i.code.as_str(), i.path.as_str())).collect()
+  }
+
+  #[test]
+  fn builtin_pack_is_clean() {
+    let rep = lint_pack_json(BUILTIN_PACK_JSON);
+    assert!(rep.rule_count > 0);
+    assert_eq!(rep.error_count, 0, "{:?}", rep.issues);
+    assert!(!rep.issues.iter().any(|i| i.code == "unknown_place"), "{:?}", rep.issues);
+  }
+
+  #[test]
+  fn valid_rule_has_no_issues() {
+    let rep = lint(vec![rule("A", json!({ "keywordsAny": ["폭언"] }), json!({ "type": "case_created" }))]);
+    assert!(rep.is_ok());
+    assert!(rep.issues.is_empty(), "{:?}", rep.issues);
+  }
+
+  #[test]
+  fn reports_parse_errors_with_position() {
+    let rep = lint_pack_json("{\"version\": 2, \"rules\": [");
+    assert_eq!(codes(&rep), vec![("parse", "$")]);
+    assert!(rep.issues[0].message.contains("line 1"));
+  }
+
+  #[test]
+  fn reports_unknown_keys_at_every_level() {
+    let mut r = rule(
+      "A",
+      json!({ "keywordAny": ["x"] }),
+      json!({ "type": "after_first_match", "at": 1, "match": { "store": "문서" } }),
+    );
+    r["prio"] = json!(1);
+    let rep = lint(vec![r]);
+    let c = codes(&rep);
+    assert!(c.contains(&("unknown_key", "rules[0].prio")));
+    assert!(c.contains(&("unknown_key", "rules[0].when.keywordAny")));
+    assert!(c.contains(&("unknown_key", "rules[0].anchor.at")));
+    assert!(c.contains(&("unknown_key", "rules[0].anchor.match.store")));
+  }
+
+  #[test]
+  fn reports_duplicate_ids_against_the_first() {
+    let a = rule("A", json!({}), json!({ "type": "case_created" }));
+    let rep = lint(vec![a.clone(), rule("B", json!({}), json!({ "type": "case_created" })), a]);
+    assert_eq!(codes(&rep), vec![("duplicate_id", "rules[2].id")]);
+    assert!(rep.issues[0].message.contains("rules[0]"));
+    assert_eq!(rep.issues[0].rule_id.as_deref(), Some("A"));
+  }
+
+  #[test]
+  fn reports_invalid_level_sensitivity_and_anchor() {
+    let mut r = rule("A", json!({ "minSensitivity": "LV9" }), json!({ "type": "later" }));
+    r["level"] = json!("hot");
+    let rep = lint(vec![r]);
+    let c = codes(&rep);
+    assert!(c.contains(&("invalid_level", "rules[0].level")));
+    assert!(c.contains(&("invalid_sensitivity", "rules[0].when.minSensitivity")));
+    assert!(c.contains(&("unknown_anchor", "rules[0].anchor.type")));
+    assert_eq!(rep.error_count, 3);
+  }
+
+  #[test]
+  fn reports_rules_that_can_never_fire() {
+    let empty = rule("A", json!({ "placesAny": [] }), json!({ "type": "case_created" }));
+    let disjoint = rule(
+      "B",
+      json!({ "storeTypesAny": ["문서"], "storeTypesAll": ["문서", "사진"] }),
+      json!({ "type": "case_created" }),
+    );
+    let rep = lint(vec![empty, disjoint]);
+    assert_eq!(
+      codes(&rep),
+      vec![("never_fires", "rules[0].when.placesAny"), ("never_fires", "rules[1].when.storeTypesAll")]
+    );
+    assert!(rep.issues[1].message.contains("사진"));
+  }
+
+  #[test]
+  fn reports_anchor_matches_outside_the_when_filter() {
+    let r = rule(
+      "A",
+      json!({ "storeTypesAny": ["문서"], "placesAny": ["교실"] }),
+      json!({ "type": "after_first_match", "match": { "storeType": "사진", "place": "복도" } }),
+    );
+    let rep = lint(vec![r]);
+    assert_eq!(
+      codes(&rep),
+      vec![
+        ("anchor_match_unreachable", "rules[0].anchor.match.storeType"),
+        ("anchor_match_unreachable", "rules[0].anchor.match.place"),
+      ]
+    );
+    assert_eq!(rep.error_count, 0);
+    assert_eq!(rep.warn_count, 2);
+  }
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::tokenize::Tokenizer;
+use super::RecordItem;
+
+/* -------------------- corpus index (BM25) -------------------- */
+
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+
+/// 쿼리 토큰 하나의 BM25 구성(히트별로 RankedComponents에 실림)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bm25Term {
+  pub term: String,
+  pub idf: f32,
+  pub df: u32,
+  pub tf: u32,
+}
+
+/// 기록 요약 전체에 대한 역색인(토큰 → 기록 id) + 문서별 토큰.
+/// 쿼리 토큰과 같은 토큰만 등장으로 센다(어휘 전체를 훑지 않고 postings에서 바로 찾는다).
+#[derive(Debug, Clone, Default)]
+pub struct CorpusIndex {
+  tokenizer: String,
+  docs: HashMap<String, Vec<String>>,
+  postings: HashMap<String, HashSet<String>>,
+  total_len: usize,
+}
+
+impl CorpusIndex {
+  pub fn build(records: &[RecordItem], tokenizer: &dyn Tokenizer) -> Self {
+    let mut idx = CorpusIndex {
+      tokenizer: tokenizer.name().to_string(),
+      ..CorpusIndex::default()
+    };
+    for r in records {
+      idx.upsert(&r.id, &r.summary, tokenizer);
+    }
+    idx
+  }
+
+  /// 색인에 쓴 토크나이저 이름(랭킹 요청과 다르면 재사용하지 않는다)
+  pub fn tokenizer_name(&self) -> &str {
+    &self.tokenizer
+  }
+
+  pub fn upsert(&mut self, id: &str, summary: &str, tokenizer: &dyn Tokenizer) {
+    self.remove(id);
+    let toks = tokenizer.tokenize(summary);
+    for t in &toks {
+      self.postings.entry(t.clone()).or_default().insert(id.to_string());
+    }
+    self.total_len += toks.len();
+    self.docs.insert(id.to_string(), toks);
+  }
+
+  pub fn remove(&mut self, id: &str) {
+    let Some(old) = self.docs.remove(id) else {
+      return;
+    };
+    self.total_len -= old.len();
+    for t in &old {
+      if let Some(ids) = self.postings.get_mut(t) {
+        ids.remove(id);
+        if ids.is_empty() {
+          self.postings.remove(t);
+        }
+      }
+    }
+  }
+
+  pub fn doc_count(&self) -> usize {
+    self.docs.len()
+  }
+
+  fn avg_len(&self) -> f32 {
+    if self.docs.is_empty() {
+      return 1.0;
+    }
+    (self.total_len as f32 / self.docs.len() as f32).max(1.0)
+  }
+
+  fn tf(doc: &[String], term: &str) -> u32 {
+    doc.iter().filter(|t| t.as_str() == term).count() as u32
+  }
+
+  /// term이 나온 기록 수
+  pub fn df(&self, term: &str) -> u32 {
+    self.postings.get(term).map_or(0, |ids| ids.len() as u32)
+  }
+
+  /// BM25+ 스타일 idf: ln(1 + (N - df + 0.5) / (df + 0.5)) — 항상 양수
+  pub fn idf(&self, df: u32) -> f32 {
+    let n = self.docs.len() as f32;
+    let df = df as f32;
+    (1.0 + (n - df + 0.5) / (df + 0.5)).ln()
+  }
+
+  /// 쿼리 토큰별 (df, idf)를 한 번만 계산
+  pub fn query_stats(&self, q_tokens: &[String]) -> Vec<(u32, f32)> {
+    q_tokens
+      .iter()
+      .map(|t| {
+        let df = self.df(t);
+        (df, self.idf(df))
+      })
+      .collect()
+  }
+
+  /// (맞은 토큰 수, 정규화 점수 0~1, 토큰별 구성).
+  /// 정규화: 평균 길이 문서에 모든 토큰이 한 번씩 나온 점수(Σidf)로 나눔
+  pub fn score(&self, id: &str, q_tokens: &[String], q_stats: &[(u32, f32)]) -> (usize, f32, Vec<Bm25Term>) {
+    let empty: Vec<String> = Vec::new();
+    let doc = self.docs.get(id).unwrap_or(&empty);
+    let len_norm = 1.0 - B + B * (doc.len() as f32 / self.avg_len());
+
+    let mut hit = 0usize;
+    let mut raw = 0.0f32;
+    let mut idf_sum = 0.0f32;
+    let mut terms: Vec<Bm25Term> = Vec::new();
+
+    for (t, (df, idf)) in q_tokens.iter().zip(q_stats.iter()) {
+      let tf = Self::tf(doc, t);
+      idf_sum += idf;
+      if tf > 0 {
+        hit += 1;
+        let tf = tf as f32;
+        raw += idf * (tf * (K1 + 1.0)) / (tf + K1 * len_norm);
+      }
+      terms.push(Bm25Term {
+        term: t.clone(),
+        idf: *idf,
+        df: *df,
+        tf,
+      });
+    }
+
+    let sim = if idf_sum > 0.0 { (raw / idf_sum).min(1.0) } else { 0.0 };
+    (hit, sim, terms)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::test_util::record;
+  use crate::engine::tokenize::SimpleTokenizer;
+
+  fn rec(id: &str, summary: &str) -> RecordItem {
+    record(id, summary, serde_json::json!({}))
+  }
+
+  #[test]
+  fn df_and_tf_use_exact_tokens() {
+    let tk = SimpleTokenizer;
+    let idx = CorpusIndex::build(&[rec("a", "욕설 신고"), rec("b", "욕설 욕설"), rec("c", "욕설을 했다")], &tk);
+    assert_eq!(idx.df("욕설"), 2);
+    assert_eq!(idx.df("욕"), 0);
+    let q = vec!["욕설".to_string()];
+    let stats = idx.query_stats(&q);
+    let (hit, _, terms) = idx.score("b", &q, &stats);
+    assert_eq!((hit, terms[0].tf), (1, 2));
+    let (hit, _, _) = idx.score("c", &q, &stats);
+    assert_eq!(hit, 0);
+  }
+
+  #[test]
+  fn remove_drops_postings() {
+    let tk = SimpleTokenizer;
+    let mut idx = CorpusIndex::build(&[rec("a", "칼 소지"), rec("b", "칼 소지")], &tk);
+    idx.remove("a");
+    assert_eq!((idx.doc_count(), idx.df("소지")), (1, 1));
+    idx.remove("b");
+    assert_eq!(idx.df("소지"), 0);
+  }
+}
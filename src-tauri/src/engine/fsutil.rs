@@ -0,0 +1,50 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+/* -------------------- atomic file write (AppDataDir 설정 파일) -------------------- */
+
+/// 같은 폴더 임시 파일(이름.tmp)에 쓰고 fsync → rename → 폴더 fsync.
+/// 도중에 꺼져도 예전 파일이나 새 파일 중 하나는 온전히 남는다.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+  let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+  fs::create_dir_all(dir)?;
+
+  let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+  tmp_name.push(".tmp");
+  let tmp = dir.join(tmp_name);
+  {
+    let mut f = File::create(&tmp)?;
+    f.write_all(bytes)?;
+    f.sync_all()?;
+  }
+  fs::rename(&tmp, path)?;
+  sync_dir(dir);
+  Ok(())
+}
+
+/// 디렉터리 항목(rename 결과)까지 디스크에 반영. Windows는 디렉터리를 열 수 없어 생략.
+pub fn sync_dir(dir: &Path) {
+  #[cfg(unix)]
+  if let Ok(d) = File::open(dir) {
+    let _ = d.sync_all();
+  }
+  #[cfg(not(unix))]
+  let _ = dir;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn write_atomic_replaces_and_leaves_no_tmp() {
+    let dir = std::env::temp_dir().join(format!("fsutil_{}", std::process::id()));
+    let path = dir.join("a.json");
+    write_atomic(&path, b"one").unwrap();
+    write_atomic(&path, b"two").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "two");
+    assert!(!dir.join("a.json.tmp").exists());
+    fs::remove_dir_all(&dir).ok();
+  }
+}
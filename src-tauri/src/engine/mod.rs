@@ -1,7 +1,26 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
-use std::collections::HashSet;
-use std::time::{SystemTime, UNIX_EPOCH}; // 시간 처리를 위한 표준 라이브러리 추가
+use std::collections::{HashMap, HashSet};
+
+pub mod advisor;
+pub mod advisor_custom;
+pub mod advisor_lint;
+pub mod bm25;
+pub mod fsutil;
+pub mod network;
+pub mod query;
+pub mod similarity;
+pub mod store;
+pub mod suggest;
+pub mod synonym;
+#[cfg(test)]
+pub(crate) mod test_util;
+pub mod time;
+pub mod tokenize;
+
+pub use advisor::{generate_advisors_for_case, regenerate_advisors_for_case, AdvisorExplain, AdvisorItem, AdvisorPack};
 
 /* -------------------- tiny helpers -------------------- */
 
@@ -16,63 +35,40 @@ fn norm(s: &str) -> String {
     .join(" ")
 }
 
-fn within_range(ts: &str, from: &str, to: &str) -> bool {
-  if !from.is_empty() && ts < from {
-    return false;
-  }
-  if !to.is_empty() && ts > to {
-    return false;
-  }
-  true
+/// 한쪽 유형이 비어 있으면 유형은 따지지 않는다
+fn actor_type_compat(a: &ActorRef, b: &ActorRef) -> bool {
+  let (ta, tb) = (a.r#type.trim(), b.r#type.trim());
+  ta.is_empty() || tb.is_empty() || ta == tb
 }
 
-fn is_word_char(cp: u32) -> bool {
-  let is_ascii_num = cp >= 0x30 && cp <= 0x39;
-  let is_ascii_upper = cp >= 0x41 && cp <= 0x5A;
-  let is_ascii_lower = cp >= 0x61 && cp <= 0x7A;
-  let is_hangul_syllable = cp >= 0xAC00 && cp <= 0xD7A3;
-  let is_hangul_jamo1 = cp >= 0x3131 && cp <= 0x314E;
-  let is_hangul_jamo2 = cp >= 0x314F && cp <= 0x3163;
-  is_ascii_num || is_ascii_upper || is_ascii_lower || is_hangul_syllable || is_hangul_jamo1 || is_hangul_jamo2
+/// 사람 구분 키 "유형|이름"(이름이 비면 None)
+fn actor_key(a: &ActorRef) -> Option<String> {
+  let n = norm(&a.name);
+  (!n.is_empty()).then(|| format!("{}|{}", a.r#type.trim(), n))
 }
 
-fn tokenize(s: &str) -> Vec<String> {
-  let mut out: Vec<String> = Vec::new();
-  let mut cur = String::new();
-
-  for ch in s.chars() {
-    let cp = ch as u32;
-    if is_word_char(cp) {
-      cur.push(ch);
-    } else {
-      let t = norm(&cur);
-      if t.len() >= 2 {
-        out.push(t);
-      }
-      cur.clear();
-    }
-  }
-  let t = norm(&cur);
-  if t.len() >= 2 {
-    out.push(t);
+/// (유형, 이름)이 같은 당사자인지. 한쪽 유형이 비어 있으면 이름만 비교
+fn actor_eq(a: &ActorRef, b: &ActorRef) -> bool {
+  if !actor_type_compat(a, b) {
+    return false;
   }
-  out
+  let na = norm(&a.name);
+  !na.is_empty() && na == norm(&b.name)
 }
 
-fn text_similarity_stats(q_tokens: &[String], summary: &str) -> (usize, usize, f32) {
-  if q_tokens.is_empty() {
-    return (0, 0, 0.0);
-  }
-  let s = norm(summary);
-  let mut hit = 0usize;
-  for qt in q_tokens {
-    if qt.len() >= 2 && s.contains(qt) {
-      hit += 1;
-    }
+/// "LV1".."LV5" → 1..5 (그 외는 None)
+fn lv_rank(lv: &str) -> Option<u8> {
+  let t = lv.trim();
+  let n = t.strip_prefix("LV").or_else(|| t.strip_prefix("lv"))?;
+  match n.parse::<u8>() {
+    Ok(v) if (1..=5).contains(&v) => Some(v),
+    _ => None,
   }
-  let total = q_tokens.len();
-  let ratio = hit as f32 / total as f32;
-  (hit, total, ratio)
+}
+
+/// 기본 시간대(Asia/Seoul) 기준으로 타임스탬프 → UTC
+fn parse_ts(s: &str) -> Option<DateTime<Utc>> {
+  time::parse_ts_in(s, time::DEFAULT_TZ).ok()
 }
 
 /* -------------------- shared types (proto) -------------------- */
@@ -105,6 +101,9 @@ pub struct RecordItem {
   pub place: PlaceType,
   pub place_other: String,
   pub summary: String,
+  /// 마지막으로 고친 시각(v8부터, 예전 데이터는 빈 문자열). ts는 사건 시각이라 백업 합치기는 이것을 비교한다.
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub updated_at: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -126,6 +125,42 @@ pub struct CaseItem {
 
   #[serde(default)]
   pub actors: Vec<ActorRef>,
+
+  /// true면 주요 당사자(actors[0])가 주체인 기록만 랭킹
+  #[serde(default)]
+  pub only_main_actor: bool,
+
+  /// "any" | "LV1".."LV5" — 이 민감도 미만 기록은 랭킹에서 제외
+  #[serde(default)]
+  pub sens_filter: CaseSensFilter,
+
+  #[serde(default)]
+  pub created_at: String,
+
+  #[serde(default)]
+  pub record_ids: Vec<String>,
+
+  #[serde(default)]
+  pub advisors: Vec<AdvisorItem>,
+}
+
+/// 케이스 진행 단계(랭킹에는 쓰지 않고 저장/백업에서만 다룬다)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepItem {
+  pub id: String,
+  pub ts: String,
+  pub name: String,
+  #[serde(default)]
+  pub note: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub text: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub place: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub owner: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub lv: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -137,6 +172,22 @@ pub struct RankWeights {
   pub related: Option<f32>,
   #[serde(default)]
   pub text: Option<f32>,
+  /// 시간 근접도(기준 시점에서 멀어질수록 반감, 기본 0 = 끔). 켜면 점수에 더해져 minScore 판단에도 들어간다.
+  #[serde(default)]
+  pub time: Option<f32>,
+  /// 민감도 가산(LV5=1.0 × 가중치, 기본 0 = 끔)
+  #[serde(default)]
+  pub sens: Option<f32>,
+  /// 쿼리 토큰이 붙어/가까이 나올 때 가산(구 그대로면 1.0 × 가중치)
+  #[serde(default)]
+  pub phrase: Option<f32>,
+  /// 2차 관계: 케이스 당사자와 자주 함께 나오는 사람의 기록(3건 이상이면 1.0 × 가중치).
+  /// 다른 조건으로 후보가 된 기록의 점수만 올리고, 이것만으로는 후보에 넣지 않는다.
+  #[serde(default)]
+  pub second_degree: Option<f32>,
+  /// 당사자/관련자 점수에 곱하는 유형별 배수(예: {"학부모": 1.2, "관리자": 0.6}), 없는 유형은 1.0
+  #[serde(default)]
+  pub actor_types: Option<HashMap<String, f32>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -153,6 +204,26 @@ pub struct RankOpts {
 
   #[serde(default, alias = "minTextSim")]
   pub min_text_sim: Option<f32>,
+
+  /// 오프셋 없는 시각/날짜를 해석할 IANA 시간대(기본 Asia/Seoul)
+  #[serde(default, alias = "timeZone", alias = "tz")]
+  pub time_zone: Option<String>,
+
+  /// 쿼리 토크나이저: "korean"(기본, 조사/어미 제거) | "simple"
+  #[serde(default)]
+  pub tokenizer: Option<String>,
+
+  /// 키워드 유사도 방식: "token"(기본, 토큰 포함) | "ngram"(문자 2-gram) | "bm25"(코퍼스 idf)
+  #[serde(default, alias = "textMode")]
+  pub text_mode: Option<String>,
+
+  /// 시간 근접도 반감기(일). 기준 시점에서 이만큼 떨어지면 time 점수가 절반
+  #[serde(default, alias = "timeHalfLifeDays")]
+  pub time_half_life_days: Option<f32>,
+
+  /// 근접 가산 범위(두 토큰 사이 공백 제외 글자 수)
+  #[serde(default, alias = "proximityWindow")]
+  pub proximity_window: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -162,19 +233,51 @@ pub struct RankedComponents {
   pub text_sim: f32,
   pub q_hit: u32,
   pub q_total: u32,
+  /// 쿼리를 자른 토크나이저 이름
+  pub tokenizer: String,
+  /// text_sim 계산 방식(token | ngram | bm25)
+  pub text_mode: String,
+  /// bm25 모드일 때 쿼리 토큰별 idf/df/tf
+  pub bm25_terms: Vec<bm25::Bm25Term>,
+  /// 구/근접 가산(이웃 토큰이 붙어 있을수록 큼)
+  pub phrase_score: f32,
 
   pub actor_score: f32,
   pub actor_match: bool,
   pub is_main_actor: bool,
+  /// 당사자 점수에 곱한 유형 배수
+  pub actor_type_weight: f32,
 
   pub related_score: f32,
   pub related_hits: u32,
 
+  /// 2차 관계 가산(기록 당사자가 케이스 당사자와 다른 기록에서 함께 나온 횟수 기준)
+  pub second_degree_score: f32,
+  pub second_degree_cooc: u32,
+
   pub in_range: Option<bool>,
+  /// 기록 ts를 해석할 수 있었는지(false면 기간 판단 없이 포함하고 reasons에 표시)
+  pub ts_valid: bool,
+  /// UTC로 정규화한 기록 ts
+  pub ts_utc: Option<String>,
+
+  pub sens_score: f32,
+  pub sens_lv: Option<u8>,
+  /// 케이스 sensFilter 기준(LV n 이상), any면 None
+  pub sens_min: Option<u8>,
+
+  pub time_score: f32,
+  /// 시간 근접도 기준 시점(주요 당사자의 최근 기록 → 기간 끝 → 전체 최신 기록 순)
+  pub time_ref: Option<String>,
+  pub time_dist_days: Option<f32>,
 
   pub w_actor: f32,
   pub w_related: f32,
   pub w_text: f32,
+  pub w_time: f32,
+  pub w_sens: f32,
+  pub w_phrase: f32,
+  pub w_second: f32,
   pub min_score: f32,
   pub min_text_sim: f32,
 }
@@ -193,12 +296,34 @@ pub struct RankedHit {
 
 /* -------------------- core: rank -------------------- */
 
+/// 2차 관계 동시 등장이 이 횟수 이상이면 가산이 최대
+const COOC_SATURATE: u32 = 3;
+
+/// 랭킹에 곁들이는 Rust 쪽 상태(프런트에서 넘어오지 않는 것)
+#[derive(Default, Clone, Copy)]
+pub struct RankContext<'a> {
+  /// records와 같은 기록으로 유지된 역색인(store::RecordStore). 있으면 bm25 코퍼스를 다시 만들지 않는다.
+  /// 토크나이저가 다르면 무시하고 새로 만든다.
+  pub index: Option<&'a bm25::CorpusIndex>,
+  /// 쿼리 동의어/당사자 별칭
+  pub synonyms: Option<&'a synonym::SynonymTable>,
+}
+
 pub fn rank_records_for_case(
   records: &[RecordItem],
   case_item: &CaseItem,
   opts: Option<RankOpts>,
-) -> Vec<RankedHit> {
-  let (k, w_actor, w_related, w_text, min_score, min_text_sim) = {
+) -> Result<Vec<RankedHit>, String> {
+  rank_records_with(records, case_item, opts, RankContext::default())
+}
+
+pub fn rank_records_with(
+  records: &[RecordItem],
+  case_item: &CaseItem,
+  opts: Option<RankOpts>,
+  ctx: RankContext,
+) -> Result<Vec<RankedHit>, String> {
+  let (k, w_actor, w_related, w_text, w_time, w_sens, w_phrase, w_second, half_life, window, min_score, min_text_sim) = {
     let k = opts
       .as_ref()
       .and_then(|o| o.max_results)
@@ -210,36 +335,163 @@ pub fn rank_records_for_case(
     let w_actor = w.as_ref().and_then(|x| x.actor).unwrap_or(2.5);
     let w_related = w.as_ref().and_then(|x| x.related).unwrap_or(1.0);
     let w_text = w.as_ref().and_then(|x| x.text).unwrap_or(2.0);
+    let w_time = w.as_ref().and_then(|x| x.time).unwrap_or(0.0);
+    let w_sens = w.as_ref().and_then(|x| x.sens).unwrap_or(0.0);
+    let w_phrase = w.as_ref().and_then(|x| x.phrase).unwrap_or(1.0);
+    let w_second = w.as_ref().and_then(|x| x.second_degree).unwrap_or(0.5);
+    let half_life = opts
+      .as_ref()
+      .and_then(|o| o.time_half_life_days)
+      .filter(|d| *d > 0.0)
+      .unwrap_or(30.0);
+    let window = opts
+      .as_ref()
+      .and_then(|o| o.proximity_window)
+      .unwrap_or(similarity::PROXIMITY_WINDOW);
 
     let min_score = opts.as_ref().and_then(|o| o.min_score).unwrap_or(0.8);
     let min_text_sim = opts.as_ref().and_then(|o| o.min_text_sim).unwrap_or(0.34);
 
-    (k, w_actor, w_related, w_text, min_score, min_text_sim)
+    (k, w_actor, w_related, w_text, w_time, w_sens, w_phrase, w_second, half_life, window, min_score, min_text_sim)
+  };
+  let type_weights: HashMap<String, f32> = opts
+    .as_ref()
+    .and_then(|o| o.weights.as_ref())
+    .and_then(|w| w.actor_types.clone())
+    .unwrap_or_default();
+  // 기록 쪽 유형이 비어 있으면 맞은 케이스 당사자의 유형으로
+  let type_w = |a: &ActorRef, matched: Option<&ActorRef>| -> f32 {
+    let t = match a.r#type.trim() {
+      "" => matched.map(|m| m.r#type.trim()).unwrap_or(""),
+      t => t,
+    };
+    type_weights.get(t).copied().unwrap_or(1.0)
   };
 
   let q = case_item.query.trim();
-  let q_tokens = if q.is_empty() { vec![] } else { tokenize(q) };
+  let tokenizer = tokenize::tokenizer_by_name(opts.as_ref().and_then(|o| o.tokenizer.as_deref()))?;
+  // 연산자/필드가 있으면 조건으로 거르고, 제외가 아닌 단어/구만 키워드 점수에 쓴다.
+  // 문법이 틀린 쿼리(입력 중인 "(폭언")는 평범한 키워드로 랭킹한다 — 오류 위치는 engine_parse_query로만 알린다
+  let plan = query::parse_query(q)
+    .ok()
+    .filter(|parsed| !query::is_plain(parsed))
+    .map(|parsed| query::QueryPlan::new(parsed, tokenizer.as_ref(), ctx.synonyms));
+  let q_tokens = match &plan {
+    Some(p) => p.positive_tokens(tokenizer.as_ref()),
+    None if q.is_empty() => vec![],
+    None => tokenizer.tokenize(q),
+  };
+  let text_mode = similarity::TextMode::parse(opts.as_ref().and_then(|o| o.text_mode.as_deref()))?;
+
+  // bm25: 넘어온 기록 전체를 코퍼스로 문서 빈도를 잡는다
+  let built;
+  let corpus = match ctx.index {
+    _ if text_mode != similarity::TextMode::Bm25 => None,
+    Some(idx) if idx.tokenizer_name() == tokenizer.name() => Some(idx),
+    _ => {
+      built = bm25::CorpusIndex::build(records, tokenizer.as_ref());
+      Some(&built)
+    }
+  };
+  let q_stats = corpus.map(|c| c.query_stats(&q_tokens)).unwrap_or_default();
 
-  let case_actor_names: HashSet<String> = case_item
-    .actors
+  // 쿼리 토큰별 동의어. 원래 말이 요약에 없을 때만 대신 쓴다.
+  let q_alts: Vec<Vec<String>> = q_tokens
     .iter()
-    .map(|a| norm(&a.name))
-    .filter(|s| !s.is_empty())
+    .map(|t| ctx.synonyms.map(|s| s.term_alternatives(t)).unwrap_or_default())
     .collect();
+  let alt_stats: HashMap<&str, (u32, f32)> = match corpus {
+    Some(c) => q_alts
+      .iter()
+      .flatten()
+      .map(|a| {
+        let df = c.df(a);
+        (a.as_str(), (df, c.idf(df)))
+      })
+      .collect(),
+    None => HashMap::new(),
+  };
 
-  let main_actor_name = case_item
+  // 케이스 당사자와 그 별칭(정규화)
+  let case_actors: Vec<(&ActorRef, Vec<String>)> = case_item
     .actors
-    .get(0)
-    .map(|a| norm(&a.name))
-    .filter(|s| !s.is_empty());
+    .iter()
+    .filter(|a| !norm(&a.name).is_empty())
+    .map(|a| (a, ctx.synonyms.map(|s| s.name_aliases(&a.name)).unwrap_or_default()))
+    .collect();
+  // (유형, 이름)으로 맞는 케이스 당사자. 별칭으로 맞았으면 (별칭, 원래 이름)도 함께
+  type ActorHit<'a> = (&'a ActorRef, Option<(String, String)>);
+  let match_case_actor = |a: &ActorRef| -> Option<ActorHit> {
+    if let Some((c, _)) = case_actors.iter().find(|(c, _)| actor_eq(a, c)) {
+      return Some((*c, None));
+    }
+    let n = norm(&a.name);
+    if n.is_empty() {
+      return None;
+    }
+    case_actors
+      .iter()
+      .find(|(c, aliases)| actor_type_compat(a, c) && aliases.contains(&n))
+      .map(|(c, _)| (*c, Some((n.clone(), c.name.clone()))))
+  };
 
-  let has_range = !case_item.time_from.is_empty() || !case_item.time_to.is_empty();
+  let main_actor: Option<&ActorRef> = case_actors.first().map(|(a, _)| *a);
+  let is_main = |a: &ActorRef| -> bool {
+    match (main_actor, match_case_actor(a)) {
+      (Some(m), Some((c, _))) => std::ptr::eq(m, c),
+      _ => false,
+    }
+  };
+
+  // 2차 관계: 케이스 당사자가 아닌 사람별로, 케이스 당사자와 같은 기록에 함께 나온 기록 수
+  let cooc: HashMap<String, u32> = {
+    let mut m: HashMap<String, u32> = HashMap::new();
+    for r in records {
+      let people: Vec<&ActorRef> = std::iter::once(&r.actor).chain(r.related.iter()).collect();
+      if !people.iter().any(|p| match_case_actor(p).is_some()) {
+        continue;
+      }
+      let mut seen: HashSet<String> = HashSet::new();
+      for p in people {
+        if match_case_actor(p).is_some() {
+          continue;
+        }
+        if let Some(k) = actor_key(p).filter(|k| seen.insert(k.clone())) {
+          *m.entry(k).or_insert(0) += 1;
+        }
+      }
+    }
+    m
+  };
+
+  let tz: Tz = time::resolve_tz(opts.as_ref().and_then(|o| o.time_zone.as_deref()))?;
+  let range = time::TimeRange::from_bounds(&case_item.time_from, &case_item.time_to, tz)?;
+  let has_range = range.is_set();
+
+  let sens_min: Option<u8> = match case_item.sens_filter.trim() {
+    "" | "any" => None,
+    f => Some(lv_rank(f).ok_or_else(|| format!("sensFilter: invalid sensitivity: {f}"))?),
+  };
+
+  // 시간 근접도 기준: 주요 당사자의 가장 최근 기록 → 케이스 기간 끝 → 전체 기록 중 최신
+  let time_ref: Option<DateTime<Utc>> = {
+    let main_latest = records
+      .iter()
+      .filter(|r| is_main(&r.actor))
+      .filter_map(|r| time::parse_ts_in(&r.ts, tz).ok())
+      .max();
+    let range_end = range.to_incl.or(range.to_excl);
+    main_latest
+      .or(range_end)
+      .or_else(|| records.iter().filter_map(|r| time::parse_ts_in(&r.ts, tz).ok()).max())
+  };
 
   #[derive(Clone)]
   struct Tmp {
     id: String,
     score: f32,
     ts: String,
+    t: Option<DateTime<Utc>>,
     reasons: Vec<String>,
     components: RankedComponents,
   }
@@ -248,37 +500,112 @@ pub fn rank_records_for_case(
   let mut candidates: Vec<Tmp> = Vec::new();
 
   for r in records {
-    let in_range = if has_range {
-      within_range(&r.ts, &case_item.time_from, &case_item.time_to)
-    } else {
-      true
+    let r_time = time::parse_ts_in(&r.ts, tz).ok();
+    let in_range = match r_time {
+      Some(t) if has_range => Some(range.contains(t)),
+      _ => None,
     };
-    if has_range && !in_range {
+    if in_range == Some(false) {
       continue;
     }
 
-    let r_actor_name = norm(&r.actor.name);
-    let actor_match_any = !r_actor_name.is_empty() && case_actor_names.contains(&r_actor_name);
+    let sens_lv = lv_rank(&r.lv);
+    if let Some(min) = sens_min {
+      if sens_lv.unwrap_or(0) < min {
+        continue;
+      }
+    }
 
-    let is_main_actor = main_actor_name
-      .as_ref()
-      .map(|m| &r_actor_name == m)
-      .unwrap_or(false);
+    // 구조화 쿼리는 주요 당사자 기록에도 그대로 적용된다
+    if let Some(p) = &plan {
+      if !p.matches(r, &norm(&r.summary)) {
+        continue;
+      }
+    }
+    let query_match = plan.as_ref().is_some_and(|p| p.has_positive());
+
+    let actor_hit = match_case_actor(&r.actor);
+    let actor_match_any = actor_hit.is_some();
+    let actor_type_weight = type_w(&r.actor, actor_hit.as_ref().map(|(c, _)| *c));
+    let mut alias_hits: Vec<(String, String)> = actor_hit.iter().filter_map(|(_, al)| al.clone()).collect();
+
+    let is_main_actor = is_main(&r.actor);
+    if case_item.only_main_actor && main_actor.is_some() && !is_main_actor {
+      continue;
+    }
 
     let mut related_hits = 0usize;
+    let mut related_score = 0.0f32;
     for ra in &r.related {
-      let rn = norm(&ra.name);
-      if !rn.is_empty() && case_actor_names.contains(&rn) {
+      if let Some((c, alias)) = match_case_actor(ra) {
         related_hits += 1;
+        related_score += w_related * type_w(ra, Some(c));
+        alias_hits.extend(alias);
+      }
+    }
+
+    let second_degree_cooc = if actor_match_any {
+      0
+    } else {
+      actor_key(&r.actor).and_then(|k| cooc.get(&k).copied()).unwrap_or(0)
+    };
+    let second_degree_score = if second_degree_cooc > 0 {
+      let strength = second_degree_cooc.min(COOC_SATURATE) as f32 / COOC_SATURATE as f32;
+      w_second * strength * actor_type_weight
+    } else {
+      0.0
+    };
+
+    // 원래 토큰이 요약에 없고 동의어가 있으면 그 동의어로 바꿔 계산
+    let mut syn_hits: Vec<(String, String)> = Vec::new();
+    let mut r_tokens: Vec<String> = q_tokens.clone();
+    let mut r_stats: Vec<(u32, f32)> = q_stats.clone();
+    if q_alts.iter().any(|a| !a.is_empty()) {
+      let s = norm(&r.summary);
+      for (i, alts) in q_alts.iter().enumerate() {
+        if s.contains(&q_tokens[i]) {
+          continue;
+        }
+        if let Some(a) = alts.iter().find(|a| s.contains(a.as_str())) {
+          syn_hits.push((q_tokens[i].clone(), a.clone()));
+          r_tokens[i] = a.clone();
+          if let Some(st) = alt_stats.get(a.as_str()) {
+            r_stats[i] = *st;
+          }
+        }
       }
     }
 
-    let (q_hit, q_total, sim) = text_similarity_stats(&q_tokens, &r.summary);
+    let (q_hit, q_total, sim, bm25_terms) = match &corpus {
+      Some(c) if !q_tokens.is_empty() => {
+        let (hit, sim, terms) = c.score(&r.id, &r_tokens, &r_stats);
+        (hit, q_tokens.len(), sim, terms)
+      }
+      _ => {
+        let (hit, total, sim) = similarity::similarity_stats(text_mode, &r_tokens, &r.summary);
+        (hit, total, sim, Vec::new())
+      }
+    };
 
-    let actor_score = if actor_match_any { w_actor } else { 0.0 };
-    let related_score = (related_hits as f32) * w_related;
+    let actor_score = if actor_match_any { w_actor * actor_type_weight } else { 0.0 };
     let keyword_score = sim * w_text;
-    let score: f32 = actor_score + related_score + keyword_score;
+    let phrase_score = if w_phrase > 0.0 {
+      similarity::proximity_stats(&r_tokens, &r.summary, window) * w_phrase
+    } else {
+      0.0
+    };
+    let time_dist_days = match (r_time, time_ref) {
+      (Some(t), Some(rf)) => Some((t - rf).num_minutes().abs() as f32 / 1440.0),
+      _ => None,
+    };
+    let time_score = time_dist_days
+      .map(|d| w_time * 0.5f32.powf(d / half_life))
+      .unwrap_or(0.0);
+
+    let sens_score = sens_lv.map(|lv| w_sens * lv as f32 / 5.0).unwrap_or(0.0);
+
+    let score: f32 =
+      actor_score + related_score + second_degree_score + keyword_score + phrase_score + time_score + sens_score;
 
     let mut reasons: Vec<String> = Vec::new();
     reasons.push("자동(랭킹)".into());
@@ -290,11 +617,47 @@ pub fn rank_records_for_case(
     if related_hits > 0 {
       reasons.push(format!("관련자 일치 {}명", related_hits));
     }
+    if second_degree_score > 0.0 {
+      reasons.push(format!(
+        "2차 관계: 케이스 당사자와 {}건 함께 등장 +{:.2}",
+        second_degree_cooc, second_degree_score
+      ));
+    }
+    for (alias, orig) in &alias_hits {
+      reasons.push(format!("별칭 '{}' → '{}'", alias, orig));
+    }
     if !q_tokens.is_empty() {
-      reasons.push(format!("키워드 {}/{}", q_hit, q_total));
+      reasons.push(match text_mode {
+        similarity::TextMode::Token => format!("키워드 {}/{}", q_hit, q_total),
+        similarity::TextMode::Ngram => format!("키워드(2-gram) {}/{} · 유사도 {:.2}", q_hit, q_total, sim),
+        similarity::TextMode::Bm25 => format!("키워드(BM25) {}/{} · 점수 {:.2}", q_hit, q_total, sim),
+      });
+      for (orig, syn) in &syn_hits {
+        reasons.push(format!("동의어 '{}' → '{}'", syn, orig));
+      }
+      if phrase_score > 0.0 {
+        reasons.push(format!("구/근접 가산 +{:.2}", phrase_score));
+      }
+    }
+    if query_match {
+      reasons.push("쿼리 조건 일치".into());
     }
     if has_range {
-      reasons.push(if in_range { "기간 내".into() } else { "기간 밖".into() });
+      reasons.push(if in_range.is_some() { "기간 내".into() } else { "기간 판단 불가".into() });
+    }
+    if r_time.is_none() {
+      reasons.push("일시 형식 오류".into());
+    }
+    if let (Some(min), Some(lv)) = (sens_min, sens_lv) {
+      reasons.push(format!("민감도 LV{} ≥ 기준 LV{}", lv, min));
+    }
+    if sens_score > 0.0 {
+      reasons.push(format!("민감도 가산 +{:.2}", sens_score));
+    }
+    if let Some(d) = time_dist_days {
+      if w_time > 0.0 {
+        reasons.push(format!("시간 근접 {:.2} (기준과 {:.0}일)", time_score, d));
+      }
     }
 
     let components = RankedComponents {
@@ -302,19 +665,41 @@ pub fn rank_records_for_case(
       text_sim: sim,
       q_hit: q_hit as u32,
       q_total: q_total as u32,
+      tokenizer: tokenizer.name().to_string(),
+      text_mode: text_mode.name().to_string(),
+      bm25_terms,
+      phrase_score,
 
       actor_score,
       actor_match: actor_match_any,
       is_main_actor,
+      actor_type_weight,
 
       related_score,
       related_hits: related_hits as u32,
 
-      in_range: if has_range { Some(in_range) } else { None },
+      second_degree_score,
+      second_degree_cooc,
+
+      in_range,
+      ts_valid: r_time.is_some(),
+      ts_utc: r_time.map(|t| t.to_rfc3339_opts(SecondsFormat::Millis, true)),
+
+      sens_score,
+      sens_lv,
+      sens_min,
+
+      time_score,
+      time_ref: time_ref.map(|t| t.to_rfc3339_opts(SecondsFormat::Millis, true)),
+      time_dist_days,
 
       w_actor,
       w_related,
       w_text,
+      w_time,
+      w_sens,
+      w_phrase,
+      w_second,
       min_score,
       min_text_sim,
     };
@@ -323,6 +708,7 @@ pub fn rank_records_for_case(
       id: r.id.clone(),
       score,
       ts: r.ts.clone(),
+      t: r_time,
       reasons,
       components,
     };
@@ -332,16 +718,16 @@ pub fn rank_records_for_case(
       continue;
     }
 
-    let passes_logic = actor_match_any
-      || related_hits > 0
-      || (!q_tokens.is_empty() && sim >= min_text_sim);
+    // 2차 관계는 가산점일 뿐 후보 조건이 아니다(당사자 주변 사람의 무관한 기록이 끌려오지 않게)
+    let passes_logic = actor_match_any || related_hits > 0 || (!q_tokens.is_empty() && sim >= min_text_sim);
 
-    if passes_logic && score >= min_score {
+    // 쿼리 조건에 맞은 기록은 점수 하한과 상관없이 후보(필드만 있는 쿼리도 결과가 나오도록)
+    if query_match || (passes_logic && score >= min_score) {
       candidates.push(tmp);
     }
   }
 
-  main_hits.sort_by(|a, b| match b.ts.cmp(&a.ts) {
+  main_hits.sort_by(|a, b| match b.t.cmp(&a.t).then_with(|| b.ts.cmp(&a.ts)) {
     Ordering::Equal => match b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal) {
       Ordering::Equal => a.id.cmp(&b.id),
       other => other,
@@ -350,7 +736,7 @@ pub fn rank_records_for_case(
   });
 
   candidates.sort_by(|a, b| match b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal) {
-    Ordering::Equal => match b.ts.cmp(&a.ts) {
+    Ordering::Equal => match b.t.cmp(&a.t).then_with(|| b.ts.cmp(&a.ts)) {
       Ordering::Equal => a.id.cmp(&b.id),
       other => other,
     },
@@ -373,7 +759,7 @@ pub fn rank_records_for_case(
     }
   }
 
-  merged
+  Ok(merged
     .into_iter()
     .enumerate()
     .map(|(i, t)| RankedHit {
@@ -383,76 +769,102 @@ pub fn rank_records_for_case(
       reasons: t.reasons,
       components: t.components,
     })
-    .collect()
+    .collect())
 }
 
-/* -------------------- core: advise -------------------- */
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::test_util::{self, record, student};
+  use serde_json::json;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct AdvisorItem {
-  pub id: String,
-  pub ts: String,
-  pub title: String,
-  pub body: String,
-  pub level: String,
-  #[serde(default)]
-  pub tags: Vec<String>,
-  pub state: String,
-  pub rule_id: Option<String>,
-}
+  fn rec(id: &str, ts: &str, actor: &str, related: &[&str], summary: &str) -> RecordItem {
+    let related: Vec<_> = related.iter().map(|n| student(n)).collect();
+    record(id, summary, json!({"ts": ts, "actor": student(actor), "related": related}))
+  }
 
-// [수정] js_sys::Date::now() 대신 Rust 표준 라이브러리 사용
-fn uid(prefix: &str) -> String {
-  let start = SystemTime::now();
-  let since_the_epoch = start
-    .duration_since(UNIX_EPOCH)
-    .expect("Time went backwards");
-  let timestamp = since_the_epoch.as_millis();
-  format!("{}_{}", prefix, timestamp)
-}
+  fn case(actors: &[&str], query: &str) -> CaseItem {
+    let actors: Vec<_> = actors.iter().map(|n| student(n)).collect();
+    test_util::case("C", json!({"query": query, "actors": actors}))
+  }
 
-fn chrono_like_now_iso() -> String {
-  // TODO: 실제 ISO8601 문자열이 필요하면 chrono::Utc::now().to_rfc3339() 등을 사용
-  "1970-01-01T00:00:00Z".into()
-}
+  fn ids(hits: &[RankedHit]) -> Vec<&str> {
+    hits.iter().map(|h| h.id.as_str()).collect()
+  }
 
-pub fn generate_advisors_for_case(case_item: &CaseItem, _records: &[RecordItem]) -> Vec<AdvisorItem> {
-  let ts = chrono_like_now_iso();
-  let mut out: Vec<AdvisorItem> = Vec::new();
-
-  out.push(AdvisorItem {
-    id: uid("ADV"),
-    ts: ts.clone(),
-    title: "증빙 정리".into(),
-    body: "시간순으로 사실만 정리하고, 원본 증빙(녹취/문서/메신저)을 함께 묶어두세요.".into(),
-    level: "info".into(),
-    tags: vec!["정리".into()],
-    state: "active".into(),
-    rule_id: Some("proto:pack".into()),
-  });
+  #[test]
+  fn time_weight_is_opt_in() {
+    let records = vec![
+      rec("A", "2025-03-01T09:00", "김철수", &[], "폭언"),
+      rec("B", "2025-03-01T10:00", "박영희", &[], "급식 메뉴"),
+    ];
+    let hits = rank_records_for_case(&records, &case(&["김철수"], "폭언"), None).unwrap();
+    assert_eq!(ids(&hits), vec!["A"]);
+    assert_eq!(hits[0].components.time_score, 0.0);
+
+    let opts: RankOpts = serde_json::from_value(json!({"weights": {"time": 1.0}})).unwrap();
+    let hits = rank_records_for_case(&records, &case(&["김철수"], "폭언"), Some(opts)).unwrap();
+    assert!(hits[0].components.time_score > 0.0);
+  }
 
-  out.push(AdvisorItem {
-    id: uid("ADV"),
-    ts: ts.clone(),
-    title: "상대에게 전달".into(),
-    body: "감정 표현 대신 사실과 조치만 전달하고, 필요하면 외부 전문기관/관리자 경로를 안내하세요.".into(),
-    level: "warn".into(),
-    tags: vec!["대화".into()],
-    state: "active".into(),
-    rule_id: Some("proto:talk".into()),
-  });
+  #[test]
+  fn malformed_query_falls_back_to_plain_keywords() {
+    let records = vec![
+      rec("a", "2025-03-01T09:00:00+09:00", "학생1", &[], "교실에서 폭언"),
+      rec("b", "2025-03-02T09:00:00+09:00", "학생2", &[], "급식실 정리"),
+    ];
+    let hits = rank_records_for_case(&records, &case(&[], "(폭언"), None).unwrap();
+    assert_eq!(ids(&hits), vec!["a"]);
+  }
 
-  out.push(AdvisorItem {
-    id: uid("ADV"),
-    ts,
-    title: "후속 조치".into(),
-    body: "내부 보고/기록 보관/재발 방지 계획을 남겨두면 추후 방어에 도움이 됩니다.".into(),
-    level: "info".into(),
-    tags: vec!["후속".into()],
-    state: "active".into(),
-    rule_id: Some("proto:follow".into()),
-  });
+  #[test]
+  fn second_degree_only_boosts() {
+    let mut records: Vec<RecordItem> = (0..3)
+      .map(|i| rec(&format!("r{i}"), "2025-03-01T09:00", "김철수", &["박영희"], "상담"))
+      .collect();
+    records.push(rec("x", "2025-03-02T09:00", "박영희", &[], "급식 메뉴"));
+    records.push(rec("y", "2025-03-02T10:00", "박영희", &[], "폭언"));
+    records.push(rec("z", "2025-03-02T11:00", "이민수", &[], "폭언"));
+
+    // 가중치를 키워 2차 관계 점수만으로 minScore를 넘어도 후보가 되지 않는다
+    let opts: RankOpts = serde_json::from_value(json!({"weights": {"secondDegree": 1.0}, "minScore": 0.5})).unwrap();
+    let hits = rank_records_for_case(&records, &case(&["김철수"], "폭언"), Some(opts)).unwrap();
+    assert!(!ids(&hits).contains(&"x"));
+    let y = hits.iter().find(|h| h.id == "y").unwrap();
+    let z = hits.iter().find(|h| h.id == "z").unwrap();
+    assert!(y.components.second_degree_score > 0.0);
+    assert!(y.score > z.score);
+  }
 
-  out
-}
\ No newline at end of file
+  #[test]
+  fn unparseable_ts_is_kept_and_flagged() {
+    let records = vec![
+      rec("a", "2025-03-02", "김철수", &[], "폭언"),
+      rec("b", "지난주 화요일", "김철수", &[], "폭언"),
+      rec("c", "2025-03-03T09:00", "김철수", &[], "폭언"),
+    ];
+    let mut c = case(&["김철수"], "폭언");
+    c.time_from = "2025-03-01".into();
+    c.time_to = "2025-03-02".into();
+    let hits = rank_records_for_case(&records, &c, None).unwrap();
+    assert_eq!(ids(&hits).len(), 2);
+
+    let a = hits.iter().find(|h| h.id == "a").unwrap();
+    assert!(a.components.ts_valid);
+    assert_eq!(a.components.in_range, Some(true));
+    assert_eq!(a.components.ts_utc.as_deref(), Some("2025-03-01T15:00:00.000Z"));
+
+    let b = hits.iter().find(|h| h.id == "b").unwrap();
+    assert!(!b.components.ts_valid);
+    assert_eq!(b.components.ts_utc, None);
+    assert!(b.reasons.iter().any(|r| r == "일시 형식 오류"));
+  }
+
+  #[test]
+  fn unknown_time_zone_is_an_error() {
+    let records = vec![rec("a", "2025-03-02T09:00", "김철수", &[], "폭언")];
+    let opts: RankOpts = serde_json::from_value(json!({"timeZone": "Mars/Olympus"})).unwrap();
+    let err = rank_records_for_case(&records, &case(&["김철수"], "폭언"), Some(opts)).unwrap_err();
+    assert_eq!(err, "unknown time zone: Mars/Olympus");
+  }
+}
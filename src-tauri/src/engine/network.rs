@@ -0,0 +1,380 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::{actor_eq, actor_key, lv_rank, norm, time, ActorRef, RecordItem};
+
+/* -------------------- actor co-occurrence network -------------------- */
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkOpts {
+  /// 이 사람과 직접 연결된 사람만(자기 포함) 남긴다
+  #[serde(default)]
+  pub focus: Option<ActorRef>,
+  /// 함께 나온 기록 수가 이보다 적은 연결은 뺀다(기본 1)
+  #[serde(default)]
+  pub min_cooc: Option<u32>,
+  #[serde(default, alias = "tz")]
+  pub time_zone: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkNode {
+  /// "유형|이름"(정규화)
+  pub id: String,
+  #[serde(rename = "type")]
+  pub r#type: String,
+  pub name: String,
+  pub record_count: u32,
+  /// 주 당사자로 나온 기록 수(나머지는 관련자)
+  pub as_actor: u32,
+  pub as_related: u32,
+  /// "LV1".."LV5" → 기록 수(형식이 틀린 값은 "기타")
+  pub sens: BTreeMap<String, u32>,
+  pub first_ts: Option<String>,
+  pub last_ts: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkEdge {
+  pub source: String,
+  pub target: String,
+  /// 같은 기록에 함께 나온 횟수
+  pub cooc: u32,
+  /// 함께 나온 첫 기록 ~ 마지막 기록(일)
+  pub span_days: f32,
+  /// cooc × (1 + ln(1 + span_days/30)): 오래 이어진 관계일수록 굵게
+  pub weight: f32,
+  pub first_ts: Option<String>,
+  pub last_ts: Option<String>,
+  pub record_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActorNetwork {
+  pub nodes: Vec<NetworkNode>,
+  pub edges: Vec<NetworkEdge>,
+  /// GraphViz DOT(undirected)
+  pub dot: String,
+}
+
+#[derive(Default)]
+struct NodeAcc {
+  r#type: String,
+  name: String,
+  record_count: u32,
+  as_actor: u32,
+  as_related: u32,
+  sens: BTreeMap<String, u32>,
+  first: Option<DateTime<Utc>>,
+  last: Option<DateTime<Utc>>,
+}
+
+#[derive(Default)]
+struct EdgeAcc {
+  cooc: u32,
+  first: Option<DateTime<Utc>>,
+  last: Option<DateTime<Utc>>,
+  record_ids: Vec<String>,
+}
+
+fn widen(first: &mut Option<DateTime<Utc>>, last: &mut Option<DateTime<Utc>>, t: Option<DateTime<Utc>>) {
+  let Some(t) = t else {
+    return;
+  };
+  if first.is_none_or(|f| t < f) {
+    *first = Some(t);
+  }
+  if last.is_none_or(|l| t > l) {
+    *last = Some(t);
+  }
+}
+
+fn fmt_ts(t: Option<DateTime<Utc>>) -> Option<String> {
+  t.map(|t| t.to_rfc3339_opts(SecondsFormat::Millis, true))
+}
+
+pub fn build_actor_network(records: &[RecordItem], opts: &NetworkOpts) -> Result<ActorNetwork, String> {
+  let tz = time::resolve_tz(opts.time_zone.as_deref())?;
+  let min_cooc = opts.min_cooc.unwrap_or(1).max(1);
+
+  // 유형 없이 적힌 이름은 같은 이름의 유형 있는 사람이 하나뿐이면 그 사람으로 본다(둘 이상이면 따로 둔다)
+  let mut typed: HashMap<String, HashSet<String>> = HashMap::new();
+  for a in records.iter().flat_map(|r| std::iter::once(&r.actor).chain(r.related.iter())) {
+    if let Some(key) = actor_key(a).filter(|_| !a.r#type.trim().is_empty()) {
+      typed.entry(norm(&a.name)).or_default().insert(key);
+    }
+  }
+  let node_key = |a: &ActorRef| -> Option<String> {
+    let key = actor_key(a)?;
+    if !a.r#type.trim().is_empty() {
+      return Some(key);
+    }
+    match typed.get(&norm(&a.name)) {
+      Some(keys) if keys.len() == 1 => keys.iter().next().cloned(),
+      _ => Some(key),
+    }
+  };
+
+  let mut nodes: HashMap<String, NodeAcc> = HashMap::new();
+  let mut edges: HashMap<(String, String), EdgeAcc> = HashMap::new();
+
+  for r in records {
+    let t = time::parse_ts_in(&r.ts, tz).ok();
+    let lv = match lv_rank(&r.lv) {
+      Some(n) => format!("LV{n}"),
+      None => "기타".to_string(),
+    };
+
+    // 한 기록에 같은 사람이 두 번 적혀도 한 번으로
+    let mut present: Vec<String> = Vec::new();
+    let people = std::iter::once((&r.actor, true)).chain(r.related.iter().map(|a| (a, false)));
+    for (a, is_actor) in people {
+      let Some(key) = node_key(a) else {
+        continue;
+      };
+      if present.contains(&key) {
+        continue;
+      }
+      let n = nodes.entry(key.clone()).or_insert_with(|| NodeAcc {
+        r#type: key.split_once('|').map_or("", |(t, _)| t).to_string(),
+        name: a.name.trim().to_string(),
+        ..NodeAcc::default()
+      });
+      n.record_count += 1;
+      if is_actor {
+        n.as_actor += 1;
+      } else {
+        n.as_related += 1;
+      }
+      *n.sens.entry(lv.clone()).or_insert(0) += 1;
+      widen(&mut n.first, &mut n.last, t);
+      present.push(key);
+    }
+
+    for i in 0..present.len() {
+      for j in i + 1..present.len() {
+        let pair = if present[i] < present[j] {
+          (present[i].clone(), present[j].clone())
+        } else {
+          (present[j].clone(), present[i].clone())
+        };
+        let e = edges.entry(pair).or_default();
+        e.cooc += 1;
+        widen(&mut e.first, &mut e.last, t);
+        e.record_ids.push(r.id.clone());
+      }
+    }
+  }
+
+  edges.retain(|_, e| e.cooc >= min_cooc);
+
+  // focus: 자기 자신 + 직접 연결된 사람
+  if let Some(f) = opts.focus.as_ref().filter(|f| !norm(&f.name).is_empty()) {
+    let focus_ids: HashSet<String> = nodes
+      .iter()
+      .filter(|(_, n)| {
+        let a = ActorRef {
+          r#type: n.r#type.clone(),
+          name: n.name.clone(),
+        };
+        actor_eq(&a, f)
+      })
+      .map(|(k, _)| k.clone())
+      .collect();
+    let mut keep: HashSet<String> = focus_ids.clone();
+    for (s, t) in edges.keys() {
+      if focus_ids.contains(s) {
+        keep.insert(t.clone());
+      }
+      if focus_ids.contains(t) {
+        keep.insert(s.clone());
+      }
+    }
+    nodes.retain(|k, _| keep.contains(k));
+    edges.retain(|(s, t), _| keep.contains(s) && keep.contains(t));
+  }
+
+  let mut out_nodes: Vec<NetworkNode> = nodes
+    .into_iter()
+    .map(|(id, n)| NetworkNode {
+      id,
+      r#type: n.r#type,
+      name: n.name,
+      record_count: n.record_count,
+      as_actor: n.as_actor,
+      as_related: n.as_related,
+      sens: n.sens,
+      first_ts: fmt_ts(n.first),
+      last_ts: fmt_ts(n.last),
+    })
+    .collect();
+  out_nodes.sort_by(|a, b| b.record_count.cmp(&a.record_count).then_with(|| a.id.cmp(&b.id)));
+
+  let mut out_edges: Vec<NetworkEdge> = edges
+    .into_iter()
+    .map(|((source, target), e)| {
+      let span_days = match (e.first, e.last) {
+        (Some(f), Some(l)) => (l - f).num_minutes() as f32 / 1440.0,
+        _ => 0.0,
+      };
+      NetworkEdge {
+        source,
+        target,
+        cooc: e.cooc,
+        span_days,
+        weight: e.cooc as f32 * (1.0 + (span_days / 30.0).ln_1p()),
+        first_ts: fmt_ts(e.first),
+        last_ts: fmt_ts(e.last),
+        record_ids: e.record_ids,
+      }
+    })
+    .collect();
+  out_edges.sort_by(|a, b| {
+    b.weight
+      .partial_cmp(&a.weight)
+      .unwrap_or(std::cmp::Ordering::Equal)
+      .then_with(|| a.source.cmp(&b.source))
+      .then_with(|| a.target.cmp(&b.target))
+  });
+
+  let dot = to_dot(&out_nodes, &out_edges);
+  Ok(ActorNetwork {
+    nodes: out_nodes,
+    edges: out_edges,
+    dot,
+  })
+}
+
+/* -------------------- GraphViz DOT -------------------- */
+
+fn dot_quote(s: &str) -> String {
+  format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+pub fn to_dot(nodes: &[NetworkNode], edges: &[NetworkEdge]) -> String {
+  let mut out = String::from("graph actors {\n  graph [overlap=false];\n  node [shape=ellipse, fontname=\"sans-serif\"];\n");
+  for n in nodes {
+    let label = if n.r#type.is_empty() {
+      format!("{}\n{}건", n.name, n.record_count)
+    } else {
+      format!("{}\n({}) {}건", n.name, n.r#type, n.record_count)
+    };
+    out.push_str(&format!("  {} [label={}];\n", dot_quote(&n.id), dot_quote(&label)));
+  }
+  let max_w = edges.iter().map(|e| e.weight).fold(0.0f32, f32::max).max(1.0);
+  for e in edges {
+    out.push_str(&format!(
+      "  {} -- {} [label=\"{}\", penwidth={:.2}];\n",
+      dot_quote(&e.source),
+      dot_quote(&e.target),
+      e.cooc,
+      1.0 + 4.0 * e.weight / max_w
+    ));
+  }
+  out.push_str("}\n");
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::test_util::record;
+  use serde_json::json;
+
+  /// people: (유형, 이름), 첫 번째가 주 당사자
+  fn rec(id: &str, ts: &str, lv: &str, people: &[(&str, &str)]) -> RecordItem {
+    let who = |(t, n): &(&str, &str)| json!({"type": t, "name": n});
+    let related: Vec<_> = people[1..].iter().map(who).collect();
+    record(id, "", json!({"ts": ts, "lv": lv, "actor": who(&people[0]), "related": related}))
+  }
+
+  fn node<'a>(net: &'a ActorNetwork, id: &str) -> &'a NetworkNode {
+    net.nodes.iter().find(|n| n.id == id).unwrap_or_else(|| panic!("no node {id}"))
+  }
+
+  fn sample() -> Vec<RecordItem> {
+    vec![
+      rec("r1", "2025-03-01T09:00:00+09:00", "LV2", &[("학생", "김철수"), ("학생", "이영희"), ("학생", "김철수")]),
+      rec("r2", "2025-03-31T09:00:00+09:00", "LV4", &[("학생", "이영희"), ("학생", "김철수")]),
+      rec("r3", "2025-03-10T09:00:00+09:00", "LVX", &[("학부모", "박민수"), ("학생", "김철수")]),
+    ]
+  }
+
+  #[test]
+  fn counts_nodes_roles_and_sensitivity() {
+    let net = build_actor_network(&sample(), &NetworkOpts::default()).unwrap();
+    assert_eq!(net.nodes.len(), 3);
+    let kim = node(&net, "학생|김철수");
+    // r1에 두 번 적혀도 한 번
+    assert_eq!((kim.record_count, kim.as_actor, kim.as_related), (3, 1, 2));
+    let sens: Vec<(&str, u32)> = kim.sens.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+    assert_eq!(sens, vec![("LV2", 1), ("LV4", 1), ("기타", 1)]);
+    assert_eq!(kim.first_ts.as_deref(), Some("2025-03-01T00:00:00.000Z"));
+    assert_eq!(kim.last_ts.as_deref(), Some("2025-03-31T00:00:00.000Z"));
+  }
+
+  #[test]
+  fn edges_carry_span_and_weight() {
+    let net = build_actor_network(&sample(), &NetworkOpts::default()).unwrap();
+    assert_eq!(net.edges.len(), 2);
+    let e = &net.edges[0];
+    assert_eq!((e.source.as_str(), e.target.as_str()), ("학생|김철수", "학생|이영희"));
+    assert_eq!((e.cooc, e.span_days), (2, 30.0));
+    assert!((e.weight - 2.0 * (1.0 + 2f32.ln())).abs() < 1e-5);
+    assert_eq!(e.record_ids, vec!["r1", "r2"]);
+    assert_eq!(net.edges[1].weight, 1.0);
+  }
+
+  #[test]
+  fn min_cooc_and_focus_trim_the_graph() {
+    let opts = NetworkOpts {
+      min_cooc: Some(2),
+      ..NetworkOpts::default()
+    };
+    let net = build_actor_network(&sample(), &opts).unwrap();
+    assert_eq!(net.edges.len(), 1);
+    assert_eq!(net.nodes.len(), 3, "min_cooc keeps nodes");
+
+    let opts = NetworkOpts {
+      focus: Some(ActorRef {
+        r#type: "학부모".into(),
+        name: "박민수".into(),
+      }),
+      ..NetworkOpts::default()
+    };
+    let net = build_actor_network(&sample(), &opts).unwrap();
+    let mut ids: Vec<&str> = net.nodes.iter().map(|n| n.id.as_str()).collect();
+    ids.sort();
+    assert_eq!(ids, vec!["학부모|박민수", "학생|김철수"]);
+    assert_eq!(net.edges.len(), 1);
+  }
+
+  #[test]
+  fn untyped_names_join_the_only_typed_node() {
+    let mut records = sample();
+    records.push(rec("r4", "2025-04-01T09:00:00+09:00", "LV1", &[("", "박민수"), ("", "이영희")]));
+    let net = build_actor_network(&records, &NetworkOpts::default()).unwrap();
+    assert_eq!(net.nodes.len(), 3);
+    assert_eq!(node(&net, "학부모|박민수").record_count, 2);
+
+    // 같은 이름의 유형이 둘이면 어느 쪽인지 모르니 따로 둔다
+    records.push(rec("r5", "2025-04-02T09:00:00+09:00", "LV1", &[("교사", "박민수")]));
+    let net = build_actor_network(&records, &NetworkOpts::default()).unwrap();
+    assert_eq!(node(&net, "|박민수").record_count, 1);
+    assert_eq!(node(&net, "학부모|박민수").record_count, 1);
+  }
+
+  #[test]
+  fn dot_escapes_quotes_and_backslashes() {
+    let records = vec![rec("r1", "2025-03-01T09:00:00Z", "LV1", &[("학생", "김\"철수\""), ("학생", "a\\b")])];
+    let dot = build_actor_network(&records, &NetworkOpts::default()).unwrap().dot;
+    assert!(dot.starts_with("graph actors {"));
+    assert!(dot.contains(r#""학생|김\"철수\"" [label="김\"철수\"\n(학생) 1건"];"#), "{dot}");
+    assert!(dot.contains(r#""학생|a\\b" -- "학생|김\"철수\"""#), "{dot}");
+  }
+}
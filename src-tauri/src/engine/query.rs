@@ -0,0 +1,461 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+use super::synonym::SynonymTable;
+use super::tokenize::Tokenizer;
+use super::{lv_rank, norm, ActorRef, RecordItem};
+
+/* -------------------- case query language -------------------- */
+//
+//   폭언 "교실 밖" -사과 place:교실 store:녹취록 lv:>=LV3 actor:학생4 related:교감
+//   (욕설 OR 막말) -place:온라인
+//
+// - 띄어 쓴 조건은 AND, 대문자 OR는 OR, 괄호로 묶을 수 있다
+// - "..." 는 구(붙어 있는 그대로), -조건 은 제외
+// - 필드: place / store / lv / actor / related (값에 공백이 있으면 place:"생활 지도실")
+// - 연산자가 하나도 없으면 예전처럼 토큰 묶음으로 랭킹한다(is_plain)
+
+pub const QUERY_FIELDS: &[&str] = &["place", "store", "lv", "actor", "related"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum QueryNode {
+  Term { text: String },
+  Phrase { text: String },
+  /// op는 lv에서만 쓴다(">=", "<=", ">", "<", "=", 비어 있으면 "=")
+  Field { field: String, op: String, value: String },
+  Not { node: Box<QueryNode> },
+  And { nodes: Vec<QueryNode> },
+  Or { nodes: Vec<QueryNode> },
+}
+
+/// start/end는 쿼리 문자열의 문자(char) 위치, end는 배타
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryError {
+  pub message: String,
+  pub start: usize,
+  pub end: usize,
+}
+
+impl fmt::Display for QueryError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} ({}..{})", self.message, self.start, self.end)
+  }
+}
+
+fn err<T>(message: impl Into<String>, start: usize, end: usize) -> Result<T, QueryError> {
+  Err(QueryError {
+    message: message.into(),
+    start,
+    end,
+  })
+}
+
+/* -------------------- lexer -------------------- */
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+  Word(String),
+  Quoted(String),
+  Field { field: String, op: String, value: String },
+  Minus,
+  Or,
+  LParen,
+  RParen,
+}
+
+#[derive(Debug, Clone)]
+struct Lexed {
+  tok: Tok,
+  start: usize,
+  end: usize,
+}
+
+fn is_word_end(c: char) -> bool {
+  c.is_whitespace() || c == '(' || c == ')' || c == '"'
+}
+
+/// "..." 를 읽는다(i는 여는 따옴표 위치). (내용, 닫는 따옴표 다음 위치)
+fn read_quoted(cs: &[char], i: usize) -> Result<(String, usize), QueryError> {
+  match cs[i + 1..].iter().position(|&c| c == '"') {
+    Some(off) => Ok((cs[i + 1..i + 1 + off].iter().collect(), i + off + 2)),
+    None => err("닫는 따옴표가 없음", i, cs.len()),
+  }
+}
+
+fn split_op(v: &str) -> (&str, &str) {
+  for op in [">=", "<=", ">", "<", "="] {
+    if let Some(rest) = v.strip_prefix(op) {
+      return (op, rest);
+    }
+  }
+  ("", v)
+}
+
+fn lex(q: &str) -> Result<Vec<Lexed>, QueryError> {
+  let cs: Vec<char> = q.chars().collect();
+  let mut out: Vec<Lexed> = Vec::new();
+  let mut i = 0usize;
+
+  while i < cs.len() {
+    let c = cs[i];
+    if c.is_whitespace() {
+      i += 1;
+      continue;
+    }
+    let start = i;
+    match c {
+      '(' => {
+        out.push(Lexed { tok: Tok::LParen, start, end: i + 1 });
+        i += 1;
+      }
+      ')' => {
+        out.push(Lexed { tok: Tok::RParen, start, end: i + 1 });
+        i += 1;
+      }
+      '"' => {
+        let (text, next) = read_quoted(&cs, i)?;
+        if norm(&text).is_empty() {
+          return err("빈 구", start, next);
+        }
+        out.push(Lexed { tok: Tok::Quoted(text), start, end: next });
+        i = next;
+      }
+      // 바로 뒤에 조건이 붙은 '-'만 제외 연산자(홀로 쓴 '-'는 무시)
+      '-' if cs.get(i + 1).is_some_and(|n| !n.is_whitespace() && *n != ')') => {
+        out.push(Lexed { tok: Tok::Minus, start, end: i + 1 });
+        i += 1;
+      }
+      '-' => i += 1,
+      _ => {
+        let mut j = i;
+        while j < cs.len() && !is_word_end(cs[j]) && cs[j] != ':' {
+          j += 1;
+        }
+        let head: String = cs[i..j].iter().collect();
+        let field = head.to_lowercase();
+
+        if j < cs.len() && cs[j] == ':' && QUERY_FIELDS.contains(&field.as_str()) {
+          let vstart = j + 1;
+          let (raw, next) = if cs.get(vstart) == Some(&'"') {
+            read_quoted(&cs, vstart)?
+          } else {
+            let mut k = vstart;
+            while k < cs.len() && !is_word_end(cs[k]) {
+              k += 1;
+            }
+            (cs[vstart..k].iter().collect(), k)
+          };
+          let (op, value) = split_op(raw.trim());
+          let value = value.trim().to_string();
+          if value.is_empty() {
+            return err(format!("{field}: 값이 비어 있음"), start, next);
+          }
+          if field == "lv" {
+            if lv_rank(&value.to_uppercase()).is_none() {
+              return err(format!("lv: LV1~LV5여야 함: '{value}'"), vstart, next);
+            }
+          } else if !op.is_empty() {
+            return err(format!("{field}: 비교 연산자는 lv:에서만 쓸 수 있음"), vstart, next);
+          }
+          out.push(Lexed {
+            tok: Tok::Field {
+              field,
+              op: op.to_string(),
+              value,
+            },
+            start,
+            end: next,
+          });
+          i = next;
+          continue;
+        }
+
+        // 필드가 아니면 ':'까지 포함해 한 단어("10:30")
+        while j < cs.len() && !is_word_end(cs[j]) {
+          j += 1;
+        }
+        let word: String = cs[i..j].iter().collect();
+        let tok = if word == "OR" { Tok::Or } else { Tok::Word(word) };
+        out.push(Lexed { tok, start, end: j });
+        i = j;
+      }
+    }
+  }
+
+  Ok(out)
+}
+
+/* -------------------- parser -------------------- */
+
+struct Parser {
+  toks: Vec<Lexed>,
+  at: usize,
+  len: usize,
+}
+
+impl Parser {
+  fn peek(&self) -> Option<&Lexed> {
+    self.toks.get(self.at)
+  }
+
+  fn parse_or(&mut self) -> Result<QueryNode, QueryError> {
+    let mut nodes = vec![self.parse_and()?];
+    while let Some(l) = self.peek() {
+      if l.tok != Tok::Or {
+        break;
+      }
+      let (s, e) = (l.start, l.end);
+      self.at += 1;
+      if matches!(self.peek().map(|l| &l.tok), None | Some(Tok::RParen) | Some(Tok::Or)) {
+        return err("OR 뒤에 조건이 없음", s, e);
+      }
+      nodes.push(self.parse_and()?);
+    }
+    Ok(if nodes.len() == 1 { nodes.remove(0) } else { QueryNode::Or { nodes } })
+  }
+
+  fn parse_and(&mut self) -> Result<QueryNode, QueryError> {
+    let mut nodes: Vec<QueryNode> = Vec::new();
+    while let Some(l) = self.peek() {
+      if matches!(l.tok, Tok::Or | Tok::RParen) {
+        break;
+      }
+      nodes.push(self.parse_unary()?);
+    }
+    if nodes.is_empty() {
+      return match self.peek() {
+        Some(l) if l.tok == Tok::Or => err("OR 앞에 조건이 없음", l.start, l.end),
+        Some(l) => err("괄호 안이 비어 있음", l.start, l.end),
+        None => err("조건이 없음", self.len, self.len),
+      };
+    }
+    Ok(if nodes.len() == 1 { nodes.remove(0) } else { QueryNode::And { nodes } })
+  }
+
+  fn parse_unary(&mut self) -> Result<QueryNode, QueryError> {
+    let l = self.peek().cloned().expect("parse_and checked");
+    if l.tok == Tok::Minus {
+      self.at += 1;
+      if matches!(self.peek().map(|l| &l.tok), None | Some(Tok::Or) | Some(Tok::RParen) | Some(Tok::Minus)) {
+        return err("'-' 뒤에 제외할 조건이 없음", l.start, l.end);
+      }
+      let node = self.parse_atom()?;
+      return Ok(QueryNode::Not { node: Box::new(node) });
+    }
+    self.parse_atom()
+  }
+
+  fn parse_atom(&mut self) -> Result<QueryNode, QueryError> {
+    let l = self.peek().cloned().expect("caller checked");
+    self.at += 1;
+    match l.tok {
+      Tok::Word(text) => Ok(QueryNode::Term { text }),
+      Tok::Quoted(text) => Ok(QueryNode::Phrase { text }),
+      Tok::Field { field, op, value } => Ok(QueryNode::Field { field, op, value }),
+      Tok::LParen => {
+        let inner = self.parse_or()?;
+        match self.peek() {
+          Some(r) if r.tok == Tok::RParen => {
+            self.at += 1;
+            Ok(inner)
+          }
+          _ => err("닫는 괄호가 없음", l.start, l.end),
+        }
+      }
+      Tok::RParen => err("여는 괄호가 없음", l.start, l.end),
+      Tok::Or => err("OR 앞에 조건이 없음", l.start, l.end),
+      Tok::Minus => err("'-' 뒤에 제외할 조건이 없음", l.start, l.end),
+    }
+  }
+}
+
+/// 빈 쿼리는 빈 And
+pub fn parse_query(q: &str) -> Result<QueryNode, QueryError> {
+  let toks = lex(q)?;
+  if toks.is_empty() {
+    return Ok(QueryNode::And { nodes: Vec::new() });
+  }
+  let mut p = Parser {
+    toks,
+    at: 0,
+    len: q.chars().count(),
+  };
+  let node = p.parse_or()?;
+  if let Some(l) = p.peek() {
+    // parse_or는 ')'에서만 멈춘다
+    return err("여는 괄호가 없음", l.start, l.end);
+  }
+  Ok(node)
+}
+
+/// 연산자 없이 단어만 있는 쿼리(예전 동작 그대로)
+pub fn is_plain(node: &QueryNode) -> bool {
+  match node {
+    QueryNode::Term { .. } => true,
+    QueryNode::And { nodes } => nodes.iter().all(|n| matches!(n, QueryNode::Term { .. })),
+    _ => false,
+  }
+}
+
+/* -------------------- evaluation -------------------- */
+
+/// 랭킹 한 번 동안 쓰는 평가 계획(단어 → 어간/동의어, 이름 → 별칭을 미리 계산)
+pub struct QueryPlan {
+  pub ast: QueryNode,
+  term_keys: HashMap<String, Vec<String>>,
+  name_aliases: HashMap<String, Vec<String>>,
+}
+
+/// 단어의 비교용 어간(토큰이 안 나오는 한 글자 단어는 정규화만)
+fn term_key(text: &str, tokenizer: &dyn Tokenizer) -> String {
+  tokenizer.tokenize(text).into_iter().next().unwrap_or_else(|| norm(text))
+}
+
+impl QueryPlan {
+  pub fn new(ast: QueryNode, tokenizer: &dyn Tokenizer, synonyms: Option<&SynonymTable>) -> Self {
+    let mut plan = QueryPlan {
+      ast,
+      term_keys: HashMap::new(),
+      name_aliases: HashMap::new(),
+    };
+    let mut stack: Vec<&QueryNode> = vec![&plan.ast];
+    while let Some(n) = stack.pop() {
+      match n {
+        QueryNode::Term { text } => {
+          let key = term_key(text, tokenizer);
+          let mut keys = vec![key.clone()];
+          keys.extend(synonyms.map(|s| s.term_alternatives(&key)).unwrap_or_default());
+          plan.term_keys.insert(text.clone(), keys);
+        }
+        QueryNode::Field { field, value, .. } if field == "actor" || field == "related" => {
+          let aliases = synonyms.map(|s| s.name_aliases(value)).unwrap_or_default();
+          plan.name_aliases.insert(value.clone(), aliases);
+        }
+        QueryNode::Not { node } => stack.push(node),
+        QueryNode::And { nodes } | QueryNode::Or { nodes } => stack.extend(nodes.iter()),
+        _ => {}
+      }
+    }
+    plan
+  }
+
+  /// 제외(-)가 아닌 단어/구의 토큰(키워드 유사도 계산용)
+  pub fn positive_tokens(&self, tokenizer: &dyn Tokenizer) -> Vec<String> {
+    fn walk(n: &QueryNode, tokenizer: &dyn Tokenizer, out: &mut Vec<String>) {
+      match n {
+        QueryNode::Term { text } => {
+          let k = term_key(text, tokenizer);
+          if !out.contains(&k) {
+            out.push(k);
+          }
+        }
+        QueryNode::Phrase { text } => {
+          for t in tokenizer.tokenize(text) {
+            if !out.contains(&t) {
+              out.push(t);
+            }
+          }
+        }
+        QueryNode::And { nodes } | QueryNode::Or { nodes } => nodes.iter().for_each(|c| walk(c, tokenizer, out)),
+        QueryNode::Not { .. } | QueryNode::Field { .. } => {}
+      }
+    }
+    let mut out = Vec::new();
+    walk(&self.ast, tokenizer, &mut out);
+    out
+  }
+
+  /// 제외가 아닌 조건이 하나라도 있는지(필드만 있는 쿼리도 "맞은 기록"을 후보로 올리기 위해)
+  pub fn has_positive(&self) -> bool {
+    fn walk(n: &QueryNode) -> bool {
+      match n {
+        QueryNode::Term { .. } | QueryNode::Phrase { .. } | QueryNode::Field { .. } => true,
+        QueryNode::Not { .. } => false,
+        QueryNode::And { nodes } | QueryNode::Or { nodes } => nodes.iter().any(walk),
+      }
+    }
+    walk(&self.ast)
+  }
+
+  fn actor_matches(&self, a: &ActorRef, value: &str) -> bool {
+    let (n, v) = (norm(&a.name), norm(value));
+    if n.is_empty() {
+      return false;
+    }
+    n == v || norm(&a.r#type) == v || self.name_aliases.get(value).is_some_and(|al| al.contains(&n))
+  }
+
+  fn eval(&self, n: &QueryNode, r: &RecordItem, summary: &str) -> bool {
+    match n {
+      QueryNode::Term { text } => self
+        .term_keys
+        .get(text)
+        .is_some_and(|keys| keys.iter().any(|k| summary.contains(k.as_str()))),
+      QueryNode::Phrase { text } => summary.contains(&norm(text)),
+      QueryNode::Field { field, op, value } => {
+        let v = norm(value);
+        match field.as_str() {
+          "place" => norm(&r.place) == v || norm(&r.place_other).contains(&v),
+          "store" => norm(&r.store_type) == v || norm(&r.store_other).contains(&v),
+          "lv" => match (lv_rank(&r.lv), lv_rank(&value.to_uppercase())) {
+            (Some(a), Some(b)) => match op.as_str() {
+              ">=" => a >= b,
+              "<=" => a <= b,
+              ">" => a > b,
+              "<" => a < b,
+              _ => a == b,
+            },
+            _ => false,
+          },
+          "actor" => self.actor_matches(&r.actor, value),
+          "related" => r.related.iter().any(|a| self.actor_matches(a, value)),
+          _ => false,
+        }
+      }
+      QueryNode::Not { node } => !self.eval(node, r, summary),
+      QueryNode::And { nodes } => nodes.iter().all(|c| self.eval(c, r, summary)),
+      QueryNode::Or { nodes } => nodes.iter().any(|c| self.eval(c, r, summary)),
+    }
+  }
+
+  /// summary는 norm()한 요약
+  pub fn matches(&self, r: &RecordItem, summary: &str) -> bool {
+    self.eval(&self.ast, r, summary)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn err_at(q: &str) -> (usize, usize) {
+    let e = parse_query(q).unwrap_err();
+    (e.start, e.end)
+  }
+
+  #[test]
+  fn parses_operators_and_fields() {
+    let n = parse_query(r#"폭언 "교실 밖" -사과 (욕설 OR 막말) lv:>=LV3"#).unwrap();
+    let QueryNode::And { nodes } = n else { panic!("and expected") };
+    assert_eq!(nodes.len(), 5);
+    assert!(matches!(&nodes[2], QueryNode::Not { .. }));
+    assert!(matches!(&nodes[3], QueryNode::Or { nodes } if nodes.len() == 2));
+    assert!(matches!(&nodes[4], QueryNode::Field { field, op, value } if field == "lv" && op == ">=" && value == "LV3"));
+    assert!(is_plain(&parse_query("폭언 교실").unwrap()));
+  }
+
+  #[test]
+  fn error_positions_are_char_offsets() {
+    // 한글도 한 글자 = 1
+    assert_eq!(err_at("(폭언"), (0, 1));
+    assert_eq!(err_at("폭언)"), (2, 3));
+    assert_eq!(err_at("폭언 OR"), (3, 5));
+    assert_eq!(err_at("OR 폭언"), (0, 2));
+    assert_eq!(err_at("폭언 lv:LV9"), (6, 9));
+    assert_eq!(err_at("place:"), (0, 6));
+    assert_eq!(err_at("폭언 \"교실"), (3, 6));
+  }
+}
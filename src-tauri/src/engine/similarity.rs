@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+
+use super::norm;
+use super::tokenize::is_word_char;
+
+/* -------------------- text similarity modes -------------------- */
+
+/// RankOpts.textMode로 고르는 키워드 유사도 계산 방식
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextMode {
+  /// 쿼리 토큰이 요약에 그대로 들어 있는 비율(기본)
+  Token,
+  /// 문자 2-gram 포함률: 띄어쓰기/오타/부분 합성어에 관대
+  Ngram,
+  /// 전체 기록 코퍼스 기준 BM25(드문 단어일수록 가중)
+  Bm25,
+}
+
+impl TextMode {
+  pub fn parse(name: Option<&str>) -> Result<Self, String> {
+    match name.map(|s| s.trim()).filter(|s| !s.is_empty()).unwrap_or("token") {
+      "token" => Ok(TextMode::Token),
+      "ngram" => Ok(TextMode::Ngram),
+      "bm25" => Ok(TextMode::Bm25),
+      other => Err(format!("unknown text mode: {other}")),
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      TextMode::Token => "token",
+      TextMode::Ngram => "ngram",
+      TextMode::Bm25 => "bm25",
+    }
+  }
+}
+
+/// (맞은 토큰 수, 전체 토큰 수, 유사도 0~1)
+pub fn text_similarity_stats(q_tokens: &[String], summary: &str) -> (usize, usize, f32) {
+  if q_tokens.is_empty() {
+    return (0, 0, 0.0);
+  }
+  let s = norm(summary);
+  let mut hit = 0usize;
+  for qt in q_tokens {
+    if s.contains(qt) {
+      hit += 1;
+    }
+  }
+  let total = q_tokens.len();
+  let ratio = hit as f32 / total as f32;
+  (hit, total, ratio)
+}
+
+/// 공백/기호를 빼고 이어 붙인 문자열의 2-gram 집합("상담 일지" == "상담일지")
+pub fn char_bigrams(s: &str) -> HashSet<(char, char)> {
+  let chars: Vec<char> = norm(s).chars().filter(|c| is_word_char(*c as u32)).collect();
+  chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+/// 토큰 하나가 요약 2-gram에 얼마나 들어 있는지(0~1)
+fn coverage(q: &HashSet<(char, char)>, s: &HashSet<(char, char)>) -> f32 {
+  if q.is_empty() {
+    return 0.0;
+  }
+  q.intersection(s).count() as f32 / q.len() as f32
+}
+
+/// 토큰별 2-gram 포함률이 이 값 이상이면 q_hit로 센다
+pub const NGRAM_TOKEN_HIT: f32 = 0.5;
+
+/// ngram 모드: 유사도 = 쿼리 전체 2-gram 중 요약에 있는 비율, q_hit = 포함률 0.5 이상인 토큰 수.
+/// 한 글자 토큰("칼")은 2-gram이 없으니 요약에 그대로 있는지로 보고 한 단위로 센다.
+pub fn ngram_similarity_stats(q_tokens: &[String], summary: &str) -> (usize, usize, f32) {
+  if q_tokens.is_empty() {
+    return (0, 0, 0.0);
+  }
+  let s = char_bigrams(summary);
+  let s_text: String = norm(summary).chars().filter(|c| is_word_char(*c as u32)).collect();
+
+  let mut q_all: HashSet<(char, char)> = HashSet::new();
+  let mut hit = 0usize;
+  let (mut short_total, mut short_hit) = (0usize, 0usize);
+  for qt in q_tokens {
+    let g = char_bigrams(qt);
+    if g.is_empty() {
+      let c: String = norm(qt).chars().filter(|c| is_word_char(*c as u32)).collect();
+      if c.is_empty() {
+        continue;
+      }
+      short_total += 1;
+      if s_text.contains(&c) {
+        hit += 1;
+        short_hit += 1;
+      }
+      continue;
+    }
+    if coverage(&g, &s) >= NGRAM_TOKEN_HIT {
+      hit += 1;
+    }
+    q_all.extend(g);
+  }
+
+  // 쿼리 토큰 사이 경계도 2-gram으로 보면 "상담 일지" ↔ "상담일지"가 더 잘 맞는다
+  q_all.extend(char_bigrams(&q_tokens.join("")));
+
+  let units = q_all.len() + short_total;
+  let sim = if units == 0 {
+    0.0
+  } else {
+    (q_all.intersection(&s).count() + short_hit) as f32 / units as f32
+  };
+  (hit, q_tokens.len(), sim)
+}
+
+/// 코퍼스가 필요 없는 모드용(Bm25는 CorpusIndex::score를 쓴다)
+pub fn similarity_stats(mode: TextMode, q_tokens: &[String], summary: &str) -> (usize, usize, f32) {
+  match mode {
+    TextMode::Token | TextMode::Bm25 => text_similarity_stats(q_tokens, summary),
+    TextMode::Ngram => ngram_similarity_stats(q_tokens, summary),
+  }
+}
+
+/* -------------------- phrase / proximity -------------------- */
+
+/// 두 토큰 사이(공백 제외) 글자 수가 이 이하면 붙어 있는 구로 본다("수업을 방해" = 조사 한 글자)
+pub const PHRASE_GAP: usize = 2;
+/// 이 글자 수(공백 제외) 안에 있으면 근접 가산(RankOpts.proximityWindow로 바꿀 수 있다)
+pub const PROXIMITY_WINDOW: usize = 12;
+
+/// 토큰이 나오는 (시작, 끝) 위치. 위치는 공백을 뺀 글자 기준
+fn occurrences(chars: &[char], tok: &[char]) -> Vec<(usize, usize)> {
+  if tok.is_empty() || tok.len() > chars.len() {
+    return Vec::new();
+  }
+  (0..=chars.len() - tok.len())
+    .filter(|&i| chars[i..i + tok.len()] == *tok)
+    .map(|i| (i, i + tok.len()))
+    .collect()
+}
+
+/// 쿼리에서 이웃한 토큰 쌍이 요약에서 얼마나 가까이 나오는지(0~1, 쌍 평균).
+/// 순서대로 붙어 있으면 1, window 안이면 거리에 따라 줄고(역순은 더 낮게), 멀거나 없으면 0.
+/// 토큰이 하나뿐이면 0.
+pub fn proximity_stats(q_tokens: &[String], summary: &str, window: usize) -> f32 {
+  if q_tokens.len() < 2 {
+    return 0.0;
+  }
+  let s: Vec<char> = norm(summary).chars().filter(|c| !c.is_whitespace()).collect();
+  let occ: Vec<Vec<(usize, usize)>> = q_tokens
+    .iter()
+    .map(|t| {
+      let t: Vec<char> = t.chars().filter(|c| !c.is_whitespace()).collect();
+      occurrences(&s, &t)
+    })
+    .collect();
+
+  let mut total = 0.0f32;
+  for pair in occ.windows(2) {
+    let mut best = 0.0f32;
+    for &(a0, a1) in &pair[0] {
+      for &(b0, b1) in &pair[1] {
+        let (gap, in_order) = if b0 >= a1 {
+          (b0 - a1, true)
+        } else if a0 >= b1 {
+          (a0 - b1, false)
+        } else {
+          continue; // 겹침(한 토큰이 다른 토큰 안에 있음)
+        };
+        let v = if in_order && gap <= PHRASE_GAP {
+          1.0
+        } else if gap <= window {
+          let decay = 1.0 - gap as f32 / (window as f32 + 1.0);
+          decay * if in_order { 0.7 } else { 0.5 }
+        } else {
+          0.0
+        };
+        best = best.max(v);
+      }
+    }
+    total += best;
+  }
+  total / (q_tokens.len() - 1) as f32
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn toks(q: &[&str]) -> Vec<String> {
+    q.iter().map(|t| t.to_string()).collect()
+  }
+
+  #[test]
+  fn ngram_ignores_spacing_and_small_typos() {
+    assert_eq!(ngram_similarity_stats(&toks(&["상담", "일지"]), "오늘 상담일지 작성"), (2, 2, 1.0));
+    let (hit, total, sim) = ngram_similarity_stats(&toks(&["수업방해"]), "수업 방헤 행동");
+    assert_eq!((hit, total), (1, 1));
+    assert!((sim - 2.0 / 3.0).abs() < 1e-6);
+    assert_eq!(ngram_similarity_stats(&toks(&["급식"]), "복도에서 다툼"), (0, 1, 0.0));
+  }
+
+  #[test]
+  fn ngram_single_syllable_tokens_match_by_substring() {
+    assert_eq!(ngram_similarity_stats(&toks(&["칼"]), "칼을 들고 위협"), (1, 1, 1.0));
+    assert_eq!(ngram_similarity_stats(&toks(&["칼"]), "가위로 위협"), (0, 1, 0.0));
+    let (hit, _, sim) = ngram_similarity_stats(&toks(&["칼", "위협"]), "칼로 위협함");
+    assert_eq!(hit, 2);
+    assert!(sim > NGRAM_TOKEN_HIT);
+  }
+
+  #[test]
+  fn proximity_prefers_adjacent_then_in_order_then_reversed() {
+    let q = toks(&["수업", "방해"]);
+    let adjacent = proximity_stats(&q, "학생이 수업을 방해하여 지도함", PROXIMITY_WINDOW);
+    let in_order = proximity_stats(&q, "수업 중 계속 방해", PROXIMITY_WINDOW);
+    let reversed = proximity_stats(&q, "방해 행동이 수업 내내", PROXIMITY_WINDOW);
+    let far = proximity_stats(&q, "수업 시작 전에 떠들었다. 이후 쉬는 시간에 친구들을 방해함", PROXIMITY_WINDOW);
+    assert_eq!(adjacent, 1.0);
+    assert!(1.0 > in_order && in_order > reversed && reversed > 0.0, "{in_order} {reversed}");
+    assert_eq!(far, 0.0);
+    assert_eq!(proximity_stats(&toks(&["수업"]), "수업 방해", PROXIMITY_WINDOW), 0.0);
+    assert_eq!(proximity_stats(&q, "수업만 있음", PROXIMITY_WINDOW), 0.0);
+  }
+
+  #[test]
+  fn text_mode_names_round_trip() {
+    assert_eq!(TextMode::parse(None).unwrap(), TextMode::Token);
+    for m in [TextMode::Token, TextMode::Ngram, TextMode::Bm25] {
+      assert_eq!(TextMode::parse(Some(m.name())).unwrap(), m);
+    }
+    assert!(TextMode::parse(Some("fuzzy")).is_err());
+  }
+}
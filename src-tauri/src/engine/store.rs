@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use super::bm25::CorpusIndex;
+use super::synonym::SynonymTable;
+use super::tokenize::{tokenizer_by_name, Tokenizer};
+use super::{rank_records_with, CaseItem, RankContext, RankOpts, RankedHit, RecordItem};
+
+/* -------------------- record store (Tauri managed state) -------------------- */
+
+/// Rust 쪽에 상주하는 기록 사본 + 역색인.
+/// 프런트는 기록이 바뀔 때만 upsert/delete를 보내고, 랭킹은 케이스만 넘겨 id 기준으로 돌린다.
+pub struct RecordStore {
+  records: Vec<RecordItem>,
+  /// id → records 위치
+  pos: HashMap<String, usize>,
+  index: CorpusIndex,
+  tokenizer: Box<dyn Tokenizer>,
+}
+
+impl Default for RecordStore {
+  fn default() -> Self {
+    let tokenizer = tokenizer_by_name(None).expect("default tokenizer");
+    Self {
+      records: Vec::new(),
+      pos: HashMap::new(),
+      index: CorpusIndex::build(&[], tokenizer.as_ref()),
+      tokenizer,
+    }
+  }
+}
+
+impl RecordStore {
+  pub fn len(&self) -> usize {
+    self.records.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.records.is_empty()
+  }
+
+  /// 전체 교체(앱 시작/백업 복원 직후). 같은 id가 여러 번 오면 upsert처럼 뒤의 것이 이긴다.
+  pub fn replace_all(&mut self, records: Vec<RecordItem>) {
+    let mut pos: HashMap<String, usize> = HashMap::with_capacity(records.len());
+    let mut deduped: Vec<RecordItem> = Vec::with_capacity(records.len());
+    for r in records {
+      match pos.get(&r.id) {
+        Some(&i) => deduped[i] = r,
+        None => {
+          pos.insert(r.id.clone(), deduped.len());
+          deduped.push(r);
+        }
+      }
+    }
+    self.index = CorpusIndex::build(&deduped, self.tokenizer.as_ref());
+    self.pos = pos;
+    self.records = deduped;
+  }
+
+  /// 같은 id면 덮어쓰고 요약이 바뀐 경우만 다시 색인한다
+  pub fn upsert(&mut self, records: Vec<RecordItem>) {
+    for r in records {
+      match self.pos.get(&r.id) {
+        Some(&i) => {
+          if self.records[i].summary != r.summary {
+            self.index.upsert(&r.id, &r.summary, self.tokenizer.as_ref());
+          }
+          self.records[i] = r;
+        }
+        None => {
+          self.index.upsert(&r.id, &r.summary, self.tokenizer.as_ref());
+          self.pos.insert(r.id.clone(), self.records.len());
+          self.records.push(r);
+        }
+      }
+    }
+  }
+
+  /// 지운 개수를 돌려준다(없는 id는 무시)
+  pub fn delete(&mut self, ids: &[String]) -> usize {
+    let mut removed = 0;
+    for id in ids {
+      let Some(i) = self.pos.remove(id) else {
+        continue;
+      };
+      self.index.remove(id);
+      self.records.swap_remove(i);
+      if let Some(moved) = self.records.get(i) {
+        self.pos.insert(moved.id.clone(), i);
+      }
+      removed += 1;
+    }
+    removed
+  }
+
+  pub fn get(&self, id: &str) -> Option<&RecordItem> {
+    self.pos.get(id).map(|&i| &self.records[i])
+  }
+
+  pub fn records(&self) -> &[RecordItem] {
+    &self.records
+  }
+
+  /// 색인은 기록 배열 전송(IPC)과 bm25 코퍼스 재색인만 덜어 준다. 후보를 postings로 줄이지는 않는다:
+  /// 주요 당사자 기록은 본문이 쿼리와 겹치지 않아도 올라오고, 2차 관계 집계와 시간 기준도 모든 기록을 본다.
+  pub fn rank(
+    &self,
+    case_item: &CaseItem,
+    opts: Option<RankOpts>,
+    synonyms: Option<&SynonymTable>,
+  ) -> Result<Vec<RankedHit>, String> {
+    let ctx = RankContext {
+      index: Some(&self.index),
+      synonyms,
+    };
+    rank_records_with(&self.records, case_item, opts, ctx)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::test_util::record;
+  use serde_json::json;
+
+  fn rec(id: &str, summary: &str) -> RecordItem {
+    record(id, summary, json!({}))
+  }
+
+  #[test]
+  fn replace_all_keeps_the_last_duplicate() {
+    let mut store = RecordStore::default();
+    store.replace_all(vec![rec("a", "수업 중 폭언"), rec("b", "급식 메뉴"), rec("a", "복도에서 다툼")]);
+    assert_eq!(store.len(), 2);
+    assert_eq!(store.get("a").unwrap().summary, "복도에서 다툼");
+    assert_eq!(store.index.doc_count(), 2);
+    assert_eq!(store.index.df("폭언"), 0);
+
+    // 지우면 사본 없이 깨끗이 사라진다
+    assert_eq!(store.delete(&["a".to_string()]), 1);
+    assert!(store.get("a").is_none());
+    assert_eq!(store.records().len(), 1);
+  }
+}
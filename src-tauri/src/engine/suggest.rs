@@ -0,0 +1,307 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::advisor::fnv1a64;
+use super::tokenize::tokenizer_by_name;
+use super::{actor_key, time, ActorRef, CaseItem, RecordItem};
+
+/* -------------------- case suggestion (record clustering) -------------------- */
+
+/// 링크 점수 = 당사자 겹침 0.45 + 요약 토큰 겹침 0.35 + 시간 근접 0.2
+const W_PEOPLE: f32 = 0.45;
+const W_TEXT: f32 = 0.35;
+const W_TIME: f32 = 0.2;
+/// 요약 토큰 겹침(jaccard)이 이보다 낮으면 같은 사람이어도 잇지 않는다
+/// (한 학생의 무관한 기록들이 single-link로 한 묶음이 되지 않게)
+const MIN_TEXT_OVERLAP: f32 = 0.2;
+/// 이미 있는 케이스가 묶음 기록의 이 비율 이상을 갖고 있으면 제안하지 않는다
+const COVERED_RATIO: f32 = 0.8;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestOpts {
+  /// 제안할 최소 기록 수(기본 3)
+  #[serde(default)]
+  pub min_records: Option<usize>,
+  /// 두 기록을 잇는 링크 점수 하한(0~1, 기본 0.4)
+  #[serde(default)]
+  pub link_threshold: Option<f32>,
+  /// 이 일수보다 떨어진 기록끼리는 잇지 않는다(기본 30). 시간 근접 반감기는 그 절반.
+  #[serde(default)]
+  pub window_days: Option<f32>,
+  /// 최대 제안 수(기본 10)
+  #[serde(default)]
+  pub max_suggestions: Option<usize>,
+  #[serde(default)]
+  pub tokenizer: Option<String>,
+  #[serde(default, alias = "tz")]
+  pub time_zone: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaseSuggestion {
+  /// 초안(id는 "SUG_…", createdAt은 비어 있음 — 채택할 때 새로 붙인다)
+  pub case_item: CaseItem,
+  /// 시간순
+  pub record_ids: Vec<String>,
+  /// 0~1: 묶음 안 링크 점수 평균 × (1 - 1/기록 수)
+  pub confidence: f32,
+  pub reasons: Vec<String>,
+}
+
+/// 기록 하나의 비교용 특징
+struct Feat<'a> {
+  rec: &'a RecordItem,
+  t: DateTime<Utc>,
+  people: HashSet<String>,
+  tokens: HashSet<String>,
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+  if a.is_empty() || b.is_empty() {
+    return 0.0;
+  }
+  let inter = a.intersection(b).count();
+  inter as f32 / (a.len() + b.len() - inter) as f32
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+  let mut r = i;
+  while parent[r] != r {
+    r = parent[r];
+  }
+  let mut c = i;
+  while parent[c] != r {
+    let next = parent[c];
+    parent[c] = r;
+    c = next;
+  }
+  r
+}
+
+fn local_date(t: DateTime<Utc>, tz: Tz) -> String {
+  t.with_timezone(&tz).format("%Y-%m-%d").to_string()
+}
+
+pub fn suggest_cases(records: &[RecordItem], cases: &[CaseItem], opts: &SuggestOpts) -> Result<Vec<CaseSuggestion>, String> {
+  let tz = time::resolve_tz(opts.time_zone.as_deref())?;
+  let tokenizer = tokenizer_by_name(opts.tokenizer.as_deref())?;
+  let min_records = opts.min_records.unwrap_or(3).max(2);
+  let threshold = opts.link_threshold.unwrap_or(0.4).clamp(0.0, 1.0);
+  let window = opts.window_days.filter(|d| *d > 0.0).unwrap_or(30.0);
+  let half_life = window / 2.0;
+  let max_out = opts.max_suggestions.unwrap_or(10);
+
+  // 일시를 해석할 수 없는 기록은 묶지 않는다
+  let mut feats: Vec<Feat> = records
+    .iter()
+    .filter_map(|r| {
+      let t = time::parse_ts_in(&r.ts, tz).ok()?;
+      let people = std::iter::once(&r.actor).chain(r.related.iter()).filter_map(actor_key).collect();
+      let tokens = tokenizer.tokenize(&r.summary).into_iter().collect();
+      Some(Feat { rec: r, t, people, tokens })
+    })
+    .collect();
+  feats.sort_by(|a, b| a.t.cmp(&b.t).then_with(|| a.rec.id.cmp(&b.rec.id)));
+
+  // 시간순으로 window 안의 쌍만 비교(single-link)
+  let n = feats.len();
+  let mut parent: Vec<usize> = (0..n).collect();
+  let mut links: Vec<(usize, usize, f32)> = Vec::new();
+  for i in 0..n {
+    for j in i + 1..n {
+      let days = (feats[j].t - feats[i].t).num_minutes() as f32 / 1440.0;
+      if days > window {
+        break;
+      }
+      let p = jaccard(&feats[i].people, &feats[j].people);
+      let x = jaccard(&feats[i].tokens, &feats[j].tokens);
+      // 시간만 가깝거나 사람만 겹치는 건 묶음 근거가 아님
+      if x < MIN_TEXT_OVERLAP {
+        continue;
+      }
+      let score = W_PEOPLE * p + W_TEXT * x + W_TIME * 0.5f32.powf(days / half_life);
+      if score >= threshold {
+        links.push((i, j, score));
+        let (ri, rj) = (find(&mut parent, i), find(&mut parent, j));
+        if ri != rj {
+          parent[rj] = ri;
+        }
+      }
+    }
+  }
+
+  let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+  for i in 0..n {
+    let r = find(&mut parent, i);
+    groups.entry(r).or_default().push(i);
+  }
+  let mut link_sum: HashMap<usize, (f32, u32)> = HashMap::new();
+  for (i, _, s) in &links {
+    let e = link_sum.entry(find(&mut parent, *i)).or_insert((0.0, 0));
+    e.0 += s;
+    e.1 += 1;
+  }
+
+  let mut out: Vec<CaseSuggestion> = Vec::new();
+  for (root, members) in groups {
+    if members.len() < min_records {
+      continue;
+    }
+    let ids: Vec<String> = members.iter().map(|&i| feats[i].rec.id.clone()).collect();
+
+    let covered = cases.iter().any(|c| {
+      let have = ids.iter().filter(|id| c.record_ids.contains(id)).count();
+      have as f32 >= ids.len() as f32 * COVERED_RATIO
+    });
+    if covered {
+      continue;
+    }
+
+    let size = members.len();
+
+    // 당사자: 절반 이상 기록에 나온 사람(최대 4명), 주 당사자로 가장 많이 나온 사람이 먼저
+    let mut people: HashMap<String, (ActorRef, usize, usize)> = HashMap::new();
+    for &i in &members {
+      let r = feats[i].rec;
+      for (a, is_actor) in std::iter::once((&r.actor, true)).chain(r.related.iter().map(|a| (a, false))) {
+        let Some(k) = actor_key(a) else {
+          continue;
+        };
+        let e = people.entry(k).or_insert((a.clone(), 0, 0));
+        e.1 += 1;
+        if is_actor {
+          e.2 += 1;
+        }
+      }
+    }
+    let mut people: Vec<(ActorRef, usize, usize)> = people.into_values().collect();
+    people.sort_by(|a, b| b.2.cmp(&a.2).then(b.1.cmp(&a.1)).then_with(|| a.0.name.cmp(&b.0.name)));
+    // 아무도 절반에 못 미치면(같은 사건에 여러 학생 등) 당사자 없이 키워드 묶음으로 제안
+    let actors: Vec<(ActorRef, usize)> = people
+      .iter()
+      .filter(|(_, c, _)| *c * 2 >= size)
+      .take(4)
+      .map(|(a, c, _)| (a.clone(), *c))
+      .collect();
+
+    // 키워드: 40% 이상(최소 2건) 기록에 나온 토큰, 많이 나온 순 최대 5개
+    let mut df: HashMap<&str, usize> = HashMap::new();
+    for &i in &members {
+      for t in &feats[i].tokens {
+        *df.entry(t.as_str()).or_insert(0) += 1;
+      }
+    }
+    let min_df = ((size as f32 * 0.4).ceil() as usize).max(2);
+    let mut keywords: Vec<(&str, usize)> = df.into_iter().filter(|(_, c)| *c >= min_df).collect();
+    keywords.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    keywords.truncate(5);
+
+    let (first, last) = (feats[members[0]].t, feats[members[size - 1]].t);
+    let (time_from, time_to) = (local_date(first, tz), local_date(last, tz));
+
+    let head = actors.first().map(|(a, _)| a.name.trim().to_string()).unwrap_or_default();
+    let kw: Vec<&str> = keywords.iter().map(|(k, _)| *k).collect();
+    let topic = if kw.is_empty() { "기록 묶음".to_string() } else { kw.iter().take(2).copied().collect::<Vec<_>>().join(", ") };
+    let title = if head.is_empty() {
+      format!("{topic} ({size}건)")
+    } else {
+      format!("{head} · {topic} ({size}건)")
+    };
+
+    let (sum, cnt) = link_sum.get(&root).copied().unwrap_or((0.0, 0));
+    let avg_link = if cnt > 0 { sum / cnt as f32 } else { 0.0 };
+    let confidence = (avg_link * (1.0 - 1.0 / size as f32)).clamp(0.0, 1.0);
+
+    let mut reasons: Vec<String> = Vec::new();
+    if !actors.is_empty() {
+      let list: Vec<String> = actors
+        .iter()
+        .map(|(a, c)| match a.r#type.trim() {
+          "" => format!("{} {}/{}건", a.name.trim(), c, size),
+          t => format!("{}({}) {}/{}건", a.name.trim(), t, c, size),
+        })
+        .collect();
+      reasons.push(format!("공통 당사자: {}", list.join(", ")));
+    }
+    if !keywords.is_empty() {
+      let list: Vec<String> = keywords.iter().map(|(k, c)| format!("{k} {c}건")).collect();
+      reasons.push(format!("공통 키워드: {}", list.join(", ")));
+    }
+    reasons.push(format!(
+      "기간: {} ~ {} ({:.0}일)",
+      time_from,
+      time_to,
+      (last - first).num_minutes() as f32 / 1440.0
+    ));
+
+    let id_refs: Vec<&str> = ids.iter().map(|s| s.as_str()).collect();
+    let case_item = CaseItem {
+      id: format!("SUG_{:016x}", fnv1a64(&id_refs)),
+      title,
+      query: kw.join(" "),
+      time_from,
+      time_to,
+      max_results: None,
+      actors: actors.into_iter().map(|(a, _)| a).collect(),
+      only_main_actor: false,
+      sens_filter: "any".into(),
+      created_at: String::new(),
+      record_ids: ids.clone(),
+      advisors: Vec::new(),
+    };
+
+    out.push(CaseSuggestion {
+      case_item,
+      record_ids: ids,
+      confidence,
+      reasons,
+    });
+  }
+
+  out.sort_by(|a, b| {
+    b.confidence
+      .partial_cmp(&a.confidence)
+      .unwrap_or(std::cmp::Ordering::Equal)
+      .then_with(|| a.case_item.id.cmp(&b.case_item.id))
+  });
+  out.truncate(max_out);
+  Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::engine::test_util::{record, student};
+  use serde_json::json;
+
+  fn rec(id: &str, ts: &str, actor: &str, summary: &str) -> RecordItem {
+    record(id, summary, json!({"ts": ts, "actor": student(actor)}))
+  }
+
+  #[test]
+  fn shared_people_without_text_overlap_do_not_link() {
+    let records = vec![
+      rec("a", "2025-03-01T09:00", "김철수", "급식 메뉴 문의"),
+      rec("b", "2025-03-02T09:00", "김철수", "체육복 분실"),
+      rec("c", "2025-03-03T09:00", "김철수", "현장학습 동의서 제출"),
+    ];
+    assert!(suggest_cases(&records, &[], &SuggestOpts::default()).unwrap().is_empty());
+  }
+
+  #[test]
+  fn shared_people_with_text_overlap_are_suggested() {
+    let records = vec![
+      rec("a", "2025-03-01T09:00", "김철수", "수업 중 폭언"),
+      rec("b", "2025-03-02T09:00", "김철수", "쉬는 시간 폭언"),
+      rec("c", "2025-03-03T09:00", "김철수", "복도 폭언"),
+      rec("d", "2025-03-03T10:00", "김철수", "체육복 분실"),
+    ];
+    let out = suggest_cases(&records, &[], &SuggestOpts::default()).unwrap();
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0].record_ids, vec!["a", "b", "c"]);
+  }
+}
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::fsutil::write_atomic;
+use super::norm;
+
+/* -------------------- synonym / alias table (AppDataDir) -------------------- */
+
+pub const SYNONYM_FILE: &str = "synonyms.json";
+
+/// 같은 묶음 안의 말은 서로 바꿔 써도 같은 뜻으로 본다.
+/// - terms: 쿼리 키워드("폭언" ↔ "욕설" ↔ "막말")
+/// - names: 당사자 이름 별칭("4번 모" ↔ "학생4 어머니")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SynonymTable {
+  #[serde(default = "table_version")]
+  pub version: u32,
+  #[serde(default)]
+  pub terms: Vec<Vec<String>>,
+  #[serde(default)]
+  pub names: Vec<Vec<String>>,
+}
+
+fn table_version() -> u32 {
+  1
+}
+
+/// 파일이 없을 때 쓰는 기본 키워드 묶음(이름 별칭은 학교마다 달라 비워 둔다)
+const DEFAULT_TERMS: &[&[&str]] = &[
+  &["폭언", "욕설", "막말"],
+  &["민원", "항의"],
+  &["폭행", "구타", "때림"],
+  &["협박", "위협"],
+  &["따돌림", "왕따"],
+];
+
+impl Default for SynonymTable {
+  fn default() -> Self {
+    Self {
+      version: table_version(),
+      terms: DEFAULT_TERMS
+        .iter()
+        .map(|g| g.iter().map(|s| s.to_string()).collect())
+        .collect(),
+      names: Vec::new(),
+    }
+  }
+}
+
+/// 묶음에 key가 있으면 나머지 말들(정규화)을 돌려준다
+fn others(groups: &[Vec<String>], key: &str) -> Vec<String> {
+  let key = norm(key);
+  let mut out: Vec<String> = Vec::new();
+  if key.is_empty() {
+    return out;
+  }
+  for g in groups {
+    let g: Vec<String> = g.iter().map(|s| norm(s)).filter(|s| !s.is_empty()).collect();
+    if !g.contains(&key) {
+      continue;
+    }
+    for s in g {
+      if s != key && !out.contains(&s) {
+        out.push(s);
+      }
+    }
+  }
+  out
+}
+
+impl SynonymTable {
+  /// 쿼리 토큰의 동의어(자기 자신 제외)
+  pub fn term_alternatives(&self, token: &str) -> Vec<String> {
+    others(&self.terms, token)
+  }
+
+  /// 이름의 별칭(자기 자신 제외)
+  pub fn name_aliases(&self, name: &str) -> Vec<String> {
+    others(&self.names, name)
+  }
+
+  /// 빈 말/한 단어짜리 묶음/여러 묶음에 겹친 말을 거른다
+  pub fn validate(&self) -> Result<(), String> {
+    for (kind, groups) in [("terms", &self.terms), ("names", &self.names)] {
+      let mut seen: HashMap<String, usize> = HashMap::new();
+      for (i, g) in groups.iter().enumerate() {
+        let g: Vec<String> = g.iter().map(|s| norm(s)).collect();
+        if g.iter().any(|s| s.is_empty()) {
+          return Err(format!("{kind}[{i}]: 빈 항목이 있음"));
+        }
+        if g.len() < 2 {
+          return Err(format!("{kind}[{i}]: 두 개 이상이어야 함"));
+        }
+        for s in g {
+          if let Some(first) = seen.get(&s) {
+            if *first != i {
+              return Err(format!("{kind}[{i}]: '{s}'가 {kind}[{first}]에도 있음"));
+            }
+          }
+          seen.insert(s, i);
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+pub fn load_synonyms(app_data_dir: &Path) -> Result<SynonymTable, String> {
+  let path = app_data_dir.join(SYNONYM_FILE);
+  if !path.exists() {
+    return Ok(SynonymTable::default());
+  }
+  let text = std::fs::read_to_string(&path).map_err(|e| format!("synonyms read failed: {e}"))?;
+  serde_json::from_str(&text).map_err(|e| format!("synonyms parse failed ({}): {e}", path.display()))
+}
+
+/// 랭킹에 쓰는 표 + 파일이 깨져 기본 표로 대신했을 때의 경고(synonyms_get이 돌려준다)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedSynonyms {
+  #[serde(flatten)]
+  pub table: SynonymTable,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub warnings: Vec<String>,
+}
+
+/// 파일이 깨졌으면 기본 표로 랭킹하고 경고를 남긴다(랭킹 자체가 막히지 않게)
+pub fn load_synonyms_or_default(app_data_dir: &Path) -> LoadedSynonyms {
+  match load_synonyms(app_data_dir) {
+    Ok(table) => LoadedSynonyms {
+      table,
+      warnings: Vec::new(),
+    },
+    Err(e) => LoadedSynonyms {
+      table: SynonymTable::default(),
+      warnings: vec![format!("동의어 표를 읽지 못해 기본 표를 썼어요: {e}")],
+    },
+  }
+}
+
+pub fn save_synonyms(app_data_dir: &Path, table: &SynonymTable) -> Result<(), String> {
+  table.validate()?;
+  let text = serde_json::to_string_pretty(table).map_err(|e| format!("synonyms encode failed: {e}"))?;
+  write_atomic(&app_data_dir.join(SYNONYM_FILE), text.as_bytes()).map_err(|e| format!("synonyms write failed: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn corrupt_file_falls_back_to_default_table() {
+    let dir = std::env::temp_dir().join(format!("syn-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(SYNONYM_FILE), "{ not json").unwrap();
+    assert!(load_synonyms(&dir).is_err());
+    let loaded = load_synonyms_or_default(&dir);
+    assert_eq!(loaded.table.term_alternatives("폭언"), vec!["욕설", "막말"]);
+    assert_eq!(loaded.warnings.len(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn saved_table_round_trips() {
+    let dir = std::env::temp_dir().join(format!("syn-test-save-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let table = SynonymTable {
+      names: vec![vec!["4번 모".into(), "학생4 어머니".into()]],
+      ..SynonymTable::default()
+    };
+    save_synonyms(&dir, &table).unwrap();
+    let loaded = load_synonyms_or_default(&dir);
+    assert_eq!(loaded.table.name_aliases("4번 모"), vec!["학생4 어머니"]);
+    assert!(loaded.warnings.is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+}
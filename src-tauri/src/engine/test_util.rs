@@ -0,0 +1,42 @@
+//! 테스트 픽스처. 기록/케이스 JSON을 기본값으로 채우고 테스트마다 필요한 필드만 patch로 덮어쓴다.
+
+use serde_json::{json, Value};
+
+use super::{CaseItem, RecordItem};
+
+fn merge(mut base: Value, patch: Value) -> Value {
+  if let Value::Object(p) = patch {
+    for (k, v) in p {
+      base[k] = v;
+    }
+  }
+  base
+}
+
+/// {"type": "학생", "name": name}
+pub fn student(name: &str) -> Value {
+  json!({"type": "학생", "name": name})
+}
+
+/// 학생 김철수가 교실에서 남긴 LV2 문서 기록(2025-03-01T09:00:00Z)
+pub fn record_json(id: &str, summary: &str, patch: Value) -> Value {
+  let base = json!({
+    "id": id, "ts": "2025-03-01T09:00:00Z", "storeType": "문서", "storeOther": "", "lv": "LV2",
+    "actor": student("김철수"), "related": [],
+    "place": "교실", "placeOther": "", "summary": summary,
+  });
+  merge(base, patch)
+}
+
+pub fn record(id: &str, summary: &str, patch: Value) -> RecordItem {
+  serde_json::from_value(record_json(id, summary, patch)).unwrap()
+}
+
+/// 제목만 있는 케이스
+pub fn case_json(id: &str, patch: Value) -> Value {
+  merge(json!({"id": id, "title": "t"}), patch)
+}
+
+pub fn case(id: &str, patch: Value) -> CaseItem {
+  serde_json::from_value(case_json(id, patch)).unwrap()
+}
@@ -0,0 +1,176 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/* -------------------- timestamp parsing -------------------- */
+
+/// 오프셋 없는 값(datetime-local, 날짜만)을 해석할 기본 지역 시간대
+pub const DEFAULT_TZ: Tz = chrono_tz::Asia::Seoul;
+
+/// "Asia/Seoul" 같은 IANA 이름 → Tz (비어 있으면 기본값)
+pub fn resolve_tz(name: Option<&str>) -> Result<Tz, String> {
+  match name.map(|s| s.trim()).filter(|s| !s.is_empty()) {
+    None => Ok(DEFAULT_TZ),
+    Some(n) => n.parse::<Tz>().map_err(|_| format!("unknown time zone: {n}")),
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parsed {
+  /// 시각까지 있는 값(UTC로 정규화)
+  Instant(DateTime<Utc>),
+  /// 날짜만 있는 값(지역 날짜)
+  Day(NaiveDate),
+}
+
+fn local_to_utc(naive: NaiveDateTime, tz: Tz) -> Option<DateTime<Utc>> {
+  tz.from_local_datetime(&naive).earliest().map(|t| t.with_timezone(&Utc))
+}
+
+/// RFC3339(Z/+09:00), 오프셋 없는 datetime-local("2025-03-02T09:30"), 날짜만("2025-03-02")을 받는다.
+pub fn parse_any(s: &str, tz: Tz) -> Result<Parsed, String> {
+  let t = s.trim();
+  if t.is_empty() {
+    return Err("empty timestamp".into());
+  }
+
+  // "2025-03-02 09:30:00" 처럼 공백 구분도 허용
+  let t: String = if t.len() > 10 && t.as_bytes()[10] == b' ' {
+    format!("{}T{}", &t[..10], &t[11..])
+  } else {
+    t.to_string()
+  };
+
+  if let Ok(dt) = DateTime::parse_from_rfc3339(&t) {
+    return Ok(Parsed::Instant(dt.with_timezone(&Utc)));
+  }
+
+  for f in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M"] {
+    if let Ok(naive) = NaiveDateTime::parse_from_str(&t, f) {
+      return local_to_utc(naive, tz)
+        .map(Parsed::Instant)
+        .ok_or_else(|| format!("nonexistent local time: {s}"));
+    }
+  }
+
+  if let Ok(d) = NaiveDate::parse_from_str(&t, "%Y-%m-%d") {
+    return Ok(Parsed::Day(d));
+  }
+
+  Err(format!("invalid timestamp: {s}"))
+}
+
+fn day_start(d: NaiveDate, tz: Tz) -> Option<DateTime<Utc>> {
+  local_to_utc(d.and_hms_opt(0, 0, 0)?, tz)
+}
+
+/// 시각 하나로 해석(날짜만이면 그날 0시, 지역 기준)
+pub fn parse_ts_in(s: &str, tz: Tz) -> Result<DateTime<Utc>, String> {
+  match parse_any(s, tz)? {
+    Parsed::Instant(t) => Ok(t),
+    Parsed::Day(d) => day_start(d, tz).ok_or_else(|| format!("invalid date: {s}")),
+  }
+}
+
+/* -------------------- range -------------------- */
+
+/// 케이스 기간. 날짜만 준 timeTo는 그날 하루 전체를 포함한다.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeRange {
+  pub from: Option<DateTime<Utc>>,
+  /// 배타 상한(ts < to_excl)
+  pub to_excl: Option<DateTime<Utc>>,
+  /// 포함 상한(ts <= to_incl)
+  pub to_incl: Option<DateTime<Utc>>,
+}
+
+impl TimeRange {
+  pub fn from_bounds(from: &str, to: &str, tz: Tz) -> Result<Self, String> {
+    let mut r = TimeRange::default();
+
+    if !from.trim().is_empty() {
+      r.from = Some(parse_ts_in(from, tz).map_err(|e| format!("timeFrom: {e}"))?);
+    }
+
+    if !to.trim().is_empty() {
+      match parse_any(to, tz).map_err(|e| format!("timeTo: {e}"))? {
+        Parsed::Instant(t) => r.to_incl = Some(t),
+        Parsed::Day(d) => {
+          let next = day_start(d, tz).map(|t| t + Duration::days(1));
+          r.to_excl = Some(next.ok_or_else(|| format!("timeTo: invalid date: {to}"))?);
+        }
+      }
+    }
+
+    Ok(r)
+  }
+
+  pub fn is_set(&self) -> bool {
+    self.from.is_some() || self.to_excl.is_some() || self.to_incl.is_some()
+  }
+
+  pub fn contains(&self, t: DateTime<Utc>) -> bool {
+    if self.from.is_some_and(|f| t < f) {
+      return false;
+    }
+    if self.to_excl.is_some_and(|e| t >= e) {
+      return false;
+    }
+    if self.to_incl.is_some_and(|e| t > e) {
+      return false;
+    }
+    true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn utc(s: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+  }
+
+  #[test]
+  fn rfc3339_keeps_its_own_offset() {
+    let want = Parsed::Instant(utc("2025-03-02T00:30:00Z"));
+    assert_eq!(parse_any("2025-03-02T09:30:00+09:00", chrono_tz::UTC), Ok(want));
+    assert_eq!(parse_any("2025-03-02T00:30:00Z", DEFAULT_TZ), Ok(want));
+  }
+
+  #[test]
+  fn naive_datetimes_are_read_in_the_given_zone() {
+    let want = Parsed::Instant(utc("2025-03-02T00:30:00Z"));
+    assert_eq!(parse_any("2025-03-02T09:30", DEFAULT_TZ), Ok(want));
+    assert_eq!(parse_any("2025-03-02 09:30:00", DEFAULT_TZ), Ok(want));
+    assert_eq!(parse_ts_in("2025-03-02", DEFAULT_TZ), Ok(utc("2025-03-01T15:00:00Z")));
+  }
+
+  #[test]
+  fn rejects_unparseable_values_and_unknown_zones() {
+    assert!(parse_any("", DEFAULT_TZ).is_err());
+    assert_eq!(parse_any("어제 오후", DEFAULT_TZ), Err("invalid timestamp: 어제 오후".into()));
+    assert_eq!(resolve_tz(Some(" ")), Ok(DEFAULT_TZ));
+    assert_eq!(resolve_tz(Some("Asia/Busan")), Err("unknown time zone: Asia/Busan".into()));
+  }
+
+  #[test]
+  fn date_only_time_to_includes_the_whole_day() {
+    let r = TimeRange::from_bounds("2025-03-01", "2025-03-02", DEFAULT_TZ).unwrap();
+    assert_eq!(r.from, Some(utc("2025-02-28T15:00:00Z")));
+    assert_eq!(r.to_excl, Some(utc("2025-03-02T15:00:00Z")));
+    assert_eq!(r.to_incl, None);
+    assert!(r.contains(utc("2025-03-02T14:59:59Z")));
+    assert!(!r.contains(utc("2025-03-02T15:00:00Z")));
+    assert!(!r.contains(utc("2025-02-28T14:59:59Z")));
+  }
+
+  #[test]
+  fn timed_time_to_is_an_inclusive_instant() {
+    let r = TimeRange::from_bounds("", "2025-03-02T18:00", DEFAULT_TZ).unwrap();
+    assert_eq!(r.to_incl, Some(utc("2025-03-02T09:00:00Z")));
+    assert!(r.contains(utc("2025-03-02T09:00:00Z")));
+    assert!(!r.contains(utc("2025-03-02T09:00:01Z")));
+    assert!(TimeRange::from_bounds("", "", DEFAULT_TZ).is_ok_and(|r| !r.is_set()));
+    assert!(TimeRange::from_bounds("nope", "", DEFAULT_TZ).unwrap_err().starts_with("timeFrom: "));
+  }
+}
@@ -0,0 +1,241 @@
+use super::norm;
+
+/* -------------------- tokenizer strategies -------------------- */
+
+/// 랭킹에서 쿼리/요약을 토큰으로 자르는 전략. RankOpts.tokenizer로 고른다.
+pub trait Tokenizer: Send + Sync {
+  fn name(&self) -> &'static str;
+  fn tokenize(&self, s: &str) -> Vec<String>;
+}
+
+pub const DEFAULT_TOKENIZER: &str = "korean";
+
+/// "simple" | "korean" (비어 있으면 korean)
+pub fn tokenizer_by_name(name: Option<&str>) -> Result<Box<dyn Tokenizer>, String> {
+  match name.map(|s| s.trim()).filter(|s| !s.is_empty()).unwrap_or(DEFAULT_TOKENIZER) {
+    "simple" => Ok(Box::new(SimpleTokenizer)),
+    "korean" => Ok(Box::new(KoreanTokenizer::default())),
+    other => Err(format!("unknown tokenizer: {other}")),
+  }
+}
+
+pub(crate) fn is_word_char(cp: u32) -> bool {
+  let is_ascii_num = (0x30..=0x39).contains(&cp);
+  let is_ascii_upper = (0x41..=0x5A).contains(&cp);
+  let is_ascii_lower = (0x61..=0x7A).contains(&cp);
+  let is_hangul_syllable = (0xAC00..=0xD7A3).contains(&cp);
+  let is_hangul_jamo1 = (0x3131..=0x314E).contains(&cp);
+  let is_hangul_jamo2 = (0x314F..=0x3163).contains(&cp);
+  is_ascii_num || is_ascii_upper || is_ascii_lower || is_hangul_syllable || is_hangul_jamo1 || is_hangul_jamo2
+}
+
+fn is_hangul(ch: char) -> bool {
+  (0xAC00..=0xD7A3).contains(&(ch as u32))
+}
+
+/// 단어 문자 덩어리로 자르고 정규화(소문자/공백 정리)
+fn word_runs(s: &str) -> Vec<String> {
+  s.split(|ch: char| !is_word_char(ch as u32))
+    .map(norm)
+    .filter(|t| !t.is_empty())
+    .collect()
+}
+
+fn push_unique(out: &mut Vec<String>, t: String) {
+  if !out.contains(&t) {
+    out.push(t);
+  }
+}
+
+/// 예전 방식: 단어 문자 덩어리 그대로, 글자 수 2 이상
+pub struct SimpleTokenizer;
+
+impl Tokenizer for SimpleTokenizer {
+  fn name(&self) -> &'static str {
+    "simple"
+  }
+
+  fn tokenize(&self, s: &str) -> Vec<String> {
+    word_runs(s).into_iter().filter(|t| t.chars().count() >= 2).collect()
+  }
+}
+
+/* -------------------- korean -------------------- */
+
+/// 긴 것부터 검사(“에게서”가 “서”보다 먼저)
+const KO_SUFFIXES: &[&str] = &[
+  // 어미
+  "했습니다", "하였습니다", "했었다", "하였다", "합니다", "했다", "했음", "하다", "하며", "하고", "해서",
+  // 조사
+  "에게서", "한테서", "으로써", "으로서", "이라고", "에서는", "에게는", "에서도", "까지", "부터", "에게", "한테",
+  "께서", "에서", "으로", "처럼", "보다", "이랑", "마다", "조차", "라고", "이나", "에는", "과의", "와의", "의",
+  "이", "가", "은", "는", "을", "를", "에", "와", "과", "도", "만", "로", "랑", "께",
+];
+
+/// 조사처럼 끝나지만 명사 자체인 말. 떼려는 조사가 이 말의 끝을 잘라 먹으면 떼지 않는다
+/// ("고양이"≠"고양"+"이", "결과의"는 "과의"가 아니라 "의"만 떼어 "결과")
+const KO_NOUN_EXCEPTIONS: &[&str] = &[
+  "고양이", "어린이", "아이", "놀이", "종이", "사이", "나이", "차이", "높이", "길이",
+  "복도", "태도", "정도", "제도", "시도", "평가", "휴가", "물가",
+  "회의", "주의", "논의", "합의", "동의", "항의", "문의",
+  "결과", "효과", "사과", "교과", "진로", "경로", "통로", "불만",
+];
+
+/// 조사/어미를 떼고 한 글자만 남아도 되는 명사(이 밖의 한 글자 어간은 떼지 않는다: "사과의"≠"사"+"과의")
+const KO_SINGLE_NOUNS: &[&str] = &[
+  "칼", "욕", "돈", "말", "손", "발", "몸", "눈", "귀", "코", "입", "목", "배", "팔", "뺨", "턱", "피", "침",
+  "술", "약", "폰", "집", "반", "책", "옷", "밥", "물", "불", "돌", "벌", "차",
+];
+
+/// 조사/어미를 떼고, 숫자+한글 혼합 토큰("3학년2반")은 단위별로도 쪼갠다. 글자 수 기준으로 거른다.
+/// 한 글자 한글 명사(칼, 욕, 돈)는 min_chars와 상관없이 남긴다.
+pub struct KoreanTokenizer {
+  pub min_chars: usize,
+}
+
+impl Default for KoreanTokenizer {
+  fn default() -> Self {
+    Self { min_chars: 2 }
+  }
+}
+
+impl KoreanTokenizer {
+  /// 어간이 두 글자 이상이거나 KO_SINGLE_NOUNS일 때만 떼고("돈에게서" → "돈", "사과의" → "사과"),
+  /// 조사가 예외 명사의 끝을 잘라 먹으면 그 조사는 건너뛴다("고양이", "결과의" → "결과")
+  pub fn strip_suffix<'a>(&self, t: &'a str) -> &'a str {
+    if !t.chars().last().is_some_and(is_hangul) {
+      return t;
+    }
+    for suf in KO_SUFFIXES {
+      let Some(stem) = t.strip_suffix(suf) else {
+        continue;
+      };
+      let stem_ok = stem.chars().count() >= 2 || KO_SINGLE_NOUNS.contains(&stem);
+      if stem_ok && !Self::cuts_noun(t, suf) {
+        return stem;
+      }
+    }
+    t
+  }
+
+  /// t = 명사 + (suf의 뒷부분)이면 suf를 떼는 것이 명사를 자른다
+  fn cuts_noun(t: &str, suf: &str) -> bool {
+    suf
+      .char_indices()
+      .skip(1)
+      .map(|(i, _)| &suf[i..])
+      .chain(std::iter::once(""))
+      .any(|rest| {
+        t.strip_suffix(rest)
+          .is_some_and(|head| KO_NOUN_EXCEPTIONS.iter().any(|n| head.ends_with(n) && n.len() > suf.len() - rest.len()))
+      })
+  }
+
+  fn keep(&self, t: &str) -> bool {
+    let mut it = t.chars();
+    match (it.next(), it.next()) {
+      (Some(c), None) => is_hangul(c) || self.min_chars <= 1,
+      _ => t.chars().count() >= self.min_chars,
+    }
+  }
+
+  /// "3학년2반" → ["3학년", "2반"] (숫자가 다시 시작하는 곳에서 자름, 숫자로 시작하는 단위만 쓴다)
+  fn units(t: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut cur = String::new();
+    let mut prev_digit = false;
+    for ch in t.chars() {
+      let digit = ch.is_ascii_digit();
+      if digit && !prev_digit && !cur.is_empty() {
+        out.push(std::mem::take(&mut cur));
+      }
+      cur.push(ch);
+      prev_digit = digit;
+    }
+    if !cur.is_empty() {
+      out.push(cur);
+    }
+    out
+  }
+}
+
+impl Tokenizer for KoreanTokenizer {
+  fn name(&self) -> &'static str {
+    "korean"
+  }
+
+  fn tokenize(&self, s: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+
+    for run in word_runs(s) {
+      let whole = self.strip_suffix(&run).to_string();
+      let units = Self::units(&whole);
+
+      if self.keep(&whole) {
+        push_unique(&mut out, whole);
+      }
+      if units.len() > 1 {
+        for u in units.into_iter().filter(|u| u.starts_with(|c: char| c.is_ascii_digit())) {
+          let u = self.strip_suffix(&u).to_string();
+          if self.keep(&u) {
+            push_unique(&mut out, u);
+          }
+        }
+      }
+    }
+
+    out
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn ko(s: &str) -> Vec<String> {
+    KoreanTokenizer::default().tokenize(s)
+  }
+
+  #[test]
+  fn strips_particles_from_request_examples() {
+    assert_eq!(ko("학부모가"), vec!["학부모"]);
+    assert_eq!(ko("학부모는"), vec!["학부모"]);
+    assert_eq!(ko("학부모에게"), vec!["학부모"]);
+    assert_eq!(ko("아이들이"), vec!["아이들"]);
+  }
+
+  #[test]
+  fn splits_class_units() {
+    assert_eq!(ko("3학년2반"), vec!["3학년2반", "3학년", "2반"]);
+    assert_eq!(ko("3학년2반에서"), vec!["3학년2반", "3학년", "2반"]);
+  }
+
+  #[test]
+  fn keeps_single_syllable_nouns() {
+    assert_eq!(ko("칼"), vec!["칼"]);
+    assert_eq!(ko("욕 돈"), vec!["욕", "돈"]);
+    assert_eq!(ko("돈에게서"), vec!["돈"]);
+    assert_eq!(ko("칼이"), vec!["칼"]);
+    // 목록에 없는 한 글자 어간은 만들지 않는다
+    assert_eq!(ko("나이"), vec!["나이"]);
+    // 영문/숫자 한 글자는 여전히 버림
+    assert!(ko("a 1").is_empty());
+  }
+
+  #[test]
+  fn does_not_over_stem_nouns() {
+    assert_eq!(ko("고양이"), vec!["고양이"]);
+    assert_eq!(ko("복도"), vec!["복도"]);
+    assert_eq!(ko("학부모항의"), vec!["학부모항의"]);
+    assert_eq!(ko("고양이가"), vec!["고양이"]);
+  }
+
+  #[test]
+  fn multi_char_particles_do_not_cut_nouns() {
+    assert_eq!(ko("결과의"), vec!["결과"]);
+    assert_eq!(ko("사과의"), vec!["사과"]);
+    assert_eq!(ko("효과의"), vec!["효과"]);
+    assert_eq!(ko("교과와의"), vec!["교과"]);
+    assert_eq!(ko("친구와의"), vec!["친구"]);
+  }
+}
@@ -1,17 +1,62 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod engine;
+mod backup;
+mod backup_crypto;
 mod commands;
+mod db;
+mod schema;
+mod storage;
 
 fn main() {
   tauri::Builder::default()
     // ✅ save()/open() 파일 다이얼로그 플러그인
     .plugin(tauri_plugin_dialog::init())
+    .manage(commands::RecordStoreState::default())
+    .manage(commands::DbState::default())
+    .manage(commands::SynonymState::default())
     .invoke_handler(tauri::generate_handler![
+      commands::load_state,
+      commands::save_state,
+      commands::save_changes,
+      commands::clear_state,
+      commands::db_records_list,
+      commands::db_record_get,
+      commands::db_record_upsert,
+      commands::db_record_delete,
+      commands::db_cases_list,
+      commands::db_case_get,
+      commands::db_case_upsert,
+      commands::db_case_delete,
+      commands::db_case_add_records,
+      commands::db_case_remove_records,
+      commands::db_step_upsert,
+      commands::db_step_delete,
+      commands::db_advisors_set,
+      commands::db_search,
+      commands::schema_upgrade,
       commands::engine_rank,
+      commands::engine_rank_indexed,
+      commands::engine_parse_query,
+      commands::engine_actor_network,
+      commands::engine_suggest_cases,
+      commands::record_store_sync,
+      commands::record_store_upsert,
+      commands::record_store_delete,
       commands::engine_advise,
+      commands::engine_regenerate_advisors,
+      commands::synonyms_get,
+      commands::synonyms_save,
+      commands::synonyms_reset,
+      commands::advisor_lint,
+      commands::advisor_custom_list,
+      commands::advisor_custom_add,
+      commands::advisor_custom_update,
+      commands::advisor_custom_delete,
+      commands::advisor_custom_disable,
       commands::export_case_pdf,
       commands::export_backup_json,
+      commands::import_backup_json,
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
@@ -0,0 +1,782 @@
+// 버전 한 단계씩 올리는 함수들(v1 → v2 → … → v7 → v8).
+// 각 단계는 바로 다음 버전 모델만 만들고, 바꾼 것을 Changes에 적는다.
+
+use chrono::{SecondsFormat, Utc};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+
+use super::models::{v1, v2, v3, v4, v5, v6, v7, Actor, StateOf};
+use super::{AppState, ChangeKind, FieldChange, StepReport, StoredCase, CURRENT_VERSION};
+use crate::engine::advisor::fnv1a64;
+use crate::engine::{ActorRef, AdvisorItem, CaseItem, RecordItem, StepItem};
+
+/* -------------------- change log -------------------- */
+
+const MAX_EXAMPLES: usize = 3;
+
+#[derive(Default)]
+struct Changes(BTreeMap<(ChangeKind, String), FieldChange>);
+
+impl Changes {
+  fn add(&mut self, kind: ChangeKind, path: &str, id: &str, note: &str) {
+    let c = self.0.entry((kind, path.to_string())).or_insert_with(|| FieldChange {
+      kind,
+      path: path.to_string(),
+      count: 0,
+      examples: Vec::new(),
+      note: note.to_string(),
+    });
+    c.count += 1;
+    if c.examples.len() < MAX_EXAMPLES && !id.is_empty() && !c.examples.iter().any(|x| x == id) {
+      c.examples.push(id.to_string());
+    }
+  }
+
+  fn defaulted(&mut self, path: &str, id: &str, note: &str) {
+    self.add(ChangeKind::Defaulted, path, id, note);
+  }
+
+  fn dropped(&mut self, path: &str, id: &str, note: &str) {
+    self.add(ChangeKind::Dropped, path, id, note);
+  }
+
+  fn converted(&mut self, path: &str, id: &str, note: &str) {
+    self.add(ChangeKind::Converted, path, id, note);
+  }
+
+  /// 모델에 없는 필드
+  fn rest(&mut self, prefix: &str, id: &str, rest: &Map<String, Value>) {
+    for k in rest.keys() {
+      self.dropped(&format!("{prefix}.{k}"), id, "이 버전에 없는 필드");
+    }
+  }
+
+  fn finish(self, from: u32, to: u32) -> StepReport {
+    StepReport {
+      from,
+      to,
+      changes: self.0.into_values().collect(),
+    }
+  }
+}
+
+fn map_state<R, C, R2, C2>(
+  s: StateOf<R, C>,
+  mut fr: impl FnMut(R) -> R2,
+  mut fc: impl FnMut(&str, C) -> C2,
+) -> StateOf<R2, C2> {
+  StateOf {
+    v: s.v,
+    tab: s.tab,
+    selected_case_id: s.selected_case_id,
+    records: s.records.into_iter().map(&mut fr).collect(),
+    cases: s.cases.into_iter().map(|(k, c)| { let c2 = fc(&k, c); (k, c2) }).collect(),
+    rest: s.rest,
+  }
+}
+
+fn case_label<'a>(key: &'a str, id: &'a Option<String>) -> &'a str {
+  id.as_deref().unwrap_or(key)
+}
+
+/* -------------------- v1 → v2: related, storeOther/placeOther -------------------- */
+
+fn v1_to_v2(s: v1::State, out: &mut Vec<StepReport>) -> v2::State {
+  let mut ch = Changes::default();
+  let s = map_state(
+    s,
+    |r: v1::Record| {
+      let id = r.id.clone().unwrap_or_default();
+      ch.rest("records[]", &id, &r.rest);
+      ch.defaulted("records[].related", &id, "관련자 없음([])");
+      ch.defaulted("records[].storeOther", &id, "빈 값");
+      ch.defaulted("records[].placeOther", &id, "빈 값");
+      v2::Record {
+        id: r.id,
+        ts: r.ts,
+        store_type: r.store_type,
+        store_other: Some(String::new()),
+        lv: r.lv,
+        actor: r.actor,
+        related: Some(Vec::new()),
+        place: r.place,
+        place_other: Some(String::new()),
+        summary: r.summary,
+        updated_at: None,
+        rest: Map::new(),
+      }
+    },
+    |_, c: v1::Case| c,
+  );
+  out.push(ch.finish(1, 2));
+  s
+}
+
+/* -------------------- v2 → v3: sensFilter, onlyMainActor -------------------- */
+
+fn v2_to_v3(s: v2::State, out: &mut Vec<StepReport>) -> v3::State {
+  let mut ch = Changes::default();
+  let s = map_state(
+    s,
+    |r| r,
+    |key, c: v1::Case| {
+      let label = case_label(key, &c.id).to_string();
+      ch.rest("cases[]", &label, &c.rest);
+      ch.defaulted("cases[].sensFilter", &label, "\"any\"(민감도 제한 없음)");
+      ch.defaulted("cases[].onlyMainActor", &label, "false");
+      v3::Case {
+        id: c.id,
+        title: c.title,
+        actors: c.actors,
+        sens_filter: Some("any".into()),
+        only_main_actor: Some(false),
+        status: c.status,
+        created_at: c.created_at,
+        query: c.query,
+        steps: c.steps,
+        rest: Map::new(),
+      }
+    },
+  );
+  out.push(ch.finish(2, 3));
+  s
+}
+
+/* -------------------- v3 → v4: step text → name/note -------------------- */
+
+/// 첫 줄은 단계 이름, 나머지는 메모
+fn split_step_text(text: &str) -> (String, String) {
+  let t = text.trim();
+  match t.split_once('\n') {
+    Some((head, tail)) => (head.trim().to_string(), tail.trim().to_string()),
+    None => (t.to_string(), String::new()),
+  }
+}
+
+fn v3_to_v4(s: v3::State, out: &mut Vec<StepReport>) -> v4::State {
+  let mut ch = Changes::default();
+  let s = map_state(
+    s,
+    |r| r,
+    |key, c: v3::Case| {
+      let label = case_label(key, &c.id).to_string();
+      ch.rest("cases[]", &label, &c.rest);
+      let steps = c.steps.map(|steps| {
+        steps
+          .into_iter()
+          .map(|st| {
+            ch.rest("cases[].steps[]", &label, &st.rest);
+            if !st.text.trim().is_empty() {
+              ch.converted("cases[].steps[].text", &label, "첫 줄 → name, 나머지 → note");
+            }
+            let (name, note) = split_step_text(&st.text);
+            v4::Step {
+              id: st.id,
+              ts: st.ts,
+              name,
+              note,
+              text: None,
+              place: st.place,
+              owner: st.owner,
+              lv: st.lv,
+              rest: Map::new(),
+            }
+          })
+          .collect()
+      });
+      v4::Case {
+        id: c.id,
+        title: c.title,
+        actors: c.actors,
+        sens_filter: c.sens_filter,
+        only_main_actor: c.only_main_actor,
+        status: c.status,
+        created_at: c.created_at,
+        query: c.query,
+        steps,
+        rest: Map::new(),
+      }
+    },
+  );
+  out.push(ch.finish(3, 4));
+  s
+}
+
+/* -------------------- v4 → v5: timeFrom/timeTo/maxResults -------------------- */
+
+fn v4_to_v5(s: v4::State, out: &mut Vec<StepReport>) -> v5::State {
+  let mut ch = Changes::default();
+  let s = map_state(
+    s,
+    |r| r,
+    |key, c: v4::Case| {
+      let label = case_label(key, &c.id).to_string();
+      ch.rest("cases[]", &label, &c.rest);
+      ch.defaulted("cases[].timeFrom/timeTo", &label, "빈 값(기간 제한 없음)");
+      v5::Case {
+        id: c.id,
+        title: c.title,
+        actors: c.actors,
+        sens_filter: c.sens_filter,
+        only_main_actor: c.only_main_actor,
+        status: c.status,
+        created_at: c.created_at,
+        query: c.query,
+        time_from: String::new(),
+        time_to: String::new(),
+        max_results: None,
+        steps: c.steps,
+        rest: Map::new(),
+      }
+    },
+  );
+  out.push(ch.finish(4, 5));
+  s
+}
+
+/* -------------------- v5 → v6: recordIds, scoreByRecordId -------------------- */
+
+fn v5_to_v6(s: v5::State, out: &mut Vec<StepReport>) -> v6::State {
+  let mut ch = Changes::default();
+  let s = map_state(
+    s,
+    |r| r,
+    |key, c: v5::Case| {
+      let label = case_label(key, &c.id).to_string();
+      ch.rest("cases[]", &label, &c.rest);
+      ch.defaulted("cases[].recordIds", &label, "빈 목록 — 케이스에서 다시 모으기 필요");
+      v6::Case {
+        id: c.id,
+        title: c.title,
+        actors: c.actors,
+        sens_filter: c.sens_filter,
+        only_main_actor: c.only_main_actor,
+        status: c.status,
+        created_at: c.created_at,
+        query: c.query,
+        time_from: c.time_from,
+        time_to: c.time_to,
+        max_results: c.max_results,
+        steps: c.steps,
+        record_ids: Some(Vec::new()),
+        score_by_record_id: None,
+        rest: Map::new(),
+      }
+    },
+  );
+  out.push(ch.finish(5, 6));
+  s
+}
+
+/* -------------------- v6 → v7: advisors, mode -------------------- */
+
+fn v6_to_v7(s: v6::State, out: &mut Vec<StepReport>) -> v7::State {
+  let mut ch = Changes::default();
+  let s = map_state(
+    s,
+    |r| r,
+    |key, c: v6::Case| {
+      let label = case_label(key, &c.id).to_string();
+      ch.rest("cases[]", &label, &c.rest);
+      ch.defaulted("cases[].advisors", &label, "빈 목록 — 권고 다시 만들기로 채움");
+      v7::Case {
+        id: c.id,
+        title: c.title,
+        actors: c.actors,
+        sens_filter: c.sens_filter,
+        only_main_actor: c.only_main_actor,
+        status: c.status,
+        created_at: c.created_at,
+        query: c.query,
+        time_from: c.time_from,
+        time_to: c.time_to,
+        max_results: c.max_results,
+        steps: c.steps,
+        advisors: Some(Vec::new()),
+        mode: None,
+        record_ids: c.record_ids,
+        score_by_record_id: c.score_by_record_id,
+        components_by_record_id: None,
+        updated_at: None,
+        rest: Map::new(),
+      }
+    },
+  );
+  out.push(ch.finish(6, 7));
+  s
+}
+
+/* -------------------- v7 → v8: 검증(허용 값/빠진 필드/중복) -------------------- */
+
+const STATUSES: &[&str] = &["진행중", "답변 준비", "종결"];
+/// 예전 빌드/샘플 팩에 쓰이던 상태 이름 → 현재 이름
+const STATUS_ALIASES: &[(&str, &str)] = &[("완료", "종결"), ("진행 중", "진행중"), ("답변준비", "답변 준비")];
+const LEVELS: &[&str] = &["info", "warn", "critical"];
+const ADVISOR_STATES: &[&str] = &["active", "done", "dismissed"];
+const DEFAULT_STORE_TYPE: &str = "문서";
+const DEFAULT_PLACE: &str = "기타";
+const DEFAULT_ACTOR_TYPE: &str = "외부인";
+const DEFAULT_LV: &str = "LV2";
+
+fn now_iso() -> String {
+  Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn valid_lv(lv: &str) -> bool {
+  matches!(lv, "LV1" | "LV2" | "LV3" | "LV4" | "LV5")
+}
+
+/// 빠졌거나 빈 id: 내용으로 만든 고정 id(같은 파일을 두 번 가져와도 같은 id)
+fn make_id(prefix: &str, parts: &[&str]) -> String {
+  format!("{prefix}_{:016x}", fnv1a64(parts))
+}
+
+/// 없으면 기본값, 있는데 허용 목록 밖이면 기본값으로 바꾼다
+fn pick(ch: &mut Changes, path: &str, id: &str, v: Option<String>, allowed: &dyn Fn(&str) -> bool, default: &str) -> String {
+  match v {
+    None => {
+      ch.defaulted(path, id, &format!("\"{default}\""));
+      default.to_string()
+    }
+    Some(x) if allowed(&x) => x,
+    Some(_) => {
+      ch.converted(path, id, &format!("허용 값이 아니어서 \"{default}\""));
+      default.to_string()
+    }
+  }
+}
+
+/// 이름 빈 당사자는 버린다
+fn actors(ch: &mut Changes, path: &str, id: &str, list: Vec<Actor>) -> Vec<ActorRef> {
+  list
+    .into_iter()
+    .filter_map(|a| {
+      let name = a.name.trim().to_string();
+      if name.is_empty() {
+        ch.dropped(path, id, "이름이 빈 당사자");
+        return None;
+      }
+      Some(ActorRef {
+        r#type: a.r#type.unwrap_or_else(|| DEFAULT_ACTOR_TYPE.into()),
+        name,
+      })
+    })
+    .collect()
+}
+
+fn record_v8(ch: &mut Changes, i: usize, r: v2::Record) -> RecordItem {
+  let id = match r.id.filter(|s| !s.trim().is_empty()) {
+    Some(id) => id,
+    None => {
+      let id = make_id("REC", &[r.ts.as_deref().unwrap_or(""), &r.summary, &i.to_string()]);
+      ch.defaulted("records[].id", &id, "내용으로 새 id");
+      id
+    }
+  };
+  ch.rest("records[]", &id, &r.rest);
+
+  let ts = r.ts.unwrap_or_else(|| {
+    ch.defaulted("records[].ts", &id, "가져온 시각");
+    now_iso()
+  });
+  let store_type = pick(ch, "records[].storeType", &id, r.store_type, &|s| !s.is_empty(), DEFAULT_STORE_TYPE);
+  let place = pick(ch, "records[].place", &id, r.place, &|s| !s.is_empty(), DEFAULT_PLACE);
+  let lv = pick(ch, "records[].lv", &id, r.lv, &valid_lv, DEFAULT_LV);
+
+  let actor = match r.actor {
+    Some(a) => ActorRef {
+      r#type: a.r#type.unwrap_or_else(|| {
+        ch.defaulted("records[].actor.type", &id, &format!("\"{DEFAULT_ACTOR_TYPE}\""));
+        DEFAULT_ACTOR_TYPE.into()
+      }),
+      name: a.name.trim().to_string(),
+    },
+    None => {
+      ch.defaulted("records[].actor", &id, "이름 없는 외부인");
+      ActorRef {
+        r#type: DEFAULT_ACTOR_TYPE.into(),
+        name: String::new(),
+      }
+    }
+  };
+  let related = actors(ch, "records[].related[]", &id, r.related.unwrap_or_default());
+
+  RecordItem {
+    id,
+    ts,
+    store_type,
+    store_other: r.store_other.unwrap_or_default(),
+    lv,
+    actor,
+    related,
+    place,
+    place_other: r.place_other.unwrap_or_default(),
+    summary: r.summary,
+    updated_at: r.updated_at.unwrap_or_default(),
+  }
+}
+
+fn non_empty(v: Option<String>) -> Option<String> {
+  v.filter(|s| !s.trim().is_empty())
+}
+
+fn step_v8(ch: &mut Changes, case_id: &str, i: usize, s: v4::Step) -> StepItem {
+  ch.rest("cases[].steps[]", case_id, &s.rest);
+  let id = non_empty(s.id).unwrap_or_else(|| {
+    ch.defaulted("cases[].steps[].id", case_id, "내용으로 새 id");
+    make_id("STEP", &[case_id, &s.name, &i.to_string()])
+  });
+  let ts = s.ts.unwrap_or_else(|| {
+    ch.defaulted("cases[].steps[].ts", case_id, "가져온 시각");
+    now_iso()
+  });
+  StepItem {
+    id,
+    ts,
+    name: s.name.trim().to_string(),
+    note: s.note.trim().to_string(),
+    text: non_empty(s.text),
+    place: non_empty(s.place),
+    owner: non_empty(s.owner),
+    lv: non_empty(s.lv),
+  }
+}
+
+fn advisor_v8(ch: &mut Changes, case_id: &str, i: usize, a: v7::Advisor) -> AdvisorItem {
+  ch.rest("cases[].advisors[]", case_id, &a.rest);
+  let id = non_empty(a.id).unwrap_or_else(|| {
+    ch.defaulted("cases[].advisors[].id", case_id, "내용으로 새 id");
+    make_id("ADV", &[case_id, &a.title, &i.to_string()])
+  });
+  let level = pick(ch, "cases[].advisors[].level", case_id, a.level, &|s| LEVELS.contains(&s), "info");
+  let state = pick(ch, "cases[].advisors[].state", case_id, a.state, &|s| ADVISOR_STATES.contains(&s), "active");
+  let explain = match a.explain {
+    None | Some(Value::Null) => None,
+    Some(v) => match serde_json::from_value(v) {
+      Ok(e) => Some(e),
+      Err(_) => {
+        ch.dropped("cases[].advisors[].explain", case_id, "모양이 맞지 않음");
+        None
+      }
+    },
+  };
+  AdvisorItem {
+    id,
+    ts: a.ts.unwrap_or_else(now_iso),
+    title: a.title.trim().to_string(),
+    body: a.body.trim().to_string(),
+    level,
+    tags: a
+      .tags
+      .unwrap_or_default()
+      .into_iter()
+      .map(|t| t.trim().to_string())
+      .filter(|t| !t.is_empty())
+      .collect(),
+    state,
+    rule_id: non_empty(a.rule_id),
+    stale: a.stale.unwrap_or(false),
+    reasons: a.reasons.unwrap_or_default(),
+    explain,
+  }
+}
+
+fn case_v8(ch: &mut Changes, key: &str, c: v7::Case) -> StoredCase {
+  let id = non_empty(c.id).unwrap_or_else(|| key.to_string());
+  if id != key {
+    ch.converted("cases{}", &id, "맵 키와 id가 달라 id 기준으로 저장");
+  }
+  ch.rest("cases[]", &id, &c.rest);
+
+  let title = match c.title.trim() {
+    "" => {
+      ch.defaulted("cases[].title", &id, "\"케이스\"");
+      "케이스".to_string()
+    }
+    t => t.to_string(),
+  };
+  let status = match c.status.as_deref().and_then(|s| STATUS_ALIASES.iter().find(|(from, _)| *from == s)) {
+    Some((from, to)) => {
+      ch.converted("cases[].status", &id, &format!("\"{from}\" → \"{to}\""));
+      to.to_string()
+    }
+    None => pick(ch, "cases[].status", &id, c.status, &|s| STATUSES.contains(&s), STATUSES[0]),
+  };
+  let sens_filter = pick(ch, "cases[].sensFilter", &id, c.sens_filter, &|s| s == "any" || valid_lv(s), "any");
+  let created_at = c.created_at.unwrap_or_else(|| {
+    ch.defaulted("cases[].createdAt", &id, "가져온 시각");
+    now_iso()
+  });
+  let mode = match c.mode {
+    Some(m) if m == "smart" || m == "normal" => Some(m),
+    Some(_) => {
+      ch.dropped("cases[].mode", &id, "smart/normal이 아님");
+      None
+    }
+    None => None,
+  };
+
+  let record_ids = match c.record_ids {
+    Some(ids) => {
+      let mut seen: HashSet<String> = HashSet::new();
+      let before = ids.len();
+      let ids: Vec<String> = ids.into_iter().filter(|x| seen.insert(x.clone())).collect();
+      if ids.len() < before {
+        ch.converted("cases[].recordIds", &id, "중복 id 제거");
+      }
+      ids
+    }
+    None => {
+      ch.defaulted("cases[].recordIds", &id, "빈 목록");
+      Vec::new()
+    }
+  };
+
+  let steps = c
+    .steps
+    .unwrap_or_default()
+    .into_iter()
+    .enumerate()
+    .map(|(i, s)| step_v8(ch, &id, i, s))
+    .collect();
+  let advisors = c
+    .advisors
+    .unwrap_or_default()
+    .into_iter()
+    .enumerate()
+    .map(|(i, a)| advisor_v8(ch, &id, i, a))
+    .collect();
+
+  let mut extra = Map::new();
+  if let Some(m) = c.score_by_record_id {
+    extra.insert("scoreByRecordId".into(), Value::Object(m));
+  }
+  if let Some(m) = c.components_by_record_id {
+    extra.insert("componentsByRecordId".into(), Value::Object(m));
+  }
+
+  StoredCase {
+    case_item: CaseItem {
+      actors: actors(ch, "cases[].actors[]", &id, c.actors.unwrap_or_default()),
+      id,
+      title,
+      query: c.query,
+      time_from: c.time_from,
+      time_to: c.time_to,
+      max_results: c.max_results,
+      only_main_actor: c.only_main_actor.unwrap_or(false),
+      sens_filter,
+      created_at,
+      record_ids,
+      advisors,
+    },
+    status,
+    mode,
+    steps,
+    updated_at: c.updated_at.unwrap_or_default(),
+    extra,
+  }
+}
+
+fn v7_to_v8(s: v7::State, from: u32, out: &mut Vec<StepReport>) -> AppState {
+  let mut ch = Changes::default();
+  ch.rest("state", "", &s.rest);
+
+  let tab = match s.tab.as_deref() {
+    Some("records") | Some("cases") => s.tab.unwrap_or_default(),
+    _ => "records".to_string(),
+  };
+
+  let mut records: Vec<RecordItem> = Vec::with_capacity(s.records.len());
+  let mut seen: HashSet<String> = HashSet::new();
+  for (i, r) in s.records.into_iter().enumerate() {
+    let r = record_v8(&mut ch, i, r);
+    if !seen.insert(r.id.clone()) {
+      ch.dropped("records[]", &r.id, "같은 id가 앞에 있어 뒤의 것을 버림");
+      continue;
+    }
+    records.push(r);
+  }
+
+  let mut cases: BTreeMap<String, StoredCase> = BTreeMap::new();
+  for (key, c) in s.cases {
+    let c = case_v8(&mut ch, &key, c);
+    let id = c.case_item.id.clone();
+    if cases.contains_key(&id) {
+      ch.dropped("cases{}", &id, "같은 id의 케이스가 이미 있어 버림");
+      continue;
+    }
+    cases.insert(id, c);
+  }
+
+  let selected_case_id = s.selected_case_id.filter(|id| cases.contains_key(id));
+
+  // v8 파일이면 검증 단계(8 → 8)
+  let step_from = if from >= CURRENT_VERSION { CURRENT_VERSION } else { 7 };
+  out.push(ch.finish(step_from, CURRENT_VERSION));
+  AppState {
+    v: CURRENT_VERSION,
+    tab,
+    selected_case_id,
+    records,
+    cases,
+  }
+}
+
+/* -------------------- chain -------------------- */
+
+fn parse<T: DeserializeOwned>(v: Value, version: u32) -> Result<T, String> {
+  serde_json::from_value(v).map_err(|e| format!("v{version} state parse failed: {e}"))
+}
+
+/// from 버전 모델로 읽고 v8까지 올린다. v7과 v8은 같은 모델로 읽는다(v8은 검증만).
+pub fn run(state: Value, from: u32) -> Result<(AppState, Vec<StepReport>), String> {
+  let mut out: Vec<StepReport> = Vec::new();
+  let s7: v7::State = match from {
+    1 => {
+      let s = v1_to_v2(parse(state, 1)?, &mut out);
+      let s = v2_to_v3(s, &mut out);
+      let s = v3_to_v4(s, &mut out);
+      let s = v4_to_v5(s, &mut out);
+      let s = v5_to_v6(s, &mut out);
+      v6_to_v7(s, &mut out)
+    }
+    2 => {
+      let s = v2_to_v3(parse(state, 2)?, &mut out);
+      let s = v3_to_v4(s, &mut out);
+      let s = v4_to_v5(s, &mut out);
+      let s = v5_to_v6(s, &mut out);
+      v6_to_v7(s, &mut out)
+    }
+    3 => {
+      let s = v3_to_v4(parse(state, 3)?, &mut out);
+      let s = v4_to_v5(s, &mut out);
+      let s = v5_to_v6(s, &mut out);
+      v6_to_v7(s, &mut out)
+    }
+    4 => {
+      let s = v4_to_v5(parse(state, 4)?, &mut out);
+      let s = v5_to_v6(s, &mut out);
+      v6_to_v7(s, &mut out)
+    }
+    5 => {
+      let s = v5_to_v6(parse(state, 5)?, &mut out);
+      v6_to_v7(s, &mut out)
+    }
+    6 => v6_to_v7(parse(state, 6)?, &mut out),
+    7 | 8 => parse(state, from)?,
+    v => return Err(format!("unknown state version: {v}")),
+  };
+  let state = v7_to_v8(s7, from, &mut out);
+  // 검증 단계에서 바꾼 게 없으면 보고서에 남기지 않는다
+  if from == CURRENT_VERSION {
+    out.retain(|s| !s.changes.is_empty());
+  }
+  Ok((state, out))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::upgrade;
+  use super::*;
+  use serde_json::json;
+
+  /// 버전 v의 빌드가 저장했을 모양(기록 1건, 케이스 1건)
+  fn fixture(v: u32) -> Value {
+    let mut rec = json!({
+      "id": "r1", "ts": "2024-03-01T09:00:00Z", "storeType": "메모", "lv": "LV3",
+      "actor": {"type": "학생", "name": "김철수"}, "place": "교실", "summary": "수업 중 폭언",
+    });
+    let mut case = json!({
+      "id": "c1", "title": "사건1", "actors": [{"type": "학생", "name": "김철수"}],
+      "status": "진행중", "createdAt": "2024-03-03T00:00:00Z", "query": "폭언",
+      "steps": [{"id": "s1", "ts": "2024-03-03T10:00:00Z", "text": "학부모 상담\n전화로 경위 설명"}],
+    });
+    if v >= 2 {
+      rec["related"] = json!([{"type": "학부모", "name": "김모"}]);
+      rec["storeOther"] = json!("");
+      rec["placeOther"] = json!("");
+    }
+    if v >= 3 {
+      case["sensFilter"] = json!("any");
+      case["onlyMainActor"] = json!(true);
+    }
+    if v >= 4 {
+      case["steps"] = json!([{"id": "s1", "ts": "2024-03-03T10:00:00Z", "name": "학부모 상담", "note": "전화로 경위 설명"}]);
+    }
+    if v >= 5 {
+      case["timeFrom"] = json!("2024-03-01T00:00:00Z");
+      case["timeTo"] = json!("");
+      case["maxResults"] = json!(50);
+    }
+    if v >= 6 {
+      case["recordIds"] = json!(["r1"]);
+      case["scoreByRecordId"] = json!({"r1": 0.9});
+    }
+    if v >= 7 {
+      case["advisors"] = json!([]);
+      case["mode"] = json!("smart");
+    }
+    json!({"v": v, "tab": "cases", "selectedCaseId": "c1", "records": [rec], "cases": {"c1": case}})
+  }
+
+  #[test]
+  fn every_old_version_reaches_v8() {
+    for v in 1..CURRENT_VERSION {
+      let up = upgrade(fixture(v)).unwrap_or_else(|e| panic!("v{v}: {e}"));
+      let r = &up.report;
+      assert_eq!((r.from_version, r.to_version, r.assumed_version), (v, CURRENT_VERSION, false));
+      // 한 단계씩 빠짐없이 이어진다
+      let hops: Vec<(u32, u32)> = r.steps.iter().map(|s| (s.from, s.to)).collect();
+      let want: Vec<(u32, u32)> = (v..CURRENT_VERSION).map(|n| (n, n + 1)).collect();
+      assert_eq!(hops, want, "v{v}");
+
+      let st = serde_json::to_value(&up.state).unwrap();
+      assert_eq!(st["v"], CURRENT_VERSION, "v{v}");
+      assert_eq!(st["selectedCaseId"], "c1", "v{v}");
+      let rec = &st["records"][0];
+      assert_eq!(rec["summary"], "수업 중 폭언", "v{v}");
+      assert_eq!(rec["related"].as_array().unwrap().len(), usize::from(v >= 2), "v{v}");
+      let c = &st["cases"]["c1"];
+      assert_eq!(c["steps"][0]["name"], "학부모 상담", "v{v}");
+      assert_eq!(c["steps"][0]["note"], "전화로 경위 설명", "v{v}");
+      assert_eq!(c["onlyMainActor"], v >= 3, "v{v}");
+      assert_eq!(c["maxResults"], if v >= 5 { json!(50) } else { Value::Null }, "v{v}");
+      assert_eq!(c["recordIds"], if v >= 6 { json!(["r1"]) } else { json!([]) }, "v{v}");
+      assert_eq!(c["mode"], if v >= 7 { json!("smart") } else { Value::Null }, "v{v}");
+    }
+  }
+
+  #[test]
+  fn unversioned_files_are_guessed_from_fields() {
+    for v in 1..CURRENT_VERSION {
+      let mut f = fixture(v);
+      f.as_object_mut().unwrap().remove("v");
+      let up = upgrade(f).unwrap();
+      assert!(up.report.assumed_version);
+      assert_eq!(up.report.from_version, v, "v{v}");
+    }
+  }
+
+  #[test]
+  fn v1_report_lists_defaults_and_conversions() {
+    let up = upgrade(fixture(1)).unwrap();
+    let first = &up.report.steps[0];
+    assert!(first.changes.iter().any(|c| c.kind == ChangeKind::Defaulted && c.path == "records[].related"));
+    let split = up.report.steps.iter().flat_map(|s| &s.changes).find(|c| c.path == "cases[].steps[].text").unwrap();
+    assert_eq!((split.kind, split.count, split.examples.clone()), (ChangeKind::Converted, 1, vec!["c1".to_string()]));
+  }
+
+  #[test]
+  fn v8_round_trip_is_noop_and_newer_is_rejected() {
+    let up = upgrade(fixture(7)).unwrap();
+    let again = upgrade(serde_json::to_value(&up.state).unwrap()).unwrap();
+    assert!(again.report.is_noop());
+
+    let mut newer = fixture(7);
+    newer["v"] = json!(CURRENT_VERSION + 1);
+    let e = upgrade(newer).unwrap_err();
+    assert!(e.contains("새 버전"), "{e}");
+  }
+}
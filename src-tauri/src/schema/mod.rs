@@ -0,0 +1,191 @@
+// src-tauri/src/schema/mod.rs
+// AppState 버전 관리: 예전 저장 파일/백업(v1~v7)을 읽어 현재 모양(v8)으로 한 단계씩 올린다.
+// 단계마다 기본값으로 채운 필드/버린 필드/바꾼 값을 보고서로 남긴다.
+
+pub mod migrate;
+pub mod models;
+
+use crate::engine::{CaseItem, CaseStatus, RecordItem, StepItem};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// v8: Rust가 검증한 모양(필드가 빠짐없이 채워지고, 값은 허용 목록 안)
+pub const CURRENT_VERSION: u32 = 8;
+
+/* -------------------- current model (v8) -------------------- */
+
+/// 저장용 케이스: 엔진 CaseItem + 상태/단계 + 나머지 필드(scoreByRecordId 등은 JSON 그대로)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredCase {
+  #[serde(flatten)]
+  pub case_item: CaseItem,
+  #[serde(default)]
+  pub status: CaseStatus,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub mode: Option<String>,
+  #[serde(default)]
+  pub steps: Vec<StepItem>,
+  /// 마지막으로 고친 시각(제목/검색어/연결/단계 등 무엇이든, v8부터)
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub updated_at: String,
+  #[serde(flatten)]
+  pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppState {
+  pub v: u32,
+  /// "records" | "cases"
+  pub tab: String,
+  pub selected_case_id: Option<String>,
+  pub records: Vec<RecordItem>,
+  /// 케이스 id → 케이스
+  pub cases: BTreeMap<String, StoredCase>,
+}
+
+/* -------------------- migration report -------------------- */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+  /// 없던 필드를 기본값으로 채움
+  Defaulted,
+  /// 모델에 없거나 쓸 수 없는 값을 버림
+  Dropped,
+  /// 값을 새 모양으로 옮기거나 허용 값으로 바꿈
+  Converted,
+}
+
+/// 같은 종류/경로의 변경은 하나로 묶어 센다(기록 수천 건이어도 보고서는 짧게)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+  pub kind: ChangeKind,
+  /// "records[].related", "cases[].steps[].text" 처럼 배열은 []로
+  pub path: String,
+  pub count: usize,
+  /// 해당 기록/케이스 id 몇 개(최대 3)
+  pub examples: Vec<String>,
+  pub note: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepReport {
+  pub from: u32,
+  pub to: u32,
+  pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationReport {
+  pub from_version: u32,
+  pub to_version: u32,
+  /// 파일에 v가 없어 필드 모양으로 버전을 짐작했는지
+  pub assumed_version: bool,
+  /// v8 파일이면 검증 단계(8 → 8) 하나만, 바꾼 게 없으면 비어 있음
+  pub steps: Vec<StepReport>,
+}
+
+impl MigrationReport {
+  pub fn is_noop(&self) -> bool {
+    self.from_version == self.to_version && self.steps.iter().all(|s| s.changes.is_empty())
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Upgraded {
+  pub state: AppState,
+  pub report: MigrationReport,
+}
+
+/* -------------------- entry -------------------- */
+
+/// 백업 팩({v, exportedAt, state})이면 안쪽 state를 꺼낸다. 버전은 state.v → 팩 v 순서.
+fn unwrap_pack(value: Value) -> Result<(Value, Option<u32>), String> {
+  let Value::Object(mut o) = value else {
+    return Err("state is not an object".into());
+  };
+  let pack_v = o.get("v").and_then(version_of);
+  if let Some(Value::Object(inner)) = o.remove("state") {
+    let v = inner.get("v").and_then(version_of).or(pack_v);
+    return Ok((Value::Object(inner), v));
+  }
+  Ok((Value::Object(o), pack_v))
+}
+
+fn version_of(v: &Value) -> Option<u32> {
+  match v {
+    Value::Number(n) => n.as_u64().map(|n| n.min(u32::MAX as u64) as u32),
+    Value::String(s) => s.trim().parse().ok(),
+    _ => None,
+  }
+}
+
+/// v가 없는 파일: 나중 버전에만 있는 필드가 보이면 그 버전으로 본다
+fn guess_version(state: &Value) -> u32 {
+  let cases: Vec<&Map<String, Value>> = state
+    .get("cases")
+    .and_then(|c| c.as_object())
+    .map(|m| m.values().filter_map(|c| c.as_object()).collect())
+    .unwrap_or_default();
+  let records: Vec<&Map<String, Value>> = state
+    .get("records")
+    .and_then(|r| r.as_array())
+    .map(|a| a.iter().filter_map(|r| r.as_object()).collect())
+    .unwrap_or_default();
+  let case_has = |k: &str| cases.iter().any(|c| c.contains_key(k));
+  let step_has = |k: &str| {
+    cases.iter().any(|c| {
+      c.get("steps")
+        .and_then(|s| s.as_array())
+        .is_some_and(|s| s.iter().any(|x| x.get(k).is_some()))
+    })
+  };
+
+  if case_has("advisors") || case_has("mode") || case_has("componentsByRecordId") {
+    7
+  } else if case_has("recordIds") || case_has("scoreByRecordId") {
+    6
+  } else if case_has("timeFrom") || case_has("timeTo") || case_has("maxResults") {
+    5
+  } else if step_has("name") || step_has("note") {
+    4
+  } else if case_has("sensFilter") || case_has("onlyMainActor") {
+    3
+  } else if records.iter().any(|r| r.contains_key("related") || r.contains_key("storeOther")) {
+    2
+  } else {
+    1
+  }
+}
+
+/// 어느 버전의 AppState(또는 백업 팩)든 v8로 올린다
+pub fn upgrade(value: Value) -> Result<Upgraded, String> {
+  let (inner, declared) = unwrap_pack(value)?;
+  let (from, assumed) = match declared {
+    Some(v) => (v.max(1), false),
+    None => (guess_version(&inner), true),
+  };
+  if from > CURRENT_VERSION {
+    return Err(format!(
+      "저장 데이터 v{from}은 이 앱(v{CURRENT_VERSION})보다 새 버전이에요 — 앱을 업데이트한 뒤 열어 주세요"
+    ));
+  }
+  let (state, steps) = migrate::run(inner, from)?;
+  Ok(Upgraded {
+    state,
+    report: MigrationReport {
+      from_version: from,
+      to_version: CURRENT_VERSION,
+      assumed_version: assumed,
+      steps,
+    },
+  })
+}
@@ -0,0 +1,469 @@
+// 예전 빌드가 저장/백업한 AppState 모양(v1~v7).
+// 모든 필드를 관대하게 읽는다: 빠진 필드는 None/빈 값, 숫자/불리언이 문자열 자리에 와도 문자열로.
+// 모델에 없는 필드는 rest에 모였다가 마이그레이션 보고서에 "버림"으로 남는다.
+
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/* -------------------- lenient field readers -------------------- */
+
+fn scalar_to_string(v: Value) -> Option<String> {
+  match v {
+    Value::String(s) => Some(s),
+    Value::Number(n) => Some(n.to_string()),
+    Value::Bool(b) => Some(b.to_string()),
+    _ => None,
+  }
+}
+
+/// 문자열 자리: null/객체/배열이면 None
+pub(crate) fn opt_string<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+  Ok(scalar_to_string(Value::deserialize(d)?))
+}
+
+pub(crate) fn string<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+  Ok(scalar_to_string(Value::deserialize(d)?).unwrap_or_default())
+}
+
+/// 음수/소수/문자열 숫자도 받는다. 숫자로 읽을 수 없으면 None.
+pub(crate) fn opt_u32<'de, D: Deserializer<'de>>(d: D) -> Result<Option<u32>, D::Error> {
+  let n = match Value::deserialize(d)? {
+    Value::Number(n) => n.as_f64(),
+    Value::String(s) => s.trim().parse::<f64>().ok(),
+    _ => None,
+  };
+  Ok(n.filter(|n| n.is_finite()).map(|n| n.clamp(0.0, u32::MAX as f64) as u32))
+}
+
+/// 불리언이 아니면 1과 "true"만 true, null/객체/배열은 None
+pub(crate) fn opt_bool<'de, D: Deserializer<'de>>(d: D) -> Result<Option<bool>, D::Error> {
+  Ok(match Value::deserialize(d)? {
+    Value::Bool(b) => Some(b),
+    Value::Number(n) => Some(n.as_f64() == Some(1.0)),
+    Value::String(s) => Some(s == "true"),
+    _ => None,
+  })
+}
+
+/// 배열이 아니면 None, 원소는 그대로(원소 검사는 각 모델에서)
+pub(crate) fn opt_vec<'de, D: Deserializer<'de>, T: for<'a> Deserialize<'a>>(d: D) -> Result<Option<Vec<T>>, D::Error> {
+  match Value::deserialize(d)? {
+    Value::Array(items) => items
+      .into_iter()
+      .map(|x| serde_json::from_value(x).map_err(serde::de::Error::custom))
+      .collect::<Result<Vec<T>, _>>()
+      .map(Some),
+    _ => Ok(None),
+  }
+}
+
+fn opt_string_vec<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<String>>, D::Error> {
+  Ok(match Value::deserialize(d)? {
+    Value::Array(items) => Some(items.into_iter().filter_map(scalar_to_string).collect()),
+    _ => None,
+  })
+}
+
+fn opt_object<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Map<String, Value>>, D::Error> {
+  Ok(match Value::deserialize(d)? {
+    Value::Object(m) => Some(m),
+    _ => None,
+  })
+}
+
+/* -------------------- shared pieces -------------------- */
+
+/// 당사자(모든 버전 공통)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Actor {
+  #[serde(default, rename = "type", deserialize_with = "opt_string")]
+  pub r#type: Option<String>,
+  #[serde(default, deserialize_with = "string")]
+  pub name: String,
+}
+
+/// records/cases를 뺀 AppState 껍데기(모든 버전 공통)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", bound(deserialize = "R: Deserialize<'de>, C: Deserialize<'de>"))]
+pub struct StateOf<R, C> {
+  #[serde(default, deserialize_with = "opt_u32")]
+  pub v: Option<u32>,
+  #[serde(default, deserialize_with = "opt_string")]
+  pub tab: Option<String>,
+  #[serde(default, deserialize_with = "opt_string")]
+  pub selected_case_id: Option<String>,
+  #[serde(default)]
+  pub records: Vec<R>,
+  #[serde(default)]
+  pub cases: BTreeMap<String, C>,
+  #[serde(flatten)]
+  pub rest: Map<String, Value>,
+}
+
+/* -------------------- v1 -------------------- */
+
+/// v1: 기록은 주 당사자 하나, 케이스 단계는 자유 텍스트(text) 한 칸
+pub mod v1 {
+  use super::*;
+
+  #[derive(Debug, Clone, Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  pub struct Record {
+    #[serde(default, deserialize_with = "opt_string")]
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub ts: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub store_type: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub lv: Option<String>,
+    #[serde(default)]
+    pub actor: Option<Actor>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub place: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub summary: String,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+  }
+
+  #[derive(Debug, Clone, Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  pub struct Step {
+    #[serde(default, deserialize_with = "opt_string")]
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub ts: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub text: String,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub place: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub owner: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub lv: Option<String>,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+  }
+
+  #[derive(Debug, Clone, Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  pub struct Case {
+    #[serde(default, deserialize_with = "opt_string")]
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub title: String,
+    #[serde(default, deserialize_with = "opt_vec")]
+    pub actors: Option<Vec<Actor>>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub created_at: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub query: String,
+    #[serde(default, deserialize_with = "opt_vec")]
+    pub steps: Option<Vec<Step>>,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+  }
+
+  pub type State = StateOf<Record, Case>;
+}
+
+/* -------------------- v2 -------------------- */
+
+/// v2: 기록에 관련자(related)와 "기타" 입력칸(storeOther/placeOther)
+pub mod v2 {
+  use super::*;
+
+  #[derive(Debug, Clone, Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  pub struct Record {
+    #[serde(default, deserialize_with = "opt_string")]
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub ts: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub store_type: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub store_other: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub lv: Option<String>,
+    #[serde(default)]
+    pub actor: Option<Actor>,
+    #[serde(default, deserialize_with = "opt_vec")]
+    pub related: Option<Vec<Actor>>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub place: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub place_other: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub summary: String,
+    /// v8 파일에만 있다
+    #[serde(default, deserialize_with = "opt_string")]
+    pub updated_at: Option<String>,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+  }
+
+  pub type State = StateOf<Record, super::v1::Case>;
+}
+
+/* -------------------- v3 -------------------- */
+
+/// v3: 케이스 민감도 필터(sensFilter)와 "주 당사자만"(onlyMainActor)
+pub mod v3 {
+  use super::*;
+
+  #[derive(Debug, Clone, Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  pub struct Case {
+    #[serde(default, deserialize_with = "opt_string")]
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub title: String,
+    #[serde(default, deserialize_with = "opt_vec")]
+    pub actors: Option<Vec<Actor>>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub sens_filter: Option<String>,
+    #[serde(default, deserialize_with = "opt_bool")]
+    pub only_main_actor: Option<bool>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub created_at: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub query: String,
+    #[serde(default, deserialize_with = "opt_vec")]
+    pub steps: Option<Vec<super::v1::Step>>,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+  }
+
+  pub type State = StateOf<super::v2::Record, Case>;
+}
+
+/* -------------------- v4 -------------------- */
+
+/// v4: 단계가 이름(name) + 메모(note)로 나뉨. text/place/owner/lv는 예전 칸으로 남음
+pub mod v4 {
+  use super::*;
+
+  #[derive(Debug, Clone, Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  pub struct Step {
+    #[serde(default, deserialize_with = "opt_string")]
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub ts: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub name: String,
+    #[serde(default, deserialize_with = "string")]
+    pub note: String,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub text: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub place: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub owner: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub lv: Option<String>,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+  }
+
+  #[derive(Debug, Clone, Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  pub struct Case {
+    #[serde(default, deserialize_with = "opt_string")]
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub title: String,
+    #[serde(default, deserialize_with = "opt_vec")]
+    pub actors: Option<Vec<Actor>>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub sens_filter: Option<String>,
+    #[serde(default, deserialize_with = "opt_bool")]
+    pub only_main_actor: Option<bool>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub created_at: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub query: String,
+    #[serde(default, deserialize_with = "opt_vec")]
+    pub steps: Option<Vec<Step>>,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+  }
+
+  pub type State = StateOf<super::v2::Record, Case>;
+}
+
+/* -------------------- v5 -------------------- */
+
+/// v5: 케이스 기간(timeFrom/timeTo)과 최대 결과 수(maxResults)
+pub mod v5 {
+  use super::*;
+
+  #[derive(Debug, Clone, Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  pub struct Case {
+    #[serde(default, deserialize_with = "opt_string")]
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub title: String,
+    #[serde(default, deserialize_with = "opt_vec")]
+    pub actors: Option<Vec<Actor>>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub sens_filter: Option<String>,
+    #[serde(default, deserialize_with = "opt_bool")]
+    pub only_main_actor: Option<bool>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub created_at: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub query: String,
+    #[serde(default, deserialize_with = "string")]
+    pub time_from: String,
+    #[serde(default, deserialize_with = "string")]
+    pub time_to: String,
+    #[serde(default, deserialize_with = "opt_u32")]
+    pub max_results: Option<u32>,
+    #[serde(default, deserialize_with = "opt_vec")]
+    pub steps: Option<Vec<super::v4::Step>>,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+  }
+
+  pub type State = StateOf<super::v2::Record, Case>;
+}
+
+/* -------------------- v6 -------------------- */
+
+/// v6: 케이스가 모은 기록 목록(recordIds)과 점수 스냅샷(scoreByRecordId)을 직접 가짐
+pub mod v6 {
+  use super::*;
+
+  #[derive(Debug, Clone, Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  pub struct Case {
+    #[serde(default, deserialize_with = "opt_string")]
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub title: String,
+    #[serde(default, deserialize_with = "opt_vec")]
+    pub actors: Option<Vec<Actor>>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub sens_filter: Option<String>,
+    #[serde(default, deserialize_with = "opt_bool")]
+    pub only_main_actor: Option<bool>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub created_at: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub query: String,
+    #[serde(default, deserialize_with = "string")]
+    pub time_from: String,
+    #[serde(default, deserialize_with = "string")]
+    pub time_to: String,
+    #[serde(default, deserialize_with = "opt_u32")]
+    pub max_results: Option<u32>,
+    #[serde(default, deserialize_with = "opt_vec")]
+    pub steps: Option<Vec<super::v4::Step>>,
+    #[serde(default, deserialize_with = "opt_string_vec")]
+    pub record_ids: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "opt_object")]
+    pub score_by_record_id: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+  }
+
+  pub type State = StateOf<super::v2::Record, Case>;
+}
+
+/* -------------------- v7 -------------------- */
+
+/// v7: 권고(advisors), 모으기 방식(mode), 점수 구성요소 스냅샷(componentsByRecordId)
+pub mod v7 {
+  use super::*;
+
+  #[derive(Debug, Clone, Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  pub struct Advisor {
+    #[serde(default, deserialize_with = "opt_string")]
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub ts: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub title: String,
+    #[serde(default, deserialize_with = "string")]
+    pub body: String,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub level: Option<String>,
+    #[serde(default, deserialize_with = "opt_string_vec")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub state: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub rule_id: Option<String>,
+    #[serde(default, deserialize_with = "opt_bool")]
+    pub stale: Option<bool>,
+    #[serde(default, deserialize_with = "opt_string_vec")]
+    pub reasons: Option<Vec<String>>,
+    #[serde(default)]
+    pub explain: Option<Value>,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+  }
+
+  #[derive(Debug, Clone, Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  pub struct Case {
+    #[serde(default, deserialize_with = "opt_string")]
+    pub id: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub title: String,
+    #[serde(default, deserialize_with = "opt_vec")]
+    pub actors: Option<Vec<Actor>>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub sens_filter: Option<String>,
+    #[serde(default, deserialize_with = "opt_bool")]
+    pub only_main_actor: Option<bool>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub created_at: Option<String>,
+    #[serde(default, deserialize_with = "string")]
+    pub query: String,
+    #[serde(default, deserialize_with = "string")]
+    pub time_from: String,
+    #[serde(default, deserialize_with = "string")]
+    pub time_to: String,
+    #[serde(default, deserialize_with = "opt_u32")]
+    pub max_results: Option<u32>,
+    #[serde(default, deserialize_with = "opt_vec")]
+    pub steps: Option<Vec<super::v4::Step>>,
+    #[serde(default, deserialize_with = "opt_vec")]
+    pub advisors: Option<Vec<Advisor>>,
+    #[serde(default, deserialize_with = "opt_string")]
+    pub mode: Option<String>,
+    #[serde(default, deserialize_with = "opt_string_vec")]
+    pub record_ids: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "opt_object")]
+    pub score_by_record_id: Option<Map<String, Value>>,
+    #[serde(default, deserialize_with = "opt_object")]
+    pub components_by_record_id: Option<Map<String, Value>>,
+    /// v8 파일에만 있다
+    #[serde(default, deserialize_with = "opt_string")]
+    pub updated_at: Option<String>,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+  }
+
+  pub type State = StateOf<super::v2::Record, Case>;
+}
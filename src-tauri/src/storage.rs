@@ -0,0 +1,116 @@
+// src-tauri/src/storage.rs
+// 예전 JSON 저장 파일(AppDataDir/roosycozy_state_v1.json + 세대 .1~.5) 읽기.
+// 지금은 db.rs가 저장을 맡고, 이 파일은 DB가 비어 있는 첫 실행 때 옮겨 올 데이터를 읽는 데만 쓴다.
+
+use crate::engine::fsutil::sync_dir;
+use crate::schema::MigrationReport;
+
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const STATE_FILE: &str = "roosycozy_state_v1.json";
+
+/// 직전 저장본을 몇 세대까지 남길지(roosycozy_state_v1.json.1 이 가장 최근)
+pub const STATE_GENERATIONS: usize = 5;
+
+/// 손상된 본 파일은 지우지 않고 이 이름으로 옮겨 둔다(덮어씀)
+const CORRUPT_SUFFIX: &str = "corrupt";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedState {
+  /// 저장된 AppState(없으면 None — 첫 실행)
+  pub state: Option<Value>,
+  /// "current" | "generation:N" | "none" (DB에서 읽었으면 "sqlite" | "snapshot:N")
+  pub source: String,
+  /// 본 파일이 손상돼 이전 세대에서 읽었는지
+  pub recovered: bool,
+  /// 읽다가 건너뛴 파일과 이유
+  pub errors: Vec<String>,
+  /// 예전 버전 파일을 v8로 올렸으면 그 보고서
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub migration: Option<MigrationReport>,
+}
+
+fn generation_path(dir: &Path, n: usize) -> PathBuf {
+  dir.join(format!("{STATE_FILE}.{n}"))
+}
+
+/// AppState 모양인지 가볍게 확인(객체, records는 배열, cases는 객체)
+fn check_shape(v: &Value) -> Result<(), String> {
+  let o = v.as_object().ok_or("state is not an object")?;
+  if o.get("records").is_some_and(|r| !r.is_array()) {
+    return Err("records is not an array".into());
+  }
+  if o.get("cases").is_some_and(|c| !c.is_object()) {
+    return Err("cases is not an object".into());
+  }
+  Ok(())
+}
+
+fn read_state_file(path: &Path) -> Result<Value, String> {
+  let text = fs::read_to_string(path).map_err(|e| format!("read failed: {e}"))?;
+  let v: Value = serde_json::from_str(&text).map_err(|e| format!("parse failed: {e}"))?;
+  check_shape(&v)?;
+  Ok(v)
+}
+
+/// 본 파일 → 없으면/손상이면 .1, .2, … 순서로 처음 멀쩡한 것.
+/// 본 파일이 없는데 세대가 있으면 회전과 rename 사이에 멈춘 것이므로 복구로 본다.
+pub fn load_state(dir: &Path) -> LoadedState {
+  let mut errors: Vec<String> = Vec::new();
+  let current = dir.join(STATE_FILE);
+
+  let mut current_bad = false;
+  if current.exists() {
+    match read_state_file(&current) {
+      Ok(v) => {
+        return LoadedState {
+          state: Some(v),
+          source: "current".into(),
+          recovered: false,
+          errors,
+          migration: None,
+        }
+      }
+      Err(e) => {
+        errors.push(format!("{STATE_FILE}: {e}"));
+        current_bad = true;
+      }
+    }
+  }
+
+  for n in 1..=STATE_GENERATIONS {
+    let p = generation_path(dir, n);
+    if !p.exists() {
+      continue;
+    }
+    match read_state_file(&p) {
+      Ok(v) => {
+        // 다음 저장 때 손상본이 세대로 밀려 들어가지 않게 옮겨 둔다
+        if current_bad {
+          let _ = fs::rename(&current, dir.join(format!("{STATE_FILE}.{CORRUPT_SUFFIX}")));
+          sync_dir(dir);
+        }
+        return LoadedState {
+          state: Some(v),
+          source: format!("generation:{n}"),
+          recovered: true,
+          errors,
+          migration: None,
+        };
+      }
+      Err(e) => errors.push(format!("{STATE_FILE}.{n}: {e}")),
+    }
+  }
+
+  LoadedState {
+    state: None,
+    source: "none".into(),
+    recovered: false,
+    errors,
+    migration: None,
+  }
+}
@@ -1,5 +1,5 @@
 // src/engine.ts
-import { rustGenerateAdvisorsForCase, rustRankRecordsForCase } from './engine_rust';
+import { rustGenerateAdvisorsForCase, rustRankIndexed, rustRegenerateAdvisorsForCase, updateRecordStore } from './engine_rust';
 
 /* -------------------- Types -------------------- */
 
@@ -20,6 +20,7 @@ export type RecordItem = {
   place: PlaceType;
   placeOther: string;
   summary: string;
+  updatedAt?: string; // 마지막으로 고친 시각(ts는 사건 시각) — 저장할 때 바뀐 기록에만 찍는다
 };
 
 export type CaseSensFilter = 'any' | Sensitivity;
@@ -39,6 +40,15 @@ export type StepItem = {
 export type AdvisorLevel = 'info' | 'warn' | 'critical';
 export type AdvisorState = 'active' | 'done' | 'dismissed';
 
+export type AdvisorConditionHit = { key: string; values: string[]; recordIds: string[] };
+
+export type AdvisorExplain = {
+  matchedRecordIds: string[];
+  anchorType: string;
+  anchorRecordId?: string | null;
+  conditions: AdvisorConditionHit[];
+};
+
 export type AdvisorItem = {
   id: string;
   ts: string;
@@ -48,6 +58,10 @@ export type AdvisorItem = {
   tags: string[];
   state: AdvisorState;
   ruleId?: string;
+  stale?: boolean;
+  // Rust가 채워주는 발동 근거
+  reasons?: string[];
+  explain?: AdvisorExplain;
   [k: string]: any;
 };
 
@@ -57,19 +71,41 @@ export type RankedComponents = {
   textSim: number;
   qHit: number;
   qTotal: number;
+  tokenizer?: string;
+  textMode?: string;
+  bm25Terms?: { term: string; idf: number; df: number; tf: number }[];
+  phraseScore?: number; // 쿼리 토큰이 붙어/가까이 나온 가산
 
   actorScore: number;
   actorMatch: boolean;
   isMainActor: boolean;
+  actorTypeWeight?: number; // 당사자 유형별 배수
 
   relatedScore: number;
   relatedHits: number;
 
+  secondDegreeScore?: number; // 케이스 당사자와 자주 함께 나오는 사람의 기록 가산
+  secondDegreeCooc?: number;
+
   inRange?: boolean;
+  tsValid?: boolean;
+  tsUtc?: string | null;
+
+  sensScore?: number;
+  sensLv?: number | null;
+  sensMin?: number | null;
+
+  timeScore?: number;
+  timeRef?: string | null;
+  timeDistDays?: number | null;
 
   wActor: number;
   wRelated: number;
   wText: number;
+  wTime?: number;
+  wSens?: number;
+  wPhrase?: number;
+  wSecond?: number;
   minScore: number;
   minTextSim: number;
 };
@@ -82,6 +118,7 @@ export type CaseItem = {
   onlyMainActor?: boolean;
   status: CaseStatus;
   createdAt: string;
+  updatedAt?: string; // 마지막으로 고친 시각(제목/검색어/연결/단계 등)
   steps: StepItem[];
   advisors?: AdvisorItem[];
   query?: string;
@@ -156,17 +193,17 @@ export async function rankRecordsForCase(
   c: CaseItem,
   opts?: {
     limit?: number;
-    weights?: { actor?: number; related?: number; text?: number; time?: number };
+    weights?: { actor?: number; related?: number; text?: number; time?: number; sens?: number };
     minScore?: number;
     minTextSim?: number;
   }
 ): Promise<RankedHit[]> {
-  // Rust에서 전체 데이터(reasons, components 포함)를 받아옴
-  const main = (c as any).onlyMainActor ? ((c.actors || [])[0] ?? null) : null;
-  const scoped = main ? records.filter((r) => actorEq(r.actor, main)) : records;
-  const hits: any[] = await rustRankRecordsForCase(scoped, c, opts);
+  // Rust 기록 저장소를 records에 맞춘 뒤(바뀐 것만 전송) 케이스만 넘겨 랭킹
+  // onlyMainActor / sensFilter 등 범위 지정도 Rust가 처리
+  await updateRecordStore(records);
+  const hits: any[] = await rustRankIndexed(c, opts);
 
-  const map = new Map(scoped.map((r) => [r.id, r]));
+  const map = new Map(records.map((r) => [r.id, r]));
 
   return hits
     .map((h) => {
@@ -187,7 +224,7 @@ export async function rankRecordsForCase(
 }
 
 export const regenerateCaseAdvisors = (c: CaseItem, records: RecordItem[]) =>
-  rustGenerateAdvisorsForCase(records, c);
+  rustRegenerateAdvisorsForCase(records, c);
 
 /* -------------------- builders -------------------- */
 
@@ -1,35 +1,369 @@
 // src/engine_rust.ts
 import { invoke } from '@tauri-apps/api/core';
-import type { CaseItem, RecordItem, AdvisorItem, RankedHit } from './engine';
+import type { CaseItem, RecordItem, AdvisorItem, RankedHit, StepItem } from './engine';
+
+export type RustRankOpts = {
+  limit?: number; // 최대 결과 개수
+  weights?: {
+    actor?: number;
+    related?: number;
+    text?: number;
+    time?: number; // 시간 근접(기본 0 = 끔)
+    sens?: number;
+    phrase?: number;
+    secondDegree?: number; // 2차 관계(기본 0.5)
+    actorTypes?: Record<string, number>; // 예: { 학부모: 1.2, 관리자: 0.6 }
+  };
+  minScore?: number;
+  minTextSim?: number; // 0~1, query 토큰 부분일치 비율
+  timeZone?: string; // 오프셋 없는 시각 해석 기준(기본 Asia/Seoul)
+  tokenizer?: 'korean' | 'simple';
+  textMode?: 'token' | 'ngram' | 'bm25';
+  proximityWindow?: number; // 근접 가산 범위(토큰 사이 공백 제외 글자 수)
+};
+
+function toRankOpts(opts?: RustRankOpts) {
+  return opts
+    ? {
+        maxResults: opts.limit,
+        weights: opts.weights,
+        minScore: opts.minScore,
+        minTextSim: opts.minTextSim,
+        timeZone: opts.timeZone,
+        tokenizer: opts.tokenizer,
+        textMode: opts.textMode,
+        proximityWindow: opts.proximityWindow,
+      }
+    : undefined;
+}
 
 export async function rustRankRecordsForCase(
   records: RecordItem[],
   caseItem: CaseItem,
-  opts?: {
-    limit?: number; // 최대 결과 개수
-    weights?: { actor?: number; related?: number; text?: number; time?: number };
-    minScore?: number;
-    minTextSim?: number; // 0~1, query 토큰 부분일치 비율
-  }
+  opts?: RustRankOpts
 ): Promise<RankedHit[]> {
   // 반환 타입을 RankedHit[]로 지정하여 Rust가 주는 상세 정보(reasons, components 등)를 모두 받습니다.
-  return invoke('engine_rank', {
-    records,
-    caseItem,
-    opts: opts
-      ? {
-          maxResults: opts.limit,
-          weights: opts.weights,
-          minScore: opts.minScore,
-          minTextSim: opts.minTextSim,
-        }
-      : undefined,
-  });
+  return invoke('engine_rank', { records, caseItem, opts: toRankOpts(opts) });
+}
+
+/* -------------------- record store (Rust 상주 기록 + 역색인) -------------------- */
+
+// 전체 교체(앱 시작/백업 복원 후). 저장된 기록 수를 돌려준다.
+export async function rustRecordStoreSync(records: RecordItem[]): Promise<number> {
+  return invoke('record_store_sync', { records });
+}
+
+// 추가/수정된 기록만 보낸다
+export async function rustRecordStoreUpsert(records: RecordItem[]): Promise<number> {
+  return invoke('record_store_upsert', { records });
+}
+
+// 실제로 지워진 개수
+export async function rustRecordStoreDelete(ids: string[]): Promise<number> {
+  return invoke('record_store_delete', { ids });
+}
+
+// 저장소에 마지막으로 보낸 기록(id → JSON). null이면 아직 맞춘 적 없음(또는 실패) → 다음에 전체 교체
+let storeMirror: Map<string, string> | null = null;
+
+// 앱 시작/복원 직후: 전체 교체
+export async function syncRecordStore(records: RecordItem[]): Promise<void> {
+  storeMirror = new Map(records.map((r) => [r.id, JSON.stringify(r)]));
+  try {
+    await rustRecordStoreSync(records);
+  } catch (e) {
+    storeMirror = null;
+    throw e;
+  }
+}
+
+// 저장/랭킹 직전: 추가·수정된 기록은 upsert, 사라진 기록은 delete만 보낸다
+export async function updateRecordStore(records: RecordItem[]): Promise<void> {
+  const prev = storeMirror;
+  if (!prev) return syncRecordStore(records);
+  const next = new Map(records.map((r) => [r.id, JSON.stringify(r)]));
+  const changed = records.filter((r) => prev.get(r.id) !== next.get(r.id));
+  const gone = [...prev.keys()].filter((id) => !next.has(id));
+  storeMirror = next;
+  try {
+    if (changed.length) await rustRecordStoreUpsert(changed);
+    if (gone.length) await rustRecordStoreDelete(gone);
+  } catch (e) {
+    storeMirror = null;
+    throw e;
+  }
+}
+
+// engine_rank와 같은 결과, 기록 배열 대신 Rust에 저장된 기록으로 계산
+export async function rustRankIndexed(caseItem: CaseItem, opts?: RustRankOpts): Promise<RankedHit[]> {
+  return invoke('engine_rank_indexed', { caseItem, opts: toRankOpts(opts) });
 }
 
+// warnings: 사용자 규칙(advisor_custom.json)을 못 읽어 기본 규칙만 쓴 경우 등
+type AdviseResult = { advisors: AdvisorItem[]; warnings: string[] };
+
+const takeAdvisors = (r: AdviseResult): AdvisorItem[] => {
+  if (r.warnings.length) console.warn('[advisor]', r.warnings);
+  return r.advisors;
+};
+
 export async function rustGenerateAdvisorsForCase(
   records: RecordItem[],
   caseItem: CaseItem
 ): Promise<AdvisorItem[]> {
-  return invoke('engine_advise', { records, caseItem });
-}
\ No newline at end of file
+  return takeAdvisors(await invoke<AdviseResult>('engine_advise', { records, caseItem }));
+}
+// 기존 advisors(caseItem.advisors)의 state를 유지하고, 더 이상 맞지 않는 권고는 stale로 표시
+export async function rustRegenerateAdvisorsForCase(
+  records: RecordItem[],
+  caseItem: CaseItem
+): Promise<AdvisorItem[]> {
+  return takeAdvisors(await invoke<AdviseResult>('engine_regenerate_advisors', { records, caseItem }));
+}
+
+export type AdvisorLintIssue = {
+  severity: 'error' | 'warn';
+  code: string;
+  path: string;
+  ruleId?: string | null;
+  message: string;
+};
+
+export type AdvisorLintReport = {
+  version?: number | null;
+  ruleCount: number;
+  errorCount: number;
+  warnCount: number;
+  issues: AdvisorLintIssue[];
+};
+
+// json을 생략하면 현재 사용 중인 규칙팩(AppDataDir → 내장)을 검사
+export async function rustLintAdvisorPack(json?: string): Promise<AdvisorLintReport> {
+  return invoke('advisor_lint', { json });
+}
+
+/* -------------------- advisor custom rules (AppDataDir/advisor_custom.json) -------------------- */
+
+export type AdvisorRule = {
+  id: string;
+  priority: number;
+  level: 'info' | 'warn' | 'critical';
+  tags: string[];
+  title: string;
+  body: string[];
+  when: Record<string, unknown>;
+  anchor: { type: string; offsetMinutes: number; match?: { storeType?: string; place?: string } };
+};
+
+export type AdvisorOverlay = { version: number; rules: AdvisorRule[]; disabled: string[] };
+
+export const rustListCustomRules = (): Promise<AdvisorOverlay> => invoke('advisor_custom_list');
+export const rustAddCustomRule = (rule: AdvisorRule): Promise<AdvisorOverlay> => invoke('advisor_custom_add', { rule });
+export const rustUpdateCustomRule = (rule: AdvisorRule): Promise<AdvisorOverlay> => invoke('advisor_custom_update', { rule });
+export const rustDeleteCustomRule = (ruleId: string): Promise<AdvisorOverlay> => invoke('advisor_custom_delete', { ruleId });
+export const rustDisableRule = (ruleId: string, disabled = true): Promise<AdvisorOverlay> =>
+  invoke('advisor_custom_disable', { ruleId, disabled });
+
+/* -------------------- case query language -------------------- */
+
+// 예: 폭언 "교실 밖" -사과 (욕설 OR 막말) place:교실 store:녹취록 lv:>=LV3 actor:학생4 related:교감
+export type QueryNode =
+  | { kind: 'term'; text: string }
+  | { kind: 'phrase'; text: string }
+  | { kind: 'field'; field: 'place' | 'store' | 'lv' | 'actor' | 'related'; op: string; value: string }
+  | { kind: 'not'; node: QueryNode }
+  | { kind: 'and'; nodes: QueryNode[] }
+  | { kind: 'or'; nodes: QueryNode[] };
+
+// start/end: 쿼리 문자열의 문자 위치(end 배타) — 입력창 하이라이트용
+export type QueryError = { message: string; start: number; end: number };
+
+// 문법 오류면 QueryError로 reject
+export async function rustParseQuery(query: string): Promise<QueryNode> {
+  return invoke('engine_parse_query', { query });
+}
+
+/* -------------------- actor co-occurrence network -------------------- */
+
+export type NetworkNode = {
+  id: string; // "유형|이름"
+  type: string;
+  name: string;
+  recordCount: number;
+  asActor: number;
+  asRelated: number;
+  sens: Record<string, number>; // LV1..LV5(형식 오류는 "기타") → 기록 수
+  firstTs?: string | null;
+  lastTs?: string | null;
+};
+
+export type NetworkEdge = {
+  source: string;
+  target: string;
+  cooc: number; // 함께 나온 기록 수
+  spanDays: number;
+  weight: number; // cooc × (1 + ln(1 + spanDays/30))
+  firstTs?: string | null;
+  lastTs?: string | null;
+  recordIds: string[];
+};
+
+export type ActorNetwork = { nodes: NetworkNode[]; edges: NetworkEdge[]; dot: string };
+
+// focus를 주면 그 사람과 직접 연결된 사람만
+export async function rustActorNetwork(
+  records: RecordItem[],
+  opts?: { focus?: { type: string; name: string }; minCooc?: number; timeZone?: string }
+): Promise<ActorNetwork> {
+  return invoke('engine_actor_network', { records, opts });
+}
+
+/* -------------------- case suggestion -------------------- */
+
+export type CaseSuggestion = {
+  caseItem: CaseItem; // 초안(id "SUG_…", createdAt 비어 있음 — 채택 시 새로 붙임)
+  recordIds: string[]; // 시간순
+  confidence: number; // 0~1
+  reasons: string[];
+};
+
+// 당사자 겹침/요약 유사도/시간 근접으로 기록을 묶어 케이스 초안 제안. cases가 이미 대부분 가진 묶음은 제외
+export async function rustSuggestCases(
+  records: RecordItem[],
+  cases?: CaseItem[],
+  opts?: {
+    minRecords?: number;
+    linkThreshold?: number;
+    windowDays?: number;
+    maxSuggestions?: number;
+    tokenizer?: 'korean' | 'simple';
+    timeZone?: string;
+  }
+): Promise<CaseSuggestion[]> {
+  return invoke('engine_suggest_cases', { records, cases, opts });
+}
+
+/* -------------------- synonyms / aliases (AppDataDir/synonyms.json) -------------------- */
+
+// 같은 묶음 안의 말은 서로 같은 뜻으로 본다(terms: 쿼리 키워드, names: 당사자 별칭)
+export type SynonymTable = {
+  version: number;
+  terms: string[][];
+  names: string[][];
+};
+
+// 파일이 없으면 기본 표. 파일이 깨져 기본 표로 랭킹 중이면 warnings가 붙는다.
+export async function rustSynonymsGet(): Promise<SynonymTable & { warnings?: string[] }> {
+  return invoke('synonyms_get');
+}
+
+// 표 전체를 검증 후 저장(빈 항목/한 개짜리 묶음/여러 묶음에 겹친 말은 에러)
+export async function rustSynonymsSave(table: SynonymTable): Promise<SynonymTable> {
+  return invoke('synonyms_save', { table });
+}
+
+export async function rustSynonymsReset(): Promise<SynonymTable> {
+  return invoke('synonyms_reset');
+}
+
+/* -------------------- SQLite (AppDataDir/roosycozy.db) -------------------- */
+
+// save_state 결과: 바뀐 행만 썼는지 확인용. droppedLinks는 없는 기록을 가리켜 버린 "caseId → recordId"
+export type DbSyncReport = {
+  recordsWritten: number;
+  recordsDeleted: number;
+  casesWritten: number;
+  casesDeleted: number;
+  droppedLinks: string[];
+};
+
+// [ ]로 감싼 발췌, score는 클수록 잘 맞음
+export type DbSearchHit = { recordId: string; snippet: string; score: number };
+
+export const rustDbRecordsList = (): Promise<RecordItem[]> => invoke('db_records_list');
+export const rustDbRecordGet = (id: string): Promise<RecordItem | null> => invoke('db_record_get', { id });
+export const rustDbRecordUpsert = (records: RecordItem[]): Promise<number> => invoke('db_record_upsert', { records });
+// 케이스에 포함된 기록이 하나라도 있으면 아무것도 지우지 않고 에러(외래 키)
+export const rustDbRecordDelete = (ids: string[]): Promise<number> => invoke('db_record_delete', { ids });
+
+export const rustDbCasesList = (): Promise<CaseItem[]> => invoke('db_cases_list');
+export const rustDbCaseGet = (id: string): Promise<CaseItem | null> => invoke('db_case_get', { id });
+// recordIds에 없는 기록이 있으면 에러
+export const rustDbCaseUpsert = (caseItem: CaseItem): Promise<void> => invoke('db_case_upsert', { caseItem });
+export const rustDbCaseDelete = (id: string): Promise<boolean> => invoke('db_case_delete', { id });
+export const rustDbCaseAddRecords = (caseId: string, recordIds: string[]): Promise<number> =>
+  invoke('db_case_add_records', { caseId, recordIds });
+export const rustDbCaseRemoveRecords = (caseId: string, recordIds: string[]): Promise<number> =>
+  invoke('db_case_remove_records', { caseId, recordIds });
+
+export const rustDbStepUpsert = (caseId: string, step: StepItem): Promise<void> => invoke('db_step_upsert', { caseId, step });
+export const rustDbStepDelete = (caseId: string, stepId: string): Promise<boolean> => invoke('db_step_delete', { caseId, stepId });
+export const rustDbAdvisorsSet = (caseId: string, advisors: AdvisorItem[]): Promise<void> =>
+  invoke('db_advisors_set', { caseId, advisors });
+
+// 요약 전문 검색(말마다 앞부분 일치: "폭언" → "폭언을")
+export const rustDbSearch = (query: string, limit?: number): Promise<DbSearchHit[]> => invoke('db_search', { query, limit });
+
+/* -------------------- schema (v1~v7 → v8) -------------------- */
+
+// 같은 종류/경로의 변경은 하나로 묶음(count, 예시 id 최대 3개)
+export type FieldChange = {
+  kind: 'defaulted' | 'dropped' | 'converted';
+  path: string;
+  count: number;
+  examples: string[];
+  note: string;
+};
+
+export type MigrationReport = {
+  fromVersion: number;
+  toVersion: number;
+  // v가 없어 필드 모양으로 버전을 짐작했는지
+  assumedVersion: boolean;
+  steps: { from: number; to: number; changes: FieldChange[] }[];
+};
+
+// 저장 파일/백업 팩 JSON 문자열 → 검증된 v8 상태 + 보고서(앱보다 새 버전이면 에러)
+export const rustSchemaUpgrade = (json: string): Promise<{ state: unknown; report: MigrationReport }> =>
+  invoke('schema_upgrade', { json });
+
+/* -------------------- backup import -------------------- */
+
+// replace: 지금 데이터를 백업으로 바꿈 / mergeNewest: 같은 id는 더 최근에 고친 것(모르면 사본) / mergeCopy: 내용이 다르면 새 id 사본으로 더함
+export type MergeMode = 'replace' | 'mergeNewest' | 'mergeCopy';
+
+export type ImportConflict = {
+  kind: 'record' | 'case';
+  id: string;
+  localLabel: string;
+  incomingLabel: string;
+  resolution: 'takeIncoming' | 'keepLocal' | 'copied';
+  newId?: string;
+  reason: string;
+};
+
+export type ImportDiff = { added: number; updated: number; unchanged: number; keptLocal: number; copied: number; removed: number };
+
+export type ImportResult = {
+  mode: MergeMode;
+  planId: string;
+  incomingRecords: number;
+  incomingCases: number;
+  migration: MigrationReport;
+  records: ImportDiff;
+  cases: ImportDiff;
+  conflicts: ImportConflict[];
+  warnings: string[];
+  // false면 미리보기만
+  applied: boolean;
+  sync?: DbSyncReport;
+};
+
+// Rust backup_crypto::FORMAT과 같음: 암호화 백업(Argon2id + XChaCha20-Poly1305) 파일의 format 값
+export const ENCRYPTED_BACKUP_FORMAT = 'roosycozy-encrypted-backup';
+export const isEncryptedBackup = (parsed: unknown) => (parsed as any)?.format === ENCRYPTED_BACKUP_FORMAT;
+
+// confirm 없이 부르면 미리보기, 미리보기의 planId를 confirm으로 넘기면 저장(그사이 데이터가 바뀌었으면 에러)
+// 암호화 백업이면 두 번 모두 passphrase가 필요
+export const rustImportBackup = (args: { json?: string; fileName?: string; mode: MergeMode; confirm?: string; passphrase?: string }): Promise<ImportResult> =>
+  invoke('import_backup_json', { args });
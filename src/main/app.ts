@@ -1,9 +1,11 @@
 import { invoke } from '@tauri-apps/api/core';
 import { save as saveDialog } from '@tauri-apps/plugin-dialog';
-import { uid, nowISO, toLocalInputValue, fromLocalInputValue, safeParseJSON, defaultState, normalizeState, loadState, saveState, wipeAll, STATUSES } from '../utils';
+import { uid, nowISO, toLocalInputValue, fromLocalInputValue, safeParseJSON, trunc, defaultState, normalizeState, loadState, saveState, wipeAll, STATUSES, IS_TAURI } from '../utils';
+import { rustDbRecordDelete, rustImportBackup, isEncryptedBackup } from '../engine_rust';
+import type { ImportDiff, ImportResult, MergeMode } from '../engine_rust';
 import type { ActorRef, PlaceType, StoreType, Sensitivity, StepItem } from '../engine';
 import { OTHER, casesContainingRecord, addActorToList, buildRecordFromDraft, createCaseWithAdvisors, regenerateCaseAdvisors, buildCaseTimeline, getCaseUpdateCandidates, addRecordsToCase, recordsForCase } from '../engine';
-import { S, setState, ui, toast, runToastAction, log, openConfirm, closeConfirm, openRecordModal, closeRecordModal,  openCaseCreateModal, closeCaseCreateModal, openTimelineModal, closeTimelineModal, openPaperModal, closePaperModal, openPaperPickModal, closePaperPickModal, openCaseUpdateModal, closeCaseUpdateModal, draftRecord, draftCase, draftStep, actorTypeTextFromInternal, actorTypeInternalFromText, getSelectedCase, logs, actorShort, LVS, PLACE_TYPES, STORE_TYPES, UI_OTHER_ACTOR_LABEL } from './state';
+import { S, setState, ui, toast, runToastAction, log, openConfirm, closeConfirm, openPassphrase, closePassphrase, openRecordModal, closeRecordModal,  openCaseCreateModal, closeCaseCreateModal, openTimelineModal, closeTimelineModal, openPaperModal, closePaperModal, openPaperPickModal, closePaperPickModal, openCaseUpdateModal, closeCaseUpdateModal, draftRecord, draftCase, draftStep, actorTypeTextFromInternal, actorTypeInternalFromText, getSelectedCase, logs, actorShort, LVS, PLACE_TYPES, STORE_TYPES, UI_OTHER_ACTOR_LABEL } from './state';
 import { ensurePaperStyles, buildPaperPayload, computeCasePaperHash } from './paper';
 import { render as renderView } from './views';
 
@@ -153,12 +155,60 @@ let _bound = false;
 // backup/restore (file)
 let _restoreFileText: string | null = null;
 let _restoreFileName: string | null = null;
+
+const MERGE_MODE_LABEL: Record<MergeMode, string> = { replace: '전부 바꾸기', mergeNewest: '합치기(최신 것)', mergeCopy: '합치기(사본)' };
+
+const importSummary = (r: ImportResult) => {
+  const d = (x: ImportDiff) =>
+    [`추가 ${x.added}`, `변경 ${x.updated}`, `그대로 ${x.unchanged}`, x.keptLocal && `이 기기 것 유지 ${x.keptLocal}`, x.copied && `사본 ${x.copied}`, x.removed && `삭제 ${x.removed}`]
+      .filter(Boolean).join(' · ');
+  const lines = [
+    `${MERGE_MODE_LABEL[r.mode]} — 백업: 기록 ${r.incomingRecords}건, 케이스 ${r.incomingCases}개 (v${r.migration.fromVersion})`,
+    `기록: ${d(r.records)}`,
+    `케이스: ${d(r.cases)}`,
+  ];
+  if (r.conflicts.length) {
+    lines.push(`같은 id 충돌 ${r.conflicts.length}건:`);
+    for (const c of r.conflicts.slice(0, 5)) lines.push(`- ${c.kind === 'case' ? '케이스' : '기록'} "${trunc(c.incomingLabel || c.id, 24)}": ${c.reason}`);
+    if (r.conflicts.length > 5) lines.push(`  …외 ${r.conflicts.length - 5}건(로그 참고)`);
+  }
+  if (r.warnings.length) lines.push(`주의: ${r.warnings.slice(0, 3).join(' / ')}`);
+  lines.push('', '이대로 가져올까요?');
+  return lines.join('\n');
+};
+
+// Tauri: Rust가 검증/합치기 미리보기 → 확인 → 같은 계획(planId)만 저장 → DB에서 다시 읽음
+const restoreViaRust = async (json: string, encrypted: boolean) => {
+  const mode = ((document.getElementById('restoreMode') as HTMLSelectElement | null)?.value || 'replace') as MergeMode;
+  const passphrase = encrypted ? await openPassphrase('암호화된 백업이에요. 비밀번호를 입력하세요.') : undefined;
+  if (passphrase === null) return;
+  try {
+    const preview = await rustImportBackup({ json, mode, passphrase });
+    log('restore preview', preview);
+    if (!(await openConfirm(importSummary(preview)))) return;
+    const done = await rustImportBackup({ json, mode, passphrase, confirm: preview.planId });
+    log('restore applied', done.sync);
+    const next = await loadState();
+    next.tab = 'cases';
+    next.selectedCaseId = null;
+    setState(next);
+    await saveState(S);
+    syncDraftDefaults();
+    render();
+    closeDlg('restoreModal');
+    toast('복구 완료');
+  } catch (e) {
+    toast(`복구 실패: ${String(e)}`);
+    log('restore failed', e);
+  }
+};
 function bindEvents() {
   if (_bound) return; _bound = true;
 
   const click: Record<string, (btn: HTMLElement) => void | Promise<void>> = {
     'toast-action': () => runToastAction(),
     'confirm-yes': () => closeConfirm(true), 'confirm-no': () => closeConfirm(false),
+    'pass-ok': () => closePassphrase(true), 'pass-cancel': () => closePassphrase(false),
 
     'close-record': () => (closeRecordModal(), render()),
     'clear-record-filters': () => (ui.recFilterActor = ui.recFilterPlace = ui.recFilterKeyword = '', ui.recFilterActorDraft = ui.recFilterPlaceDraft = ui.recFilterKeywordDraft = '', render(), log('record filters cleared')),
@@ -183,9 +233,14 @@ function bindEvents() {
     'case-created-open-paper': async () => { closeDlg('caseCreatedModal'); const c = mustCase(); if (!c) return; ui.paperCaseId = c.id; ui.paperHash = await computeCasePaperHash(c); render(); openPaperModal(); log('paper open (case created modal)', c.id); },
 
     backup: async () => {
-      const json = JSON.stringify({ v: 7, exportedAt: nowISO(), state: S }, null, 2);
+      // 학생 이름/민감 기록이 들어 있어 기본은 암호화. 빈칸으로 확인하면 한 번 더 묻고 평문 저장
+      const passphrase = await openPassphrase('백업 파일을 잠글 비밀번호를 정하세요.\n잊어버리면 이 백업은 복구할 수 없어요.', true);
+      if (passphrase === null) return;
+      if (!passphrase && !(await openConfirm('비밀번호 없이 저장할까요?\n파일을 가진 사람은 누구나 내용을 볼 수 있어요.'))) return;
+
+      const json = JSON.stringify({ v: 8, exportedAt: nowISO(), state: S }, null, 2);
       const ts = nowISO().replace(/[:.]/g, '-').replace('T', '_').replace('Z', '');
-      const suggested = `roosycozy_backup_${ts}.json`;
+      const suggested = `roosycozy_backup_${ts}${passphrase ? '.enc' : ''}.json`;
 
       const path = await saveDialog({
         defaultPath: suggested,
@@ -194,11 +249,11 @@ function bindEvents() {
       if (!path) return;
 
       try {
-        const saved = await invoke<string>('export_backup_json', { args: { fileName: path, json } });
-        toast('백업 파일 저장됨 ✅');
+        const saved = await invoke<string>('export_backup_json', { args: { fileName: path, json, passphrase: passphrase || undefined } });
+        toast(passphrase ? '암호화 백업 저장됨 🔒' : '백업 파일 저장됨 ✅');
         log('backup saved', saved);
       } catch (e) {
-        toast('백업 저장에 실패했어요');
+        toast(`백업 저장에 실패했어요: ${String(e)}`);
         log('backup save failed', e);
       }
     },
@@ -228,6 +283,8 @@ function bindEvents() {
     'do-restore': async () => {
       const parsed = safeParseJSON(_restoreFileText || '');
       if (!parsed || typeof parsed !== 'object') return toast('백업 파일을 먼저 선택하세요');
+      if (IS_TAURI) return restoreViaRust(_restoreFileText || '', isEncryptedBackup(parsed));
+      if (isEncryptedBackup(parsed)) return toast('암호화된 백업은 데스크톱 앱에서만 열 수 있어요');
 
       const next = normalizeState(parsed as any);
       next.tab = 'cases';
@@ -356,9 +413,14 @@ function bindEvents() {
     'delete-record': async (btn) => {
       const id = btn.dataset.id; if (!id) return;
       const r = S.records.find((x) => x.id === id); if (!r) return;
-      const holders = casesContainingRecord(r, S.cases);
+      // 데스크톱은 DB 외래 키가 막는다(웹은 여기서 확인)
+      const holders = IS_TAURI ? [] : casesContainingRecord(r, S.cases);
       if (holders.length) return void (toast(`사건 ${holders.length}개에 포함된 기록이라 삭제할 수 없어요.`), log('delete-record blocked (in cases)', id));
       if (!(await openConfirm('이 기록을 삭제할까요?'))) return;
+      if (IS_TAURI) {
+        try { await rustDbRecordDelete([id]); }
+        catch (e) { return void (toast(String(e)), log('delete-record blocked (db)', e)); }
+      }
       S.records = S.records.filter((x) => x.id !== id); await SR();
       toastUndo('기록 삭제됨', async () => (S.records.unshift(r), await SR(), toast('복구 완료')));
       log('record deleted', id);
@@ -497,7 +559,7 @@ function bindEvents() {
     },
     'regen-advisors': async () => {
       const c = mustCase(); if (!c) return;
-      if (!(await openConfirm('대응 가이드를 현재 규칙으로 다시 생성할까요? (숨긴 대응 가이드은 사라져요)'))) return;
+      if (!(await openConfirm('대응 가이드를 현재 규칙으로 다시 생성할까요? (완료/숨김 상태는 유지돼요)'))) return;
       c.advisors = await regenerateCaseAdvisors(c, S.records); await SR(); toast('대응 가이드 재생성됨'); log('advisors regenerated', c.id);
     },
     'toggle-advisor-done': async (btn) => {
@@ -687,6 +749,12 @@ function bindEvents() {
     }
 
 
+    if (e.key === 'Enter' && ae0 && (ae0.id === 'passInput' || ae0.id === 'passInput2')) {
+      e.preventDefault();
+      if (ae0.id === 'passInput' && dlg('passModal')?.dataset.twice) return void (document.getElementById('passInput2') as HTMLInputElement | null)?.focus();
+      return void closePassphrase(true);
+    }
+
     // Enter로 필터 적용(메모 필터 / 업데이트 필터)
     if (e.key === 'Enter' && !e.metaKey && !e.ctrlKey && !e.shiftKey && !e.altKey) {
       const ae = document.activeElement as HTMLElement | null;
@@ -710,6 +778,7 @@ function bindEvents() {
 
     if (e.key === 'Escape') {
       const c = dlg('confirmModal'); if (c?.open) return void (e.preventDefault(), closeConfirm(false));
+      const pm = dlg('passModal'); if (pm?.open) return void (e.preventDefault(), closePassphrase(false));
       const sm = dlg('savedModal'); if (sm?.open) return void (e.preventDefault(), closeDlg('savedModal'));
       const cm = dlg('caseCreatedModal'); if (cm?.open) return void (e.preventDefault(), closeDlg('caseCreatedModal'));
       closeDlg('restoreModal'); closeDlg('logsModal');
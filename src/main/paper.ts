@@ -820,7 +820,9 @@ export function buildPaperPayload(
   const advisors = keyAdvisors.map((a) => {
     const head = `[${(a.level || '').toUpperCase()}] ${(a.title || '').trim()}`;
     const body1 = (a.body || '').split('\n').map((s) => s.trim()).filter(Boolean)[0] || '';
-    return body1 ? `${head} — ${body1}` : head;
+    const why = (a.reasons || []).filter(Boolean).join(' / ');
+    const line = body1 ? `${head} — ${body1}` : head;
+    return why ? `${line} (근거: ${why})` : line;
   });
 
   const byDay = new Map<string, RecordItem[]>();
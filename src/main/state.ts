@@ -43,7 +43,7 @@ export const ACTOR_TYPES: ActorType[] = ['관리자', '학부모', '학생', '
 export const LVS: Sensitivity[] = ['LV1', 'LV2', 'LV3', 'LV4', 'LV5'];
 
 export const STORE_TYPES: StoreType[] = (['녹취록','통화녹취','음성녹음','문서','공문','가정통신문','회의록','상담록','상담일지','지도일지','교무수첩','업무일지','학급일지','전화','문자','업무메신저','이메일','사진','영상','CCTV','진술서','방문상담','공식채널','기타'] as any) as StoreType[];
-export const PLACE_TYPES: PlaceType[] = (['교실','복도','급식실','보건실','교외','교무실','운동장','상담실','체육관','도서관','행정실','생활지도실','온라인','기타'] as any) as PlaceType[];
+export const PLACE_TYPES: PlaceType[] = (['교실','복도','급식실','보건실','교외','교무실','운동장','상담실','체육관','도서관','행정실','생활지도실','특별실','온라인','기타'] as any) as PlaceType[];
 
 export const STUDENT_NAMES = Array.from({ length: 40 }, (_, i) => `학생${i + 1}`);
 export const PARENT_NAMES = Array.from({ length: 40 }, (_, i) => [`${i + 1}번 모`, `${i + 1}번 부`]).flat();
@@ -170,6 +170,51 @@ export const openConfirm = (message: string) => {
 };
 export const closeConfirm = (ok: boolean) => { confirmResolver?.(ok); confirmResolver = null; const dlg = document.getElementById('confirmModal') as HTMLDialogElement | null; dlg?.open && dlg.close(); };
 
+/* passphrase (암호화 백업) */
+let passResolver: ((v: string | null) => void) | null = null;
+// twice면 확인 칸까지 받아 두 값이 같을 때만 닫힘. 취소하면 null, 빈칸으로 확인하면 ''
+export const openPassphrase = (message: string, twice = false) => {
+  const dlg = document.getElementById('passModal') as HTMLDialogElement | null;
+  if (!dlg || typeof dlg.showModal !== 'function') return Promise.resolve(window.prompt(message));
+  const msgEl = document.getElementById('passMessage');
+  const p1 = document.getElementById('passInput') as HTMLInputElement | null;
+  const p2 = document.getElementById('passInput2') as HTMLInputElement | null;
+  const row2 = document.getElementById('passRow2');
+  const err = document.getElementById('passError');
+  if (msgEl) msgEl.textContent = message;
+  if (p1) p1.value = '';
+  if (p2) p2.value = '';
+  if (row2) row2.hidden = !twice;
+  if (err) err.textContent = '';
+  dlg.dataset.twice = twice ? '1' : '';
+  dlg.showModal();
+  p1?.focus();
+  return new Promise<string | null>((resolve) => {
+    let settled = false;
+    const cleanup = () => { dlg.removeEventListener('cancel', onCancel); dlg.removeEventListener('close', onClose); if (passResolver === resolver) passResolver = null; };
+    const resolver = (v: string | null) => { if (settled) return; settled = true; resolve(v); cleanup(); };
+    const onCancel = (e: Event) => { e.preventDefault(); resolver(null); dlg.open && dlg.close(); };
+    const onClose = () => { if (!settled) resolver(null); };
+    passResolver = resolver;
+    dlg.addEventListener('cancel', onCancel);
+    dlg.addEventListener('close', onClose);
+  });
+};
+export const closePassphrase = (ok: boolean) => {
+  const dlg = document.getElementById('passModal') as HTMLDialogElement | null;
+  const p1 = document.getElementById('passInput') as HTMLInputElement | null;
+  const p2 = document.getElementById('passInput2') as HTMLInputElement | null;
+  if (ok && dlg?.dataset.twice && p1?.value !== p2?.value) {
+    const err = document.getElementById('passError');
+    if (err) err.textContent = '두 비밀번호가 달라요';
+    return p2?.focus();
+  }
+  const v = ok ? p1?.value ?? '' : null;
+  if (p1) p1.value = '';
+  if (p2) p2.value = '';
+  passResolver?.(v); passResolver = null; dlg?.open && dlg.close();
+};
+
 /* dialogs */
 const dlg = (id: string) => document.getElementById(id) as HTMLDialogElement | null;
 const openDlg = (id: string) => { const d = dlg(id); d && !d.open && d.showModal(); };
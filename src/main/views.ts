@@ -1,4 +1,4 @@
-import { esc, trunc, fmt, LS_KEY } from '../utils';
+import { esc, trunc, fmt, LS_KEY, IS_TAURI } from '../utils';
 import type { CaseItem, RecordItem, AdvisorItem, StepItem, ActorRef, RankedHit } from '../engine';
 import { recordActors, recordsForCase, buildCaseTimeline } from '../engine';
 import {
@@ -223,6 +223,7 @@ export function render() {
       ${ENABLE_BACKUP_RESTORE ? renderRestoreModal() : ''}
       ${renderLogsModal()}
       ${renderConfirmModal()}
+      ${renderPassModal()}
 
       ${renderCaseCreateModal()}
       ${renderRecordModal()}
@@ -318,7 +319,7 @@ function renderRestoreModal() {
   if (!ENABLE_BACKUP_RESTORE) return '';
   return H.modal(
     'restoreModal',
-    H.modalHead('복구', '백업 파일(JSON)을 선택해 복구하세요. (현재 데이터 덮어씀)', H.btn('닫기', 'close-restore')),
+    H.modalHead('복구', IS_TAURI ? '백업 파일(JSON)을 선택해 복구하거나 지금 데이터와 합치세요.' : '백업 파일(JSON)을 선택해 복구하세요. (현재 데이터 덮어씀)', H.btn('닫기', 'close-restore')),
     `
       <div class="field" style="margin-top:10px">
         <label>백업 파일</label>
@@ -329,12 +330,21 @@ function renderRestoreModal() {
         <input id="restoreFile" class="srOnly" type="file" accept=".json,application/json" />
         <div id="restoreFileName" class="muted" style="margin-top:10px; font-size:12px">선택된 파일 없음</div>
       </div>
+      ${IS_TAURI ? `
+      <div class="field" style="margin-top:10px">
+        <label>가져오는 방식</label>
+        <select id="restoreMode">
+          <option value="replace">전부 바꾸기(지금 데이터 덮어씀)</option>
+          <option value="mergeNewest">합치기: 같은 기록/케이스는 더 최근에 고친 것</option>
+          <option value="mergeCopy">합치기: 다르면 사본으로 추가(덮어쓰지 않음)</option>
+        </select>
+      </div>` : ''}
 
       <div class="rowInline" style="margin-top:14px">
         ${H.btn('복구', 'do-restore', '', 'btn primary')}
       </div>
       <div class="muted" style="margin-top:10px; font-size:12px">
-        복구하면 지금 데이터는 백업 파일 내용으로 덮어써져요.
+        ${IS_TAURI ? '복구 전에 바뀌는 내용(추가/변경/충돌)을 먼저 보여 드려요.' : '복구하면 지금 데이터는 백업 파일 내용으로 덮어써져요.'}
       </div>
     `
   );
@@ -359,7 +369,7 @@ function renderConfirmModal() {
     'confirmModal',
     H.modalHead('확인', '', ''),
     `
-      <div class="muted" id="confirmMessage"></div>
+      <div class="muted" id="confirmMessage" style="white-space:pre-line"></div>
       <div class="rowInline" style="margin-top:16px">
         ${H.btn('취소', 'confirm-no')}
         ${H.btn('확인', 'confirm-yes', '', 'btn primary')}
@@ -368,6 +378,27 @@ function renderConfirmModal() {
   );
 }
 
+function renderPassModal() {
+  return H.modal(
+    'passModal',
+    H.modalHead('비밀번호', '', ''),
+    `
+      <div class="muted" id="passMessage" style="white-space:pre-line"></div>
+      <div class="field" style="margin-top:10px">
+        <input id="passInput" type="password" autocomplete="new-password" placeholder="비밀번호(8자 이상)" />
+      </div>
+      <div class="field" id="passRow2" style="margin-top:8px">
+        <input id="passInput2" type="password" autocomplete="new-password" placeholder="한 번 더" />
+      </div>
+      <div class="muted" id="passError" style="margin-top:8px; font-size:12px"></div>
+      <div class="rowInline" style="margin-top:16px">
+        ${H.btn('취소', 'pass-cancel')}
+        ${H.btn('확인', 'pass-ok', '', 'btn primary')}
+      </div>
+    `
+  );
+}
+
 /* ==================== RECORDS ==================== */
 
 function renderRecordModal() {
@@ -1089,7 +1120,15 @@ function renderTimelineDetailModal() {
       const relatedHitCount = comp && typeof comp.relatedHits === 'number' ? comp.relatedHits : relatedMatches.length;
       const relatedScore = comp && typeof comp.relatedScore === 'number' ? comp.relatedScore : (relatedHitCount * W_RELATED);
 
-      const engineScore = keywordScore + actorScore + relatedScore;
+      const timeScore = comp && typeof comp.timeScore === 'number' ? comp.timeScore : 0;
+      const timeDistDays = comp && typeof comp.timeDistDays === 'number' ? comp.timeDistDays : null;
+
+      const sensScore = comp && typeof comp.sensScore === 'number' ? comp.sensScore : 0;
+      const phraseScore = comp && typeof comp.phraseScore === 'number' ? comp.phraseScore : 0;
+      const secondScore = comp && typeof comp.secondDegreeScore === 'number' ? comp.secondDegreeScore : 0;
+      const secondCooc = comp && typeof comp.secondDegreeCooc === 'number' ? comp.secondDegreeCooc : 0;
+
+      const engineScore = keywordScore + phraseScore + actorScore + relatedScore + secondScore + timeScore + sensScore;
 
       // 저장된 점수(스냅샷)가 있으면 그걸 우선 표시하고,
       // 혹시 현재 엔진 재계산과 다르면 둘 다 보여줌
@@ -1155,6 +1194,11 @@ function renderTimelineDetailModal() {
                   </div>
                 </div>
 
+                <div class="detailRow">
+                  <div class="k">구/근접</div>
+                  <div class="v">+${esc(phraseScore.toFixed(2))}</div>
+                </div>
+
                 <div class="detailRow">
                   <div class="k">주 Actor 일치</div>
                   <div class="v">${mainActorMatch ? `+${esc(actorScore.toFixed(2))} (일치)` : '0.00 (불일치)'}</div>
@@ -1169,6 +1213,16 @@ function renderTimelineDetailModal() {
                     })
                   </div>
                 </div>
+
+                <div class="detailRow">
+                  <div class="k">2차 관계</div>
+                  <div class="v">+${esc(secondScore.toFixed(2))}${secondCooc ? ` (케이스 당사자와 ${esc(String(secondCooc))}건 함께 등장)` : ''}</div>
+                </div>
+
+                <div class="detailRow">
+                  <div class="k">시간 근접</div>
+                  <div class="v">+${esc(timeScore.toFixed(2))}${timeDistDays === null ? '' : ` (기준과 ${esc(timeDistDays.toFixed(0))}일)`}</div>
+                </div>
               </div>
 
               <div class="detailSection" style="margin-top:12px">
@@ -1,7 +1,10 @@
 // src/utils.ts
 // ultra-ultra-slim: UI helpers + persistence + normalization (single-file)
 
-// -------------------- storage (localStorage only) --------------------
+// -------------------- storage (Tauri: AppDataDir SQLite / Web: localStorage) --------------------
+
+import { invoke } from '@tauri-apps/api/core';
+import { syncRecordStore, updateRecordStore } from './engine_rust';
 
 export const LS_KEY = 'roosycozy_state_v1';
 
@@ -14,9 +17,41 @@ export const IS_TAURI =
 
 const ls = () => (typeof localStorage === 'undefined' ? null : localStorage);
 
-export const storageGet = async (): Promise<string | null> => ls()?.getItem(LS_KEY) ?? null;
-export const storageSet = async (value: string): Promise<void> => void ls()?.setItem(LS_KEY, value);
-export const storageRemove = async (): Promise<void> => void ls()?.removeItem(LS_KEY);
+// Rust load_state 결과: source "sqlite"가 보통. DB가 비어 있으면 예전 JSON 파일(깨졌으면 이전 세대 .1~.5)에서 옮겨 오고 그 출처를 알려 준다
+// migration: 예전 버전(v1~v7) 파일을 v8로 올렸을 때만 온다(engine_rust.ts MigrationReport)
+type LoadedState = { state: unknown | null; source: string; recovered: boolean; errors: string[]; migration?: unknown };
+
+export const storageGet = async (): Promise<string | null> => {
+  if (!IS_TAURI) return ls()?.getItem(LS_KEY) ?? null;
+
+  const r = await invoke<LoadedState>('load_state');
+  if (r.recovered) console.warn(`[storage] 저장 파일 손상 → ${r.source}에서 복구`, r.errors);
+  if (r.migration) console.info('[storage] 예전 버전 저장 파일을 v8로 옮김', r.migration);
+  if (r.state) return JSON.stringify(r.state);
+
+  // 예전 버전(localStorage)에 남은 데이터는 한 번 파일로 옮긴다
+  const legacy = ls()?.getItem(LS_KEY) ?? null;
+  const parsed = legacy ? safeParseJSON(legacy) : null;
+  if (parsed && typeof parsed === 'object') await invoke('save_state', { state: parsed });
+  return legacy;
+};
+
+// Rust save_state/save_changes 결과 중 여기서 보는 것: 저장은 됐지만 알릴 것(스냅샷 실패 등)
+type SyncReport = { warnings?: string[] };
+const warnSaved = (warnings?: string[]) => {
+  if (warnings?.length) console.warn('[storage] 저장은 됐지만 확인할 것', warnings);
+};
+
+// Tauri: 전체 상태를 넘기지만 Rust가 바뀐 행만 쓴다(보통 저장은 saveState가 변경분만 save_changes로 보낸다)
+export const storageSet = async (value: string): Promise<void> => {
+  if (!IS_TAURI) return void ls()?.setItem(LS_KEY, value);
+  warnSaved((await invoke<SyncReport>('save_state', { state: JSON.parse(value) })).warnings);
+};
+
+export const storageRemove = async (): Promise<void> => {
+  if (!IS_TAURI) return void ls()?.removeItem(LS_KEY);
+  warnSaved(await invoke<string[]>('clear_state'));
+};
 
 // -------------------- shared app types --------------------
 
@@ -35,7 +70,7 @@ import type {
 } from './engine';
 
 export type AppState = {
-  v: 7;
+  v: 8;
   tab: 'records' | 'cases';
   selectedCaseId: string | null;
   records: RecordItem[];
@@ -96,7 +131,7 @@ export const trunc = (s: unknown, n: number) => {
 // -------------------- persistence --------------------
 
 export const defaultState = (): AppState => ({
-  v: 7,
+  v: 8,
   tab: 'records',
   selectedCaseId: null,
   records: [],
@@ -131,6 +166,7 @@ const normRecord = (r: any): RecordItem => {
     place: (o.place ?? '기타') as PlaceType,
     placeOther: str(o.placeOther, ''),
     summary: str(o.summary, ''),
+    updatedAt: o.updatedAt ? str(o.updatedAt) : undefined,
   };
 };
 
@@ -165,9 +201,12 @@ const normAdvisor = (a: any): AdvisorItem => {
   } as AdvisorItem;
 };
 
+// 예전 빌드/샘플 팩의 상태 이름(Rust schema::migrate STATUS_ALIASES와 같게)
+const STATUS_ALIASES: Record<string, CaseStatus> = { 완료: '종결', '진행 중': '진행중', 답변준비: '답변 준비' };
+
 const normCase = (raw: any, key: string): CaseItem => {
   const c = obj(raw) ?? {};
-  const st = STATUSES.includes(c.status) ? c.status : '진행중';
+  const st = STATUSES.includes(c.status) ? c.status : STATUS_ALIASES[c.status] ?? '진행중';
   const m = c.mode === 'smart' ? 'smart' : c.mode === 'normal' ? 'normal' : undefined;
 
   return {
@@ -178,6 +217,7 @@ const normCase = (raw: any, key: string): CaseItem => {
     sensFilter: (c.sensFilter ?? 'any') as CaseSensFilter,
     status: st as CaseStatus,
     createdAt: str(c.createdAt, nowISO()),
+    updatedAt: c.updatedAt ? str(c.updatedAt) : undefined,
     steps: arr(c.steps).map(normStep) as any,
     advisors: arr(c.advisors).map(normAdvisor) as any,
     query: str(c.query, ''),
@@ -234,7 +274,58 @@ const loadSamplePack = async (): Promise<any | null> => {
 
 // -------------------- public load/save/wipe --------------------
 
+// Tauri: 불러온 기록으로 Rust 기록 저장소(랭킹 색인)를 통째로 맞춘다. 실패해도 다음 랭킹 때 다시 맞춘다
+// 마지막으로 읽거나 저장한 기록/케이스 모양(updatedAt 제외). 저장할 때 이것과 비교해
+// 바뀐 것에만 updatedAt을 새로 찍고, 데스크톱은 바뀐 것만 Rust로 보낸다(save_changes).
+type Shapes = { records: Map<string, string>; cases: Map<string, string> };
+let savedShapes: Shapes | null = null;
+// 변경분 저장이 실패하면 무엇이 써졌는지 모르니 다음 저장은 전체로
+let needFullSave = false;
+
+const shapeOf = (x: { updatedAt?: string }) => JSON.stringify({ ...x, updatedAt: undefined });
+const shapesOf = (s: AppState): Shapes => ({
+  records: new Map(s.records.map((r) => [r.id, shapeOf(r)])),
+  cases: new Map(Object.values(s.cases).map((c) => [c.id, shapeOf(c)])),
+});
+
+// Rust db::StateChanges
+type StateChanges = {
+  records: RecordItem[];
+  deletedRecordIds: string[];
+  cases: CaseItem[];
+  deletedCaseIds: string[];
+  tab: string;
+  selectedCaseId: string | null;
+};
+
+// 지난 저장과 비교해 바뀐 것에 updatedAt을 찍고 변경분을 돌려준다(지난 저장을 모르면 null)
+const takeChanges = (s: AppState): StateChanges | null => {
+  const prev = savedShapes;
+  const next = shapesOf(s);
+  savedShapes = next;
+  if (!prev) return null;
+  const now = nowISO();
+  const records = s.records.filter((r) => prev.records.get(r.id) !== next.records.get(r.id));
+  const cases = Object.values(s.cases).filter((c) => prev.cases.get(c.id) !== next.cases.get(c.id));
+  for (const x of [...records, ...cases]) x.updatedAt = now;
+  return {
+    records,
+    deletedRecordIds: [...prev.records.keys()].filter((id) => !next.records.has(id)),
+    cases,
+    deletedCaseIds: [...prev.cases.keys()].filter((id) => !next.cases.has(id)),
+    tab: s.tab,
+    selectedCaseId: s.selectedCaseId,
+  };
+};
+
 export const loadState = async (): Promise<AppState> => {
+  const s = await readState();
+  savedShapes = shapesOf(s);
+  if (IS_TAURI) await syncRecordStore(s.records).catch((e) => console.warn('[store] 기록 저장소 동기화 실패', e));
+  return s;
+};
+
+const readState = async (): Promise<AppState> => {
   const raw = await storageGet();
   if (raw) return normalizeState(safeParseJSON(raw)); // ✅ 기존 사용자: 그대로
 
@@ -260,7 +351,17 @@ export const loadState = async (): Promise<AppState> => {
 export const saveState = async (s: AppState) => {
   // 사용자가 저장을 시작하면 wipe 마커 제거(의미상 깨끗하게)
   void ls()?.removeItem(LS_SEED_DISABLED_KEY);
-  return storageSet(JSON.stringify(s));
+  const changes = takeChanges(s);
+  try {
+    if (IS_TAURI && changes && !needFullSave) warnSaved((await invoke<SyncReport>('save_changes', { changes })).warnings);
+    else await storageSet(JSON.stringify(s));
+    needFullSave = false;
+  } catch (e) {
+    needFullSave = true;
+    throw e;
+  }
+  // 추가/수정/삭제된 기록만 Rust 기록 저장소에 반영
+  if (IS_TAURI) await updateRecordStore(s.records).catch((e) => console.warn('[store] 기록 저장소 갱신 실패', e));
 };
 
 export const wipeAll = async () => {